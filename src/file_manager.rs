@@ -1,18 +1,25 @@
-use crate::models::DatabaseInfo;
-use crate::parser::{DatabaseParser, create_sqlite_parser};
+use crate::models::{DatabaseInfo, DatabaseView};
+use crate::parser::journal::journal_path;
+use crate::parser::wal::wal_path;
+use crate::parser::{
+    BatchParseConfig, DatabaseParser, ParseCancelledError, ProgressCallback, create_sqlite_parser,
+};
+use crate::recorder::SessionRecorder;
 use anyhow::Result;
 use gpui::{Context, EventEmitter, Task, Timer};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher, recommended_watcher};
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, mpsc};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, mpsc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc as tokio_mpsc;
 
 #[derive(Debug, Clone)]
 pub enum FileManagerEvent {
     FileOpened(PathBuf, Arc<DatabaseInfo>),
-    FileModified(PathBuf, Arc<DatabaseInfo>),
+    /// The reloaded database and the pages that changed since the last parse
+    FileModified(PathBuf, Arc<DatabaseInfo>, BTreeSet<u32>),
     FileDeleted(PathBuf),
     ParseError(PathBuf, String),
     WatchingStarted(PathBuf),
@@ -68,6 +75,14 @@ pub struct FileManager {
     last_modification: Option<Instant>,
     watching_failed: bool,
     current_parse_cancel: Option<Arc<AtomicBool>>,
+    /// Bumped by every parse, so a parse that a newer one replaced stays quiet
+    parse_generation: Arc<AtomicUsize>,
+    /// Shared with the watcher task so reloads keep the selected view
+    view: Arc<Mutex<DatabaseView>>,
+    /// Latest parse of the current file, which reloads reuse unchanged pages from
+    database_info: Arc<Mutex<Option<Arc<DatabaseInfo>>>>,
+    /// Session file that reloads are appended to while record mode is on
+    recorder: Arc<Mutex<Option<SessionRecorder>>>,
 }
 
 impl FileManager {
@@ -80,6 +95,10 @@ impl FileManager {
             last_modification: None,
             watching_failed: false,
             current_parse_cancel: None,
+            parse_generation: Arc::new(AtomicUsize::new(0)),
+            view: Arc::new(Mutex::new(DatabaseView::default())),
+            database_info: Arc::new(Mutex::new(None)),
+            recorder: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -92,6 +111,10 @@ impl FileManager {
             last_modification: None,
             watching_failed: false,
             current_parse_cancel: None,
+            parse_generation: Arc::new(AtomicUsize::new(0)),
+            view: Arc::new(Mutex::new(DatabaseView::default())),
+            database_info: Arc::new(Mutex::new(None)),
+            recorder: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -103,6 +126,50 @@ impl FileManager {
         self.parse_config = config;
     }
 
+    pub fn view(&self) -> DatabaseView {
+        *lock(&self.view)
+    }
+
+    /// Select the view used by the next parse and by reloads on file changes
+    pub fn set_view(&mut self, view: DatabaseView) {
+        *lock(&self.view) = view;
+    }
+
+    /// Remember the database the current file was last parsed into
+    pub fn set_database_info(&mut self, database_info: Option<Arc<DatabaseInfo>>) {
+        *lock(&self.database_info) = database_info;
+    }
+
+    /// Record the current state of the database to `session_path`, then
+    /// every state the watcher reloads until `stop_recording`
+    pub fn start_recording(&mut self, session_path: PathBuf) -> Result<()> {
+        let database_info = lock(&self.database_info)
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No database loaded"))?;
+        let recorder = SessionRecorder::create(session_path, &database_info)?;
+        *lock(&self.recorder) = Some(recorder);
+        Ok(())
+    }
+
+    /// Finish the recording, returning the session file and the number of
+    /// states it holds
+    pub fn stop_recording(&mut self) -> Option<(PathBuf, usize)> {
+        lock(&self.recorder)
+            .take()
+            .map(|recorder| (recorder.path().to_path_buf(), recorder.snapshot_count()))
+    }
+
+    pub fn is_recording(&self) -> bool {
+        lock(&self.recorder).is_some()
+    }
+
+    /// Number of states recorded so far, including the initial one
+    pub fn recorded_count(&self) -> usize {
+        lock(&self.recorder)
+            .as_ref()
+            .map_or(0, |recorder| recorder.snapshot_count())
+    }
+
     pub fn current_file(&self) -> Option<&Path> {
         self.current_file.as_deref()
     }
@@ -138,45 +205,108 @@ impl FileManager {
     where
         T: EventEmitter<FileManagerEvent> + 'static,
     {
-        let path_clone = path.clone();
-        
-        // Create cancellation flag
-        let cancel_flag = Arc::new(AtomicBool::new(false));
-        self.current_parse_cancel = Some(cancel_flag.clone());
+        let (cancel_flag, generation) = self.start_parse();
+        let parse_generation = self.parse_generation.clone();
+        let superseded = move || parse_generation.load(Ordering::SeqCst) != generation;
 
         // Emit parse started event
         cx.emit(FileManagerEvent::ParseStarted(path.clone()));
 
+        // The parser reports progress from the background thread; it is
+        // forwarded to the entity until the parse finishes and drops the sender
+        let (progress_tx, mut progress_rx) = tokio_mpsc::unbounded_channel();
+        let progress_callback: ProgressCallback = Box::new(move |progress| {
+            // The forwarding task is gone once the entity is, and then there
+            // is no one left to report to
+            if progress_tx.is_closed() {
+                return;
+            }
+            if let Err(e) = progress_tx.send(progress) {
+                eprintln!("Warning: Failed to report parse progress: {}", e);
+            }
+        });
+        let config = BatchParseConfig {
+            batch_size: self.parse_config.batch_size,
+            enable_cancellation: self.parse_config.enable_cancellation,
+            ..BatchParseConfig::default()
+        };
+
+        let view = self.view();
+        let parse_path = path.clone();
+        let parse_task = cx.background_spawn(async move {
+            create_sqlite_parser().parse_file_with_progress(
+                &parse_path,
+                view,
+                Some(progress_callback),
+                Some(cancel_flag),
+                Some(config),
+            )
+        });
+
+        let latest = self.database_info.clone();
+        let current_view = self.view.clone();
         cx.spawn(async move |entity, cx| {
-            let parser = create_sqlite_parser();
-            
-            // Simple parsing with progress indication
-            let result = parser.parse_file(&path_clone);
-            
+            while let Some(progress) = progress_rx.recv().await {
+                if superseded() {
+                    continue;
+                }
+                let _ = entity.update(cx, |_this, cx| {
+                    cx.emit(FileManagerEvent::ParseProgress(path.clone(), progress));
+                });
+            }
+
+            let result = parse_task.await;
+            // The parse that replaced this one reports for both
+            if superseded() {
+                return result;
+            }
+            if let Ok(database_info) = &result
+                && database_info.view == *lock(&current_view)
+            {
+                *lock(&latest) = Some(database_info.clone());
+            }
+
             // Emit completion events
             let _ = entity.update(cx, |_this, cx| {
                 match &result {
                     Ok(_) => {
-                        cx.emit(FileManagerEvent::ParseCompleted(path_clone.clone()));
+                        cx.emit(FileManagerEvent::ParseCompleted(path.clone()));
+                    }
+                    Err(e) if e.is::<ParseCancelledError>() => {
+                        cx.emit(FileManagerEvent::ParseCancelled(path.clone()));
                     }
                     Err(e) => {
-                        cx.emit(FileManagerEvent::ParseError(path_clone.clone(), e.to_string()));
+                        cx.emit(FileManagerEvent::ParseError(path.clone(), e.to_string()));
                     }
                 }
             });
-            
+
             result
         })
     }
 
     pub fn cancel_current_parse(&mut self) {
         if let Some(ref cancel_flag) = self.current_parse_cancel {
-            cancel_flag.store(true, Ordering::Relaxed);
+            cancel_flag.store(true, Ordering::SeqCst);
         }
     }
 
+    /// Cancel the parse in flight, if any, and return the cancel flag and
+    /// generation of the one replacing it
+    fn start_parse(&mut self) -> (Arc<AtomicBool>, usize) {
+        self.cancel_current_parse();
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        self.current_parse_cancel = Some(cancel_flag.clone());
+        let generation = self.parse_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        (cancel_flag, generation)
+    }
+
+    /// The background parse holds the other reference to its cancel flag
+    /// until it finishes
     pub fn is_parsing(&self) -> bool {
-        self.current_parse_cancel.is_some()
+        self.current_parse_cancel
+            .as_ref()
+            .is_some_and(|cancel_flag| Arc::strong_count(cancel_flag) > 1)
     }
 
     pub fn set_current_file(&mut self, path: Option<PathBuf>) {
@@ -201,10 +331,16 @@ impl FileManager {
         let (sync_tx, sync_rx) = mpsc::channel();
         let (async_tx, mut async_rx) = tokio_mpsc::unbounded_channel();
 
-        // Try to create and configure watcher
+        // Watch the directory rather than the file: writers in WAL mode only
+        // touch the -wal sibling until a checkpoint, and SQLite creates and
+        // deletes the -wal and -journal files as it goes
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
         let watcher_result = recommended_watcher(sync_tx)
             .and_then(|mut w| {
-                w.watch(path, RecursiveMode::NonRecursive)?;
+                w.watch(directory, RecursiveMode::NonRecursive)?;
                 Ok(w)
             });
 
@@ -263,9 +399,11 @@ impl FileManager {
         let path_clone = path.to_path_buf();
         let debounce_duration = self.watcher_config.debounce_duration;
         let _reload_timeout = self.watcher_config.reload_timeout;
+        let view = self.view.clone();
+        let latest = self.database_info.clone();
+        let recorder = self.recorder.clone();
         
         cx.spawn(async move |entity, cx| {
-            let parser = create_sqlite_parser();
             let mut last_event_time: Option<Instant> = None;
             let mut consecutive_errors = 0u32;
             const MAX_CONSECUTIVE_ERRORS: u32 = 5;
@@ -274,15 +412,13 @@ impl FileManager {
                 match async_rx.recv().await {
                     Some(event_result) => {
                         eprintln!("DEBUG: Received file event: {:?}", event_result);
+                        let change = match &event_result {
+                            Ok(event) => watched_change(&path_clone, event),
+                            Err(_) => WatchedChange::Ignored,
+                        };
+
                         match event_result {
-                            Ok(Event {
-                                kind: EventKind::Modify(_),
-                                ..
-                            })
-                            | Ok(Event {
-                                kind: EventKind::Create(_),
-                                ..
-                            }) => {
+                            Ok(_) if change == WatchedChange::Reload => {
                                 let now = Instant::now();
                                 
                                 // Debounce rapid file changes
@@ -296,19 +432,67 @@ impl FileManager {
                                 // Wait for debounce period to ensure file is fully written
                                 Timer::after(debounce_duration).await;
 
-                                // Parse file - use simple parsing for file watching
-                                let parse_result = parser.parse_file(&path_clone);
+                                // Reparse off the UI thread, reusing the pages that did not change
+                                let current_view = *lock(&view);
+                                let previous = lock(&latest).clone();
+                                let reload_path = path_clone.clone();
+                                let recorder = recorder.clone();
+                                let parse_result = cx
+                                    .background_spawn(async move {
+                                        let parser = create_sqlite_parser();
+                                        let result = match previous {
+                                            Some(previous) => parser.reparse_file_view(
+                                                &reload_path,
+                                                current_view,
+                                                &previous,
+                                            ),
+                                            None => parser
+                                                .parse_file_view(&reload_path, current_view)
+                                                .map(|info| {
+                                                    let changed = info.pages.keys().copied().collect();
+                                                    (info, changed)
+                                                }),
+                                        };
+
+                                        if let Ok((database_info, _)) = &result
+                                            && let Some(recorder) = lock(&recorder).as_mut()
+                                            && let Err(e) = recorder.record(database_info)
+                                        {
+                                            eprintln!(
+                                                "Warning: Failed to record {} to {}: {}",
+                                                reload_path.display(),
+                                                recorder.path().display(),
+                                                e
+                                            );
+                                        }
+                                        result
+                                    })
+                                    .await;
 
                                 match parse_result {
-                                    Ok(database_info) => {
+                                    Ok((database_info, changed_pages)) => {
                                         consecutive_errors = 0; // Reset error count on success
-                                        
+
+                                        let unchanged = lock(&latest)
+                                            .as_ref()
+                                            .is_some_and(|previous| Arc::ptr_eq(previous, &database_info));
+                                        if unchanged {
+                                            eprintln!("DEBUG: {} is unchanged, skipping reload", path_clone.display());
+                                            continue;
+                                        }
+                                        *lock(&latest) = Some(database_info.clone());
+
                                         // File was modified - emit event to update UI
                                         if entity.update(cx, |_this, cx| {
-                                            eprintln!("File modified and reloaded: {}", path_clone.display());
+                                            eprintln!(
+                                                "File modified and reloaded: {} ({} pages changed)",
+                                                path_clone.display(),
+                                                changed_pages.len()
+                                            );
                                             cx.emit(FileManagerEvent::FileModified(
                                                 path_clone.clone(),
                                                 database_info,
+                                                changed_pages,
                                             ));
                                         }).is_err() {
                                             eprintln!("DEBUG: Failed to emit FileModified event - entity dropped");
@@ -345,10 +529,7 @@ impl FileManager {
 
                                 }
                             }
-                            Ok(Event {
-                                kind: EventKind::Remove(_),
-                                ..
-                            }) => {
+                            Ok(_) if change == WatchedChange::Deleted => {
                                 // File was deleted - emit event and stop watching
                                 if entity.update(cx, |_this, cx| {
                                     cx.emit(FileManagerEvent::FileDeleted(path_clone.clone()));
@@ -454,6 +635,39 @@ impl Default for FileManager {
     }
 }
 
+/// Lock state shared with the watcher and parse tasks. Every critical section
+/// leaves the value whole, so a panic in one does not make it unusable.
+/// What a watcher event in the database's directory means for the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchedChange {
+    Reload,
+    Deleted,
+    Ignored,
+}
+
+fn watched_change(path: &Path, event: &Event) -> WatchedChange {
+    let companions = [wal_path(path), journal_path(path)];
+    let is_database = |p: &PathBuf| p.file_name() == path.file_name();
+    let is_companion =
+        |p: &PathBuf| companions.iter().any(|companion| p.file_name() == companion.file_name());
+
+    if !event.paths.iter().any(|p| is_database(p) || is_companion(p)) {
+        return WatchedChange::Ignored;
+    }
+    match event.kind {
+        EventKind::Modify(_) | EventKind::Create(_) => WatchedChange::Reload,
+        EventKind::Remove(_) if event.paths.iter().any(is_database) => WatchedChange::Deleted,
+        // A checkpoint deletes the WAL and a commit the journal, which changes
+        // the effective database
+        EventKind::Remove(_) => WatchedChange::Reload,
+        _ => WatchedChange::Ignored,
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -531,6 +745,15 @@ mod tests {
         assert!(parse_config.enable_cancellation);
     }
 
+    #[test]
+    fn test_view_selection() {
+        let mut file_manager = FileManager::new();
+        assert_eq!(file_manager.view(), DatabaseView::WithWal);
+
+        file_manager.set_view(DatabaseView::MainFile);
+        assert_eq!(file_manager.view(), DatabaseView::MainFile);
+    }
+
     #[test]
     fn test_watching_state_management() {
         let mut file_manager = FileManager::new();
@@ -547,4 +770,86 @@ mod tests {
         assert!(!file_manager.has_watching_failed());
         assert!(!file_manager.is_watching());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_new_parse_cancels_the_previous_one() {
+        use crate::parser::PageReadStrategy;
+        use crate::parser::test_support::database_image;
+
+        let path = std::env::temp_dir().join(format!(
+            "sqlite-browser-superseded-{}.db",
+            std::process::id()
+        ));
+        std::fs::write(&path, database_image(1024, 50, 0, |page_number| page_number % 7 + 1)).unwrap();
+        let config = BatchParseConfig {
+            batch_size: 1,
+            progress_update_interval: Duration::ZERO,
+            strategy: PageReadStrategy::Sequential,
+            ..BatchParseConfig::default()
+        };
+        let mut file_manager = FileManager::new();
+
+        // The first parse stalls in its first progress report until the second starts
+        let (first_cancel, _) = file_manager.start_parse();
+        let (started_tx, started_rx) = mpsc::channel();
+        let (resume_tx, resume_rx) = mpsc::channel::<()>();
+        let resume_rx = Mutex::new(resume_rx);
+        let progress: ProgressCallback = Box::new(move |_| {
+            if started_tx.send(()).is_ok() {
+                // Fails once resume_tx is dropped, which lets later reports through
+                lock(&resume_rx).recv().unwrap_or(());
+            }
+        });
+        let first = std::thread::spawn({
+            let path = path.clone();
+            let config = config.clone();
+            move || {
+                create_sqlite_parser().parse_file_with_progress(
+                    &path,
+                    DatabaseView::WithWal,
+                    Some(progress),
+                    Some(first_cancel),
+                    Some(config),
+                )
+            }
+        });
+
+        started_rx.recv().unwrap();
+        let (second_cancel, _) = file_manager.start_parse();
+        drop(started_rx);
+        drop(resume_tx);
+        let first = first.join().unwrap();
+        let second = create_sqlite_parser().parse_file_with_progress(
+            &path,
+            DatabaseView::WithWal,
+            None,
+            Some(second_cancel),
+            Some(config),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(first.unwrap_err().is::<ParseCancelledError>());
+        assert_eq!(second.unwrap().pages.len(), 50);
+    }
+
+    #[test]
+    fn test_watched_change_filters_directory_events() {
+        use notify::event::{CreateKind, ModifyKind, RemoveKind};
+
+        let path = PathBuf::from("/data/app.db");
+        let event = |kind, name: &str| Event::new(kind).add_path(PathBuf::from("/data").join(name));
+        let modify = EventKind::Modify(ModifyKind::Any);
+        let remove = EventKind::Remove(RemoveKind::File);
+
+        assert_eq!(watched_change(&path, &event(modify, "app.db")), WatchedChange::Reload);
+        assert_eq!(
+            watched_change(&path, &event(EventKind::Create(CreateKind::File), "app.db-wal")),
+            WatchedChange::Reload
+        );
+        assert_eq!(watched_change(&path, &event(remove, "app.db-journal")), WatchedChange::Reload);
+        assert_eq!(watched_change(&path, &event(remove, "app.db")), WatchedChange::Deleted);
+        // Other files in the directory, the shared-memory index included
+        assert_eq!(watched_change(&path, &event(modify, "app.db-shm")), WatchedChange::Ignored);
+        assert_eq!(watched_change(&path, &event(remove, "other.db")), WatchedChange::Ignored);
+    }
+}
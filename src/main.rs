@@ -3,7 +3,9 @@ use std::path::PathBuf;
 
 mod file_manager;
 mod models;
+mod page_editor;
 mod parser;
+mod recorder;
 mod ui;
 
 use ui::SqliteBrowser;
@@ -22,13 +24,20 @@ fn main() {
                 cx.new(|cx| {
                     let mut browser = SqliteBrowser::new(cx);
 
-                    // Try to open file from command line argument, or show file dialog
-                    if let Some(path) = std::env::args().nth(1) {
-                        let path = PathBuf::from(path);
-                        browser.try_open_file_or_dialog(path, cx).detach();
-                    } else {
-                        // No file provided, show file dialog
-                        browser.open_file_dialog(cx).detach();
+                    // Try to open file from command line argument, or show file dialog.
+                    // A second file is compared against the first.
+                    let mut args = std::env::args().skip(1).map(PathBuf::from);
+                    match (args.next(), args.next()) {
+                        (Some(before), Some(after)) => {
+                            browser.open_and_compare(before, after, cx);
+                        }
+                        (Some(path), None) => {
+                            browser.try_open_file_or_dialog(path, cx).detach();
+                        }
+                        _ => {
+                            // No file provided, show file dialog
+                            browser.open_file_dialog(cx).detach();
+                        }
                     }
 
                     browser
@@ -1,8 +1,14 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
 
 use gpui::{Hsla, rgb};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct DatabaseHeader {
     pub magic: [u8; 16],
     pub page_size: u16,
@@ -40,6 +46,42 @@ impl DatabaseHeader {
     pub fn is_valid_sqlite_file(&self) -> bool {
         &self.magic[..16] == b"SQLite format 3\0"
     }
+
+    pub fn encoding(&self) -> TextEncoding {
+        TextEncoding::from_header(self.text_encoding)
+    }
+
+    /// Auto-vacuum and incremental-vacuum databases keep pointer-map pages
+    pub fn has_pointer_map(&self) -> bool {
+        self.largest_root_btree_page != 0
+    }
+}
+
+/// Text encoding declared in the database header, used for TEXT record values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16le,
+    Utf16be,
+}
+
+impl TextEncoding {
+    pub fn from_header(value: u32) -> Self {
+        match value {
+            2 => TextEncoding::Utf16le,
+            3 => TextEncoding::Utf16be,
+            // 1 is UTF-8; treat anything else the same way rather than failing
+            _ => TextEncoding::Utf8,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16le => "UTF-16le",
+            TextEncoding::Utf16be => "UTF-16be",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -112,6 +154,26 @@ impl PageType {
         }
     }
 
+    /// Short description of the page's role within the b-tree that owns it
+    pub fn role(&self) -> &'static str {
+        match self {
+            PageType::TableBTreeInterior | PageType::IndexBTreeInterior => "interior",
+            PageType::TableBTreeLeaf | PageType::IndexBTreeLeaf => "leaf",
+            PageType::PayloadOverflow => "overflow",
+            other => other.name(),
+        }
+    }
+
+    pub fn is_btree(&self) -> bool {
+        matches!(
+            self,
+            PageType::TableBTreeInterior
+                | PageType::IndexBTreeInterior
+                | PageType::TableBTreeLeaf
+                | PageType::IndexBTreeLeaf
+        )
+    }
+
     pub fn has_rightmost_pointer(&self) -> bool {
         matches!(
             self,
@@ -120,14 +182,352 @@ impl PageType {
     }
 }
 
+/// A single cell on a b-tree page, decoded from the cell pointer array
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellInfo {
+    pub index: u16,
+    /// Offset of the cell from the start of the page
+    pub offset: u32,
+    /// Bytes the cell occupies on the page, including any overflow pointer
+    pub size: u32,
+    /// Left child page, present on interior pages only
+    pub left_child: Option<u32>,
+    /// Rowid of table cells, or the key of table interior cells
+    pub rowid: Option<i64>,
+    /// Total payload length, absent on table interior cells which carry no payload
+    pub payload_size: Option<u64>,
+    /// Offset of the payload from the start of the page
+    pub payload_offset: u32,
+    /// Portion of the payload stored on this page
+    pub local_payload_size: u64,
+    pub first_overflow_page: Option<u32>,
+}
+
+impl CellInfo {
+    /// Payload bytes stored in the overflow chain rather than on the page
+    pub fn overflow_bytes(&self) -> u64 {
+        self.payload_size
+            .map_or(0, |size| size.saturating_sub(self.local_payload_size))
+    }
+}
+
+/// A single value decoded from SQLite's record format
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordValue {
+    Null,
+    Integer(i64),
+    Float(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl RecordValue {
+    /// Short SQL-literal style rendering, truncated to roughly `max_len` characters
+    pub fn display(&self, max_len: usize) -> String {
+        let full = match self {
+            RecordValue::Null => return "NULL".to_string(),
+            RecordValue::Integer(value) => return value.to_string(),
+            RecordValue::Float(value) => return value.to_string(),
+            RecordValue::Text(text) => format!("'{}'", text),
+            RecordValue::Blob(bytes) => {
+                let hex: String = bytes
+                    .iter()
+                    .take(max_len / 2 + 1)
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect();
+                format!("x'{}'", hex)
+            }
+        };
+
+        if full.chars().count() > max_len {
+            let truncated: String = full.chars().take(max_len.saturating_sub(1)).collect();
+            format!("{}…", truncated)
+        } else {
+            full
+        }
+    }
+}
+
+/// A decoded table row or index entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub cell_index: u16,
+    pub rowid: Option<i64>,
+    pub values: Vec<RecordValue>,
+    /// False when part of the payload could not be read, e.g. a broken overflow chain
+    pub complete: bool,
+}
+
+/// A row of the `sqlite_schema` table describing a table, index, view or trigger
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaObject {
+    pub object_type: String,
+    pub name: String,
+    pub table_name: String,
+    /// Root page of the object's b-tree, 0 for views and triggers
+    pub root_page: u32,
+    pub sql: Option<String>,
+}
+
+/// The table or index whose b-tree a page belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageOwner {
+    pub object_type: String,
+    pub name: String,
+}
+
+impl PageOwner {
+    pub fn label(&self) -> String {
+        format!("{} {}", self.object_type, self.name)
+    }
+}
+
+/// A page within a reconstructed b-tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct BTreeNode {
+    pub page_number: u32,
+    /// Distance from the root page, which is at depth 0
+    pub depth: u32,
+    pub children: Vec<u32>,
+}
+
+impl BTreeNode {
+    pub fn fan_out(&self) -> usize {
+        self.children.len()
+    }
+}
+
+/// A table or index b-tree linked from its root page down to its leaves
+#[derive(Debug, Clone)]
+pub struct BTree {
+    pub root_page: u32,
+    pub owner: PageOwner,
+    /// Nodes in breadth-first order, so each level is contiguous
+    pub nodes: Vec<BTreeNode>,
+}
+
+impl BTree {
+    /// Number of levels, 1 for a tree that is a single leaf
+    pub fn height(&self) -> u32 {
+        self.nodes.last().map_or(0, |node| node.depth + 1)
+    }
+
+    pub fn levels(&self) -> Vec<&[BTreeNode]> {
+        self.nodes
+            .chunk_by(|a, b| a.depth == b.depth)
+            .collect()
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Average number of children per interior page
+    pub fn average_fan_out(&self) -> f32 {
+        let interior: Vec<_> = self.nodes.iter().filter(|node| node.fan_out() > 0).collect();
+        if interior.is_empty() {
+            return 0.0;
+        }
+        interior.iter().map(|node| node.fan_out()).sum::<usize>() as f32 / interior.len() as f32
+    }
+}
+
+/// Links a payload overflow page back to the b-tree cell whose payload spilled into it
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverflowOwner {
+    pub page_number: u32,
+    pub cell_index: u16,
+    /// 1-based position of this page within the overflow chain
+    pub chain_position: u32,
+    pub chain_length: u32,
+}
+
+/// Entry type stored in a pointer-map page for auto-vacuum databases
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PtrMapType {
+    RootPage,
+    FreePage,
+    Overflow1,
+    Overflow2,
+    BTree,
+    Invalid(u8),
+}
+
+impl PtrMapType {
+    pub fn from_byte(byte: u8) -> Self {
+        match byte {
+            1 => PtrMapType::RootPage,
+            2 => PtrMapType::FreePage,
+            3 => PtrMapType::Overflow1,
+            4 => PtrMapType::Overflow2,
+            5 => PtrMapType::BTree,
+            other => PtrMapType::Invalid(other),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PtrMapType::RootPage => "B-Tree Root",
+            PtrMapType::FreePage => "Free Page",
+            PtrMapType::Overflow1 => "First Overflow",
+            PtrMapType::Overflow2 => "Overflow",
+            PtrMapType::BTree => "B-Tree Child",
+            PtrMapType::Invalid(_) => "Invalid",
+        }
+    }
+}
+
+/// The pointer-map entry describing a page, checked against what the parser found
+#[derive(Debug, Clone, PartialEq)]
+pub struct PtrMapEntry {
+    pub entry_type: PtrMapType,
+    pub parent_page: u32,
+    /// Set when the entry disagrees with the page ownership discovered by the parser
+    pub mismatch: Option<String>,
+}
+
+/// Pointer-map layout of an auto-vacuum or incremental-vacuum database
+#[derive(Debug, Clone, Default)]
+pub struct PointerMapInfo {
+    pub ptrmap_pages: Vec<u32>,
+    pub entry_count: usize,
+    pub mismatch_count: usize,
+}
+
+/// Kind of structural problem the integrity check found on a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FindingKind {
+    /// A cell pointer outside the cell content area
+    CellPointerOutOfBounds,
+    CellsOverlap,
+    /// The cell content area starts before the end of the cell pointer array
+    ContentInPointerArray,
+    /// A freeblock chain that loops, runs off the page or leaves the content area
+    FreeblockChain,
+    /// More fragmented bytes than SQLite ever leaves on a page
+    Fragmentation,
+    /// A child pointer to a page past the end of the file
+    ChildPastEnd,
+    /// The page is the child of more than one interior cell
+    MultipleParents,
+}
+
+impl FindingKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FindingKind::CellPointerOutOfBounds => "Cell pointer out of bounds",
+            FindingKind::CellsOverlap => "Overlapping cells",
+            FindingKind::ContentInPointerArray => "Content in pointer array",
+            FindingKind::FreeblockChain => "Bad freeblock chain",
+            FindingKind::Fragmentation => "Excess fragmentation",
+            FindingKind::ChildPastEnd => "Child past end of file",
+            FindingKind::MultipleParents => "Multiple parents",
+        }
+    }
+
+    /// Found by looking at the page alone, rather than at the pages linking to it
+    pub fn is_local(&self) -> bool {
+        !matches!(self, FindingKind::ChildPastEnd | FindingKind::MultipleParents)
+    }
+}
+
+/// A structural problem on a page, found without asking SQLite
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrityFinding {
+    pub kind: FindingKind,
+    pub message: String,
+}
+
+/// A gap in the cell content area left by a deleted cell, linked into the
+/// page's freeblock chain for reuse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Freeblock {
+    /// Offset of the freeblock from the start of the page
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// What a run of bytes on a page holds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageRegionKind {
+    /// The 100-byte database header at the start of page 1
+    DatabaseHeader,
+    PageHeader,
+    CellPointers,
+    /// Gap between the cell pointer array and the cell content area
+    Unallocated,
+    /// A cell, by its index in the cell pointer array
+    Cell(u16),
+    Freeblock,
+    /// Gap in the cell content area too small to join the freeblock chain
+    Fragment,
+    /// Everything on a page that is not a b-tree page
+    Content,
+    /// Bytes at the end of every page reserved for extensions
+    Reserved,
+}
+
+impl PageRegionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PageRegionKind::DatabaseHeader => "Database header",
+            PageRegionKind::PageHeader => "Page header",
+            PageRegionKind::CellPointers => "Cell pointers",
+            PageRegionKind::Unallocated => "Unallocated",
+            PageRegionKind::Cell(_) => "Cell",
+            PageRegionKind::Freeblock => "Freeblock",
+            PageRegionKind::Fragment => "Fragment",
+            PageRegionKind::Content => "Content",
+            PageRegionKind::Reserved => "Reserved",
+        }
+    }
+}
+
+/// A run of bytes on a page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageRegion {
+    pub kind: PageRegionKind,
+    /// Offset of the region from the start of the page
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl PageRegion {
+    pub fn end(&self) -> u32 {
+        self.offset + self.length
+    }
+
+    pub fn label(&self) -> String {
+        match self.kind {
+            PageRegionKind::Cell(index) => format!("Cell {}", index),
+            kind => kind.name().to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PageInfo {
     pub page_number: u32,
     pub page_type: PageType,
     pub cell_count: u16,
-    pub free_space: u16,
+    /// Unallocated gap between the cell pointer array and the cell content area
+    pub free_space: u32,
     pub fragmented_bytes: u8,
     pub rightmost_pointer: Option<u32>,
+    /// Freeblock chain in page order, up to the first broken link
+    pub freeblocks: Vec<Freeblock>,
+    pub overflow_owner: Option<OverflowOwner>,
+    pub ptrmap_entry: Option<PtrMapEntry>,
+    pub cells: Vec<CellInfo>,
+    pub owner: Option<PageOwner>,
+    /// Latest committed WAL frame holding a newer version of this page
+    pub wal_frame: Option<u32>,
+    /// The rollback journal holds an original image of this page
+    pub journaled: bool,
+    /// Hash of the page bytes, compared on reload to find changed pages
+    pub content_hash: u64,
+    /// Problems found by the structural integrity check
+    pub findings: Vec<IntegrityFinding>,
 }
 
 impl PageInfo {
@@ -135,7 +535,7 @@ impl PageInfo {
         page_number: u32,
         page_type: PageType,
         cell_count: u16,
-        free_space: u16,
+        free_space: u32,
         fragmented_bytes: u8,
         rightmost_pointer: Option<u32>,
     ) -> Self {
@@ -146,31 +546,562 @@ impl PageInfo {
             free_space,
             fragmented_bytes,
             rightmost_pointer,
+            freeblocks: Vec::new(),
+            overflow_owner: None,
+            ptrmap_entry: None,
+            cells: Vec::new(),
+            owner: None,
+            wal_frame: None,
+            journaled: false,
+            content_hash: 0,
+            findings: Vec::new(),
+        }
+    }
+
+    pub fn with_cells(mut self, cells: Vec<CellInfo>) -> Self {
+        self.cells = cells;
+        self
+    }
+
+    pub fn with_content_hash(mut self, content_hash: u64) -> Self {
+        self.content_hash = content_hash;
+        self
+    }
+
+    pub fn with_freeblocks(mut self, freeblocks: Vec<Freeblock>) -> Self {
+        self.freeblocks = freeblocks;
+        self
+    }
+
+    pub fn with_findings(mut self, findings: Vec<IntegrityFinding>) -> Self {
+        self.findings = findings;
+        self
+    }
+
+    /// Copy of a b-tree page as the page loop parsed it, before owners,
+    /// pointer-map entries, findings about other pages' links to it and WAL
+    /// or journal state were attached
+    pub fn parsed_only(&self) -> Self {
+        Self {
+            owner: None,
+            overflow_owner: None,
+            ptrmap_entry: None,
+            wal_frame: None,
+            journaled: false,
+            findings: self
+                .findings
+                .iter()
+                .filter(|finding| finding.kind.is_local())
+                .cloned()
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// One-line description such as "Page 812 — index idx_orders_customer (leaf)"
+    pub fn summary(&self) -> String {
+        match &self.owner {
+            Some(owner) => format!(
+                "Page {} — {} ({})",
+                self.page_number,
+                owner.label(),
+                self.page_type.role()
+            ),
+            None => format!("Page {} — {}", self.page_number, self.page_type.name()),
+        }
+    }
+
+    /// Child pages of an interior page: each cell's left child followed by the rightmost pointer
+    pub fn child_pages(&self) -> impl Iterator<Item = u32> + '_ {
+        self.cells
+            .iter()
+            .filter_map(|cell| cell.left_child)
+            .chain(self.rightmost_pointer)
+    }
+
+    pub fn with_overflow_owner(mut self, owner: OverflowOwner) -> Self {
+        self.overflow_owner = Some(owner);
+        self
+    }
+
+    /// The page's bytes from start to end as contiguous regions. Cells and
+    /// freeblocks are placed at their offsets, and any other gap in the cell
+    /// content area is a fragment. Pages other than b-tree pages are shown
+    /// as a single content region.
+    pub fn layout(&self, page_size: usize, usable_size: usize) -> Vec<PageRegion> {
+        let page_size = page_size as u32;
+        let usable_size = (usable_size as u32).min(page_size);
+        let mut regions = Vec::new();
+        let mut push = |kind, offset: u32, end: u32| {
+            if end > offset {
+                regions.push(PageRegion {
+                    kind,
+                    offset,
+                    length: end - offset,
+                });
+            }
+        };
+
+        let header_offset = if self.page_number == 1 { 100 } else { 0 };
+        push(PageRegionKind::DatabaseHeader, 0, header_offset);
+
+        if self.page_type.is_btree() {
+            let header_size = if self.rightmost_pointer.is_some() { 12 } else { 8 };
+            let pointers_start = header_offset + header_size;
+            let pointers_end = (pointers_start + self.cell_count as u32 * 2).min(usable_size);
+            let content_start = (pointers_end + self.free_space).min(usable_size);
+            push(PageRegionKind::PageHeader, header_offset, pointers_start);
+            push(PageRegionKind::CellPointers, pointers_start, pointers_end);
+            push(PageRegionKind::Unallocated, pointers_end, content_start);
+
+            let mut blocks: Vec<_> = self
+                .cells
+                .iter()
+                .map(|cell| (PageRegionKind::Cell(cell.index), cell.offset, cell.size))
+                .chain(self.freeblocks.iter().map(|freeblock| {
+                    (PageRegionKind::Freeblock, freeblock.offset, freeblock.size)
+                }))
+                .collect();
+            blocks.sort_by_key(|&(_, offset, _)| offset);
+
+            // Overlapping blocks on a corrupt page are cut short where the
+            // previous one ends
+            let mut cursor = content_start;
+            for (kind, offset, size) in blocks {
+                let start = offset.max(cursor);
+                let end = (offset + size).min(usable_size);
+                if end <= start {
+                    continue;
+                }
+                push(PageRegionKind::Fragment, cursor, start);
+                push(kind, start, end);
+                cursor = end;
+            }
+            push(PageRegionKind::Fragment, cursor, usable_size);
+        } else {
+            push(PageRegionKind::Content, header_offset, usable_size);
         }
+
+        push(PageRegionKind::Reserved, usable_size, page_size);
+        regions
+    }
+
+    /// Bytes held by the freeblock chain
+    pub fn freeblock_space(&self) -> u32 {
+        self.freeblocks.iter().map(|freeblock| freeblock.size).sum()
+    }
+
+    /// Every byte a new cell could reuse: the unallocated gap, the freeblocks
+    /// and the fragments too small to be freeblocks
+    pub fn total_free_space(&self) -> u32 {
+        self.free_space + self.freeblock_space() + self.fragmented_bytes as u32
     }
 
     pub fn utilization_percent(&self, page_size: usize) -> f32 {
         if page_size == 0 {
             return 0.0;
         }
-        
-        let size_u16 = page_size as u16;
-        let used_space = if self.free_space <= size_u16 {
-            size_u16 - self.free_space
-        } else {
-            // Handle invalid data gracefully - free space cannot exceed page size
-            0
-        };
-        
+
+        // Invalid data can claim more free space than the page holds
+        let used_space = (page_size as u32).saturating_sub(self.total_free_space());
         (used_space as f32 / page_size as f32) * 100.0
     }
 }
 
+/// Something in the file that points at a page
+#[derive(Debug, Clone, PartialEq)]
+pub enum PageReference {
+    /// The schema names the page as the root of an object's b-tree
+    Root(PageOwner),
+    /// A child pointer of an interior b-tree page
+    Child(u32),
+    /// A cell whose payload spills into the page
+    Overflow { page_number: u32, cell_index: u16 },
+    /// The previous page of an overflow chain, started by a cell
+    OverflowChain { page_number: u32, cell_index: u16 },
+    FreelistTrunk,
+    FreelistLeaf,
+}
+
+impl PageReference {
+    pub fn description(&self) -> String {
+        match self {
+            PageReference::Root(owner) => format!("Root of {}", owner.label()),
+            PageReference::Child(page_number) => format!("Child of page {}", page_number),
+            PageReference::Overflow {
+                page_number,
+                cell_index,
+            } => format!("Overflow of page {} cell {}", page_number, cell_index),
+            PageReference::OverflowChain {
+                page_number,
+                cell_index,
+            } => format!("Overflow chain of page {} cell {}", page_number, cell_index),
+            PageReference::FreelistTrunk => "Freelist trunk".to_string(),
+            PageReference::FreelistLeaf => "Freelist leaf".to_string(),
+        }
+    }
+}
+
+/// How the reachability analysis classified a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageReach {
+    /// Reached from exactly one place, or structural like pointer-map pages
+    Reached,
+    /// No root, freelist or overflow chain reaches the page
+    Lost,
+    /// Reached from this many places
+    Shared(usize),
+}
+
+/// Pages that no root, freelist or overflow chain reaches, and pages reached
+/// from more than one place. These are what `PRAGMA integrity_check` reports
+/// as never used or referenced multiple times.
+#[derive(Debug, Clone, Default)]
+pub struct Reachability {
+    pub lost: BTreeSet<u32>,
+    /// Every reference to each page reached more than once
+    pub shared: BTreeMap<u32, Vec<PageReference>>,
+}
+
+impl Reachability {
+    pub fn is_clean(&self) -> bool {
+        self.lost.is_empty() && self.shared.is_empty()
+    }
+
+    pub fn reach(&self, page_number: u32) -> PageReach {
+        if self.lost.contains(&page_number) {
+            PageReach::Lost
+        } else if let Some(references) = self.shared.get(&page_number) {
+            PageReach::Shared(references.len())
+        } else {
+            PageReach::Reached
+        }
+    }
+}
+
+/// Freelist layout discovered by following the trunk chain from the database header
+#[derive(Debug, Clone, Default)]
+pub struct FreelistInfo {
+    pub trunk_pages: Vec<u32>,
+    pub leaf_pages: Vec<u32>,
+    /// Freelist page count recorded in the database header
+    pub expected_total: u32,
+    pub errors: Vec<String>,
+}
+
+impl FreelistInfo {
+    pub fn total_pages(&self) -> u32 {
+        (self.trunk_pages.len() + self.leaf_pages.len()) as u32
+    }
+
+    pub fn has_mismatch(&self) -> bool {
+        self.total_pages() != self.expected_total
+    }
+
+    pub fn page_types(&self) -> impl Iterator<Item = (u32, PageType)> + '_ {
+        self.trunk_pages
+            .iter()
+            .map(|&page| (page, PageType::FreelistTrunk))
+            .chain(
+                self.leaf_pages
+                    .iter()
+                    .map(|&page| (page, PageType::FreelistLeaf)),
+            )
+    }
+}
+
+/// Header at the start of a write-ahead log file
+#[derive(Debug, Clone, Default)]
+pub struct WalHeader {
+    pub magic: u32,
+    pub format_version: u32,
+    pub page_size: u32,
+    pub checkpoint_sequence: u32,
+    pub salt1: u32,
+    pub salt2: u32,
+    pub checksum1: u32,
+    pub checksum2: u32,
+}
+
+impl WalHeader {
+    /// Checksums are computed on big-endian words when the low bit of the magic is set
+    pub fn big_endian_checksums(&self) -> bool {
+        self.magic & 1 == 1
+    }
+}
+
+/// One frame of the write-ahead log: a 24-byte header followed by a page image
+#[derive(Debug, Clone)]
+pub struct WalFrame {
+    /// 1-based position of the frame in the log
+    pub frame_number: u32,
+    /// Byte offset of the frame header within the WAL file
+    pub offset: u64,
+    pub page_number: u32,
+    /// Database size in pages after this frame commits, or 0 for non-commit frames
+    pub commit_size: u32,
+    pub salt1: u32,
+    pub salt2: u32,
+    pub checksum1: u32,
+    pub checksum2: u32,
+    /// Salts match the WAL header
+    pub salt_valid: bool,
+    /// Stored checksum matches the cumulative checksum up to this frame
+    pub checksum_valid: bool,
+}
+
+impl WalFrame {
+    pub fn is_commit(&self) -> bool {
+        self.commit_size != 0
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.salt_valid && self.checksum_valid
+    }
+}
+
+/// Consecutive frames ending at a commit frame
+#[derive(Debug, Clone)]
+pub struct WalTransaction {
+    /// Indices into `WalInfo::frames`
+    pub frames: Range<usize>,
+    /// Database size in pages after the commit, `None` if the transaction never committed
+    pub commit_size: Option<u32>,
+    /// Every frame up to and including the commit passed its salt and checksum checks
+    pub valid: bool,
+}
+
+impl WalTransaction {
+    pub fn is_committed(&self) -> bool {
+        self.commit_size.is_some()
+    }
+}
+
+/// Write-ahead log found next to the database file
+#[derive(Debug, Clone)]
+pub struct WalInfo {
+    pub path: PathBuf,
+    pub header: WalHeader,
+    pub header_checksum_valid: bool,
+    pub frames: Vec<WalFrame>,
+    pub transactions: Vec<WalTransaction>,
+}
+
+impl WalInfo {
+    pub fn frame_size(&self) -> u64 {
+        self.header.page_size as u64 + 24
+    }
+
+    /// Frames a reader would use: every frame of the valid committed transactions
+    pub fn valid_frame_count(&self) -> usize {
+        match self.valid_transaction_count() {
+            0 => 0,
+            count => self.transactions[count - 1].frames.end,
+        }
+    }
+
+    pub fn frames_of(&self, transaction: &WalTransaction) -> &[WalFrame] {
+        &self.frames[transaction.frames.clone()]
+    }
+
+    /// Number of leading transactions that are committed and valid
+    pub fn valid_transaction_count(&self) -> usize {
+        if !self.header_checksum_valid {
+            return 0;
+        }
+
+        self.transactions
+            .iter()
+            .take_while(|transaction| transaction.valid && transaction.is_committed())
+            .count()
+    }
+
+    /// Number of frames a reader uses when `view` is selected
+    pub fn frame_limit(&self, view: DatabaseView) -> usize {
+        match view {
+            DatabaseView::MainFile => 0,
+            DatabaseView::WithWal => self.valid_frame_count(),
+            // Transactions past the last valid commit are never visible
+            DatabaseView::AtTransaction(index) => match self.valid_transaction_count() {
+                0 => 0,
+                count => self.transactions[index.min(count - 1)].frames.end,
+            },
+        }
+    }
+
+    /// Pages written by the transaction at `index`
+    pub fn transaction_pages(&self, index: usize) -> BTreeSet<u32> {
+        self.transactions
+            .get(index)
+            .map(|transaction| {
+                self.frames_of(transaction)
+                    .iter()
+                    .map(|frame| frame.page_number)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// One of the two copies of the wal-index header at the start of the `-shm` file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WalIndexHeader {
+    pub version: u32,
+    /// Incremented by every transaction that changes the wal-index
+    pub change_counter: u32,
+    pub is_init: bool,
+    pub big_endian_checksums: bool,
+    pub page_size: u32,
+    /// Index of the last valid frame in the WAL (mxFrame)
+    pub max_frame: u32,
+    /// Database size in pages (nPage)
+    pub page_count: u32,
+    /// Checksum of the last valid frame
+    pub frame_checksum: (u32, u32),
+    pub salt1: u32,
+    pub salt2: u32,
+    pub checksum: (u32, u32),
+    pub checksum_valid: bool,
+}
+
+/// Checkpoint progress and reader slots that follow the wal-index headers
+#[derive(Debug, Clone, Default)]
+pub struct WalCheckpointInfo {
+    /// Number of WAL frames already copied back into the database (nBackfill)
+    pub backfill: u32,
+    /// Highest frame each reader slot may use; `READ_MARK_UNUSED` marks a free slot
+    pub read_marks: [u32; 5],
+    pub backfill_attempted: u32,
+}
+
+impl WalCheckpointInfo {
+    pub const READ_MARK_UNUSED: u32 = 0xffff_ffff;
+}
+
+/// Decoded `-shm` wal-index, checked against the WAL it describes
+#[derive(Debug, Clone)]
+pub struct WalIndexInfo {
+    pub path: PathBuf,
+    pub headers: [WalIndexHeader; 2],
+    pub checkpoint: WalCheckpointInfo,
+    /// Page number of every frame up to mxFrame, from the hash table blocks
+    pub frame_pages: Vec<u32>,
+    /// Frames whose entry could not be found by probing the hash tables
+    pub unhashed_frames: Vec<u32>,
+    /// Disagreements between the wal-index and the WAL contents
+    pub issues: Vec<String>,
+}
+
+impl WalIndexInfo {
+    pub fn header(&self) -> &WalIndexHeader {
+        &self.headers[0]
+    }
+
+    /// A writer updates the second copy first, so differing copies mean a
+    /// write was in progress or interrupted
+    pub fn headers_match(&self) -> bool {
+        self.headers[0] == self.headers[1]
+    }
+
+    /// Reader slots in use whose mark stops a checkpoint short of mxFrame
+    pub fn pinning_read_marks(&self) -> Vec<(usize, u32)> {
+        let max_frame = self.header().max_frame;
+        self.checkpoint
+            .read_marks
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|&(_, &mark)| mark != WalCheckpointInfo::READ_MARK_UNUSED)
+            .filter(|&(_, &mark)| mark >= self.checkpoint.backfill && mark < max_frame)
+            .map(|(slot, &mark)| (slot, mark))
+            .collect()
+    }
+}
+
+/// Header of the first segment of a rollback journal
+#[derive(Debug, Clone)]
+pub struct JournalHeader {
+    /// Page records in the segment, or `None` when they fill the rest of the file
+    pub record_count: Option<u32>,
+    /// Random value added to every record checksum
+    pub nonce: u32,
+    /// Size of the database in pages before the transaction started
+    pub initial_page_count: u32,
+    pub sector_size: u32,
+    pub page_size: u32,
+}
+
+/// Original image of a page saved before a transaction changed it
+#[derive(Debug, Clone)]
+pub struct JournalPage {
+    pub page_number: u32,
+    /// Offset of the page image within the journal file
+    pub offset: u64,
+    pub checksum: u32,
+    pub checksum_valid: bool,
+}
+
+/// Decoded `-journal` rollback journal
+#[derive(Debug, Clone)]
+pub struct JournalInfo {
+    pub path: PathBuf,
+    pub header: JournalHeader,
+    pub pages: Vec<JournalPage>,
+    /// Structural problems such as a journal truncated mid-record
+    pub errors: Vec<String>,
+    /// The next connection to open the database would roll this journal back
+    pub is_hot: bool,
+}
+
+impl JournalInfo {
+    /// Record that rollback restores a page from. SQLite only journals a page
+    /// once per transaction, so the first record holds the original image.
+    pub fn page(&self, page_number: u32) -> Option<&JournalPage> {
+        self.pages
+            .iter()
+            .find(|page| page.page_number == page_number)
+    }
+}
+
+/// Which version of the database the pages were read from
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DatabaseView {
+    /// Only the main database file, ignoring the write-ahead log
+    MainFile,
+    /// The main file with the latest committed WAL frame of each page applied
+    #[default]
+    WithWal,
+    /// The main file with the WAL applied up to and including the given
+    /// transaction, as an index into `WalInfo::transactions`
+    AtTransaction(usize),
+}
+
+impl DatabaseView {
+    pub fn label(&self) -> String {
+        match self {
+            DatabaseView::MainFile => "Main file only".to_string(),
+            DatabaseView::WithWal => "Effective (with WAL)".to_string(),
+            DatabaseView::AtTransaction(index) => format!("As of commit {}", index + 1),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DatabaseInfo {
     pub header: DatabaseHeader,
     pub pages: Arc<BTreeMap<u32, PageInfo>>,
     pub total_file_size: u64,
+    pub freelist: FreelistInfo,
+    pub pointer_map: Option<PointerMapInfo>,
+    pub schema: Vec<SchemaObject>,
+    pub btrees: Vec<BTree>,
+    pub wal: Option<WalInfo>,
+    pub wal_index: Option<WalIndexInfo>,
+    pub journal: Option<JournalInfo>,
+    pub reachability: Reachability,
+    pub view: DatabaseView,
+    /// File the database was parsed from, used to read raw page bytes on demand
+    pub source_path: Option<PathBuf>,
 }
 
 impl DatabaseInfo {
@@ -183,9 +1114,69 @@ impl DatabaseInfo {
             header,
             pages,
             total_file_size,
+            freelist: FreelistInfo::default(),
+            pointer_map: None,
+            schema: Vec::new(),
+            btrees: Vec::new(),
+            wal: None,
+            wal_index: None,
+            journal: None,
+            reachability: Reachability::default(),
+            view: DatabaseView::default(),
+            source_path: None,
         }
     }
 
+    pub fn with_freelist(mut self, freelist: FreelistInfo) -> Self {
+        self.freelist = freelist;
+        self
+    }
+
+    pub fn with_pointer_map(mut self, pointer_map: Option<PointerMapInfo>) -> Self {
+        self.pointer_map = pointer_map;
+        self
+    }
+
+    pub fn with_schema(mut self, schema: Vec<SchemaObject>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn with_btrees(mut self, btrees: Vec<BTree>) -> Self {
+        self.btrees = btrees;
+        self
+    }
+
+    pub fn with_wal(mut self, wal: Option<WalInfo>) -> Self {
+        self.wal = wal;
+        self
+    }
+
+    pub fn with_wal_index(mut self, wal_index: Option<WalIndexInfo>) -> Self {
+        self.wal_index = wal_index;
+        self
+    }
+
+    pub fn with_journal(mut self, journal: Option<JournalInfo>) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    pub fn with_reachability(mut self, reachability: Reachability) -> Self {
+        self.reachability = reachability;
+        self
+    }
+
+    pub fn with_view(mut self, view: DatabaseView) -> Self {
+        self.view = view;
+        self
+    }
+
+    pub fn with_source_path(mut self, source_path: PathBuf) -> Self {
+        self.source_path = Some(source_path);
+        self
+    }
+
     pub fn page_count(&self) -> usize {
         self.pages.len()
     }
@@ -193,4 +1184,206 @@ impl DatabaseInfo {
     pub fn get_page_info(&self, page_number: u32) -> Option<&PageInfo> {
         self.pages.get(&page_number)
     }
+
+    /// Pages with integrity findings, in page order
+    pub fn pages_with_findings(&self) -> impl Iterator<Item = &PageInfo> {
+        self.pages.values().filter(|page| !page.findings.is_empty())
+    }
+
+    pub fn finding_count(&self) -> usize {
+        self.pages.values().map(|page| page.findings.len()).sum()
+    }
+}
+
+/// How a page differs from the previous load of the same file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageChange {
+    Added,
+    Modified,
+    /// Moved onto the freelist, or truncated off the end of the file
+    Freed,
+}
+
+impl PageChange {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PageChange::Added => "Added",
+            PageChange::Modified => "Modified",
+            PageChange::Freed => "Freed",
+        }
+    }
+}
+
+/// Pages that changed when a watched file was reloaded, tallied for display
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReloadChanges {
+    pub pages: BTreeMap<u32, PageChange>,
+    /// Changed pages per page type name
+    pub by_page_type: BTreeMap<&'static str, usize>,
+    /// Changed pages per owning object label. Freed pages count towards
+    /// the object that owned them before.
+    pub by_owner: BTreeMap<String, usize>,
+}
+
+impl ReloadChanges {
+    pub fn new(
+        previous: &DatabaseInfo,
+        current: &DatabaseInfo,
+        changed_pages: &BTreeSet<u32>,
+    ) -> Self {
+        let mut changes = Self::default();
+
+        for &page_number in changed_pages {
+            let before = previous.pages.get(&page_number);
+            let after = current.pages.get(&page_number);
+            let is_free = |page: &PageInfo| {
+                matches!(
+                    page.page_type,
+                    PageType::FreelistTrunk | PageType::FreelistLeaf
+                )
+            };
+
+            let Some(page) = after.or(before) else {
+                continue;
+            };
+            let change = match (before, after) {
+                (None, _) => PageChange::Added,
+                (Some(_), None) => PageChange::Freed,
+                (Some(before), Some(after)) if is_free(after) && !is_free(before) => {
+                    PageChange::Freed
+                }
+                _ => PageChange::Modified,
+            };
+            let owner = after
+                .and_then(|page| page.owner.as_ref())
+                .or_else(|| before.and_then(|page| page.owner.as_ref()))
+                .map(|owner| owner.label())
+                .unwrap_or_else(|| "Unowned".to_string());
+
+            changes.pages.insert(page_number, change);
+            *changes.by_page_type.entry(page.page_type.name()).or_default() += 1;
+            *changes.by_owner.entry(owner).or_default() += 1;
+        }
+
+        changes
+    }
+
+    pub fn count(&self, change: PageChange) -> usize {
+        self.pages.values().filter(|&&page| page == change).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+/// One database state captured by record mode
+#[derive(Debug, Clone)]
+pub struct RecordedSnapshot {
+    /// Time since the recording started
+    pub elapsed: Duration,
+    pub page_size: u32,
+    /// Size of the database in pages
+    pub page_count: u32,
+    /// The 100-byte database header
+    pub header: Vec<u8>,
+    /// Number and session file offset of every page image stored for this
+    /// state. The first snapshot stores every page, later ones only the
+    /// pages that changed.
+    pub pages: Vec<(u32, u64)>,
+}
+
+impl RecordedSnapshot {
+    pub fn change_counter(&self) -> u32 {
+        self.header
+            .get(24..28)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map_or(0, u32::from_be_bytes)
+    }
+}
+
+/// Decoded session file written by record mode
+#[derive(Debug, Clone)]
+pub struct RecordedSession {
+    pub path: PathBuf,
+    pub snapshots: Vec<RecordedSnapshot>,
+    /// Structural problems such as a session cut off mid-snapshot
+    pub errors: Vec<String>,
+}
+
+/// How a page compares between two database files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PageDiffKind {
+    Identical,
+    /// Same page type, different bytes
+    Modified,
+    TypeChanged,
+    /// Only present in the second file
+    Added,
+    /// Only present in the first file
+    Removed,
+}
+
+impl PageDiffKind {
+    pub const ALL: [PageDiffKind; 5] = [
+        PageDiffKind::Identical,
+        PageDiffKind::Modified,
+        PageDiffKind::TypeChanged,
+        PageDiffKind::Added,
+        PageDiffKind::Removed,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PageDiffKind::Identical => "Identical",
+            PageDiffKind::Modified => "Modified",
+            PageDiffKind::TypeChanged => "Type changed",
+            PageDiffKind::Added => "Added",
+            PageDiffKind::Removed => "Removed",
+        }
+    }
+
+    pub fn color(&self) -> Hsla {
+        match self {
+            PageDiffKind::Identical => rgb(0x555555).into(),
+            PageDiffKind::Modified => rgb(0x38bdf8).into(),
+            PageDiffKind::TypeChanged => rgb(0xf97316).into(),
+            PageDiffKind::Added => rgb(0x22c55e).into(),
+            PageDiffKind::Removed => rgb(0xef4444).into(),
+        }
+    }
+}
+
+/// One field of a page as it reads in each of two compared files
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageFieldDiff {
+    pub name: &'static str,
+    /// `None` when the page does not exist in that file
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+impl PageFieldDiff {
+    pub fn differs(&self) -> bool {
+        self.left != self.right
+    }
+}
+
+/// Two databases aligned by page number
+#[derive(Debug, Clone)]
+pub struct DatabaseDiff {
+    pub left: Arc<DatabaseInfo>,
+    pub right: Arc<DatabaseInfo>,
+    pub pages: BTreeMap<u32, PageDiffKind>,
+}
+
+impl DatabaseDiff {
+    pub fn count(&self, kind: PageDiffKind) -> usize {
+        self.pages.values().filter(|&&page| page == kind).count()
+    }
+
+    /// Every page that is not identical in both files
+    pub fn changed_count(&self) -> usize {
+        self.pages.len() - self.count(PageDiffKind::Identical)
+    }
 }
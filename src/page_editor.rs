@@ -0,0 +1,328 @@
+use crate::models::{DatabaseInfo, IntegrityFinding, PageInfo};
+use crate::parser::journal::journal_path;
+use crate::parser::sqlite_parser::{parse_header, parse_page, read_page};
+use crate::parser::wal::wal_path;
+use anyhow::Result;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Cursor, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One byte changed by an edit, with its values either side of the change
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ByteChange {
+    offset: usize,
+    before: u8,
+    after: u8,
+}
+
+/// Unsaved edits to the bytes of one page, with undo and redo
+#[derive(Debug, Clone)]
+pub struct PageEdit {
+    pub page_number: u32,
+    /// The page as it was read, which saving expects to still find on disk
+    original: Vec<u8>,
+    bytes: Vec<u8>,
+    undo_stack: Vec<ByteChange>,
+    redo_stack: Vec<ByteChange>,
+}
+
+impl PageEdit {
+    pub fn new(page_number: u32, bytes: Vec<u8>) -> Self {
+        Self {
+            page_number,
+            original: bytes.clone(),
+            bytes,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn is_changed(&self, offset: usize) -> bool {
+        self.bytes.get(offset) != self.original.get(offset)
+    }
+
+    /// Bytes that differ from the page as it was read
+    pub fn changed_bytes(&self) -> usize {
+        self.bytes
+            .iter()
+            .zip(&self.original)
+            .filter(|(byte, original)| byte != original)
+            .count()
+    }
+
+    /// Set the byte at `offset`, returning whether anything changed. A new
+    /// change discards whatever could be redone.
+    pub fn set_byte(&mut self, offset: usize, value: u8) -> bool {
+        let Some(byte) = self.bytes.get_mut(offset) else {
+            return false;
+        };
+        if *byte == value {
+            return false;
+        }
+        self.undo_stack.push(ByteChange {
+            offset,
+            before: *byte,
+            after: value,
+        });
+        self.redo_stack.clear();
+        *byte = value;
+        true
+    }
+
+    /// Revert the last change, returning the offset it touched
+    pub fn undo(&mut self) -> Option<usize> {
+        let change = self.undo_stack.pop()?;
+        self.bytes[change.offset] = change.before;
+        self.redo_stack.push(change);
+        Some(change.offset)
+    }
+
+    /// Apply the last undone change again, returning the offset it touched
+    pub fn redo(&mut self) -> Option<usize> {
+        let change = self.redo_stack.pop()?;
+        self.bytes[change.offset] = change.after;
+        self.undo_stack.push(change);
+        Some(change.offset)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+}
+
+/// The edited page as the parser reads it
+#[derive(Debug, Clone)]
+pub struct EditValidation {
+    pub page: PageInfo,
+    /// Problems on the edited page that the page as it was read does not have
+    pub new_findings: Vec<IntegrityFinding>,
+}
+
+impl EditValidation {
+    pub fn is_clean(&self) -> bool {
+        self.new_findings.is_empty()
+    }
+}
+
+/// Reparse the edited page with the regular page parser and compare its
+/// findings with those of the page as it was read. Edits to page 1 must also
+/// leave a database header with the same page size.
+pub fn validate_page_edit(database_info: &DatabaseInfo, edit: &PageEdit) -> Result<EditValidation> {
+    let page_size = database_info.header.actual_page_size();
+    let usable_size = page_size - database_info.header.reserved_space as usize;
+
+    let edited_header = if edit.page_number == 1 {
+        let header = parse_header(&mut Cursor::new(edit.bytes()))?;
+        if !header.is_valid_sqlite_file() {
+            return Err(anyhow::anyhow!(
+                "The database header no longer starts with the SQLite magic string"
+            ));
+        }
+        if header.actual_page_size() != page_size
+            || header.reserved_space != database_info.header.reserved_space
+        {
+            return Err(anyhow::anyhow!(
+                "Edits may not change the page size or the reserved bytes per page"
+            ));
+        }
+        Some(header)
+    } else {
+        None
+    };
+    let header = edited_header.as_ref().unwrap_or(&database_info.header);
+
+    let original = parse_page(
+        &edit.original,
+        edit.page_number,
+        page_size,
+        usable_size,
+        &database_info.header,
+    )?;
+    let page = parse_page(
+        edit.bytes(),
+        edit.page_number,
+        page_size,
+        usable_size,
+        header,
+    )?;
+    let new_findings = page
+        .findings
+        .iter()
+        .filter(|finding| !original.findings.contains(finding))
+        .cloned()
+        .collect();
+
+    Ok(EditValidation { page, new_findings })
+}
+
+/// Backup of `db_path` next to it, named after the time it was taken
+pub fn backup_path(db_path: &Path) -> PathBuf {
+    let taken = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    let name = db_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("database");
+    db_path.with_file_name(format!("{}.{}.bak", name, taken))
+}
+
+/// Write an edited page back to the database file, after copying the file to
+/// a timestamped backup next to it. Returns the backup's path.
+///
+/// Refuses while a WAL or rollback journal exists, since SQLite would apply
+/// it over the page, when the page changed on disk since it was read, and
+/// when the edit introduces problems the parser finds. The first two are
+/// checked again once the backup is taken, right before the page is written.
+pub fn save_page_edit(database_info: &DatabaseInfo, edit: &PageEdit) -> Result<PathBuf> {
+    let path = database_info
+        .source_path
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Only database files can be edited"))?;
+    let page_size = database_info.header.actual_page_size();
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    ensure_page_unchanged(path, &mut file, edit, page_size)?;
+
+    let validation = validate_page_edit(database_info, edit)?;
+    if let Some(finding) = validation.new_findings.first() {
+        return Err(anyhow::anyhow!(
+            "The edit introduces problems: {}: {}",
+            finding.kind.name(),
+            finding.message
+        ));
+    }
+
+    let backup = backup_path(path);
+    let mut backup_file = File::create_new(&backup)?;
+    file.seek(SeekFrom::Start(0))?;
+    io::copy(&mut file, &mut backup_file)?;
+    backup_file.sync_all()?;
+
+    // Copying a large file takes a while, during which a writer may have started
+    if let Err(e) = ensure_page_unchanged(path, &mut file, edit, page_size) {
+        if let Err(remove_error) = std::fs::remove_file(&backup) {
+            eprintln!(
+                "Warning: Failed to remove backup {}: {}",
+                backup.display(),
+                remove_error
+            );
+        }
+        return Err(e);
+    }
+
+    file.seek(SeekFrom::Start(
+        (edit.page_number as u64 - 1) * page_size as u64,
+    ))?;
+    file.write_all(edit.bytes())?;
+    file.sync_all()?;
+
+    Ok(backup)
+}
+
+/// Fail while a WAL or rollback journal sits beside the database, or when the
+/// page no longer holds the bytes the edit started from
+fn ensure_page_unchanged(
+    path: &Path,
+    file: &mut File,
+    edit: &PageEdit,
+    page_size: usize,
+) -> Result<()> {
+    for companion in [wal_path(path), journal_path(path)] {
+        if companion.exists() {
+            return Err(anyhow::anyhow!(
+                "{} exists; checkpoint or remove it before editing",
+                companion.display()
+            ));
+        }
+    }
+
+    if read_page(file, edit.page_number, page_size)? != edit.original {
+        return Err(anyhow::anyhow!(
+            "Page {} changed on disk since it was read",
+            edit.page_number
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::sqlite_parser::parse_database_file;
+
+    /// Copy of the 64 KiB fixture, whose page 3 is an empty table leaf
+    fn copy_fixture(name: &str) -> PathBuf {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/page_size_65536.db");
+        let path =
+            std::env::temp_dir().join(format!("sqlite-browser-{}-{}.db", name, std::process::id()));
+        std::fs::copy(fixture, &path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut edit = PageEdit::new(2, vec![0; 4]);
+        assert!(edit.set_byte(1, 0xaa));
+        assert!(edit.set_byte(2, 0xbb));
+        assert!(!edit.set_byte(2, 0xbb));
+        assert!(!edit.set_byte(4, 0xcc));
+        assert_eq!(edit.changed_bytes(), 2);
+
+        assert_eq!(edit.undo(), Some(2));
+        assert_eq!(edit.bytes(), &[0, 0xaa, 0, 0]);
+        assert_eq!(edit.redo(), Some(2));
+        assert_eq!(edit.bytes(), &[0, 0xaa, 0xbb, 0]);
+
+        edit.undo();
+        edit.set_byte(3, 0xdd);
+        assert!(!edit.can_redo());
+        assert_eq!(edit.undo(), Some(3));
+        assert_eq!(edit.undo(), Some(1));
+        assert_eq!(edit.undo(), None);
+        assert_eq!(edit.changed_bytes(), 0);
+    }
+
+    #[test]
+    fn test_save_page_edit() {
+        let path = copy_fixture("edit");
+        let original = std::fs::read(&path).unwrap();
+        let info = parse_database_file(&path).unwrap();
+        let page = read_page(&mut File::open(&path).unwrap(), 3, 65536).unwrap();
+
+        // A cell count past the end of the empty page is refused
+        let mut edit = PageEdit::new(3, page.clone());
+        edit.set_byte(4, 0xff);
+        assert!(!validate_page_edit(&info, &edit).unwrap().is_clean());
+        assert!(save_page_edit(&info, &edit).is_err());
+
+        // Unallocated bytes can change freely
+        let mut edit = PageEdit::new(3, page);
+        edit.set_byte(100, 0x42);
+        let journal = journal_path(&path);
+        std::fs::write(&journal, b"").unwrap();
+        assert!(save_page_edit(&info, &edit).is_err());
+        std::fs::remove_file(&journal).unwrap();
+
+        let backup = save_page_edit(&info, &edit).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+        assert_eq!(std::fs::read(&backup).unwrap(), original);
+        assert_eq!(saved[2 * 65536 + 100], 0x42);
+        assert_eq!(saved.len(), original.len());
+
+        // The page no longer matches what the edit was made against
+        assert!(save_page_edit(&info, &edit).is_err());
+
+        std::fs::remove_file(&backup).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
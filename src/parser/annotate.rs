@@ -0,0 +1,452 @@
+use crate::models::{CellInfo, DatabaseInfo, PageInfo, PageRegion, PageRegionKind, PageType};
+use crate::parser::cell::read_varint;
+use crate::parser::record::serial_type_size;
+use crate::parser::sqlite_parser::read_page;
+use crate::parser::wal::open_database;
+use anyhow::Result;
+
+/// Fields of the 100-byte database header, as length and name
+const DATABASE_HEADER_FIELDS: &[(u32, &str)] = &[
+    (16, "Header string"),
+    (2, "Page size"),
+    (1, "File format write version"),
+    (1, "File format read version"),
+    (1, "Reserved bytes per page"),
+    (1, "Maximum embedded payload fraction"),
+    (1, "Minimum embedded payload fraction"),
+    (1, "Leaf payload fraction"),
+    (4, "File change counter"),
+    (4, "Database size in pages"),
+    (4, "First freelist trunk page"),
+    (4, "Freelist page count"),
+    (4, "Schema cookie"),
+    (4, "Schema format number"),
+    (4, "Default page cache size"),
+    (4, "Largest root page"),
+    (4, "Text encoding"),
+    (4, "User version"),
+    (4, "Incremental vacuum mode"),
+    (4, "Application ID"),
+    (20, "Reserved for expansion"),
+    (4, "Version-valid-for number"),
+    (4, "SQLite version number"),
+];
+
+/// A field on a page, such as a header entry, a cell pointer or part of a cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageField {
+    /// Region of the page the field belongs to
+    pub region: PageRegionKind,
+    pub offset: u32,
+    pub length: u32,
+    pub name: String,
+    /// Decoded value, for fields holding a number or a short string
+    pub value: Option<String>,
+}
+
+impl PageField {
+    pub fn end(&self) -> u32 {
+        self.offset + self.length
+    }
+}
+
+/// Raw bytes of a page with every byte assigned to the field it belongs to
+#[derive(Debug, Clone)]
+pub struct AnnotatedPage {
+    pub page_number: u32,
+    pub bytes: Vec<u8>,
+    /// Contiguous fields covering the whole page, in offset order
+    pub fields: Vec<PageField>,
+}
+
+impl AnnotatedPage {
+    pub fn field_at(&self, offset: usize) -> Option<&PageField> {
+        let index = self
+            .fields
+            .partition_point(|field| field.end() as usize <= offset);
+        self.fields
+            .get(index)
+            .filter(|field| field.offset as usize <= offset)
+    }
+}
+
+/// Read `page_number` in the view the database was parsed with and annotate
+/// its bytes
+pub fn read_annotated_page(
+    database_info: &DatabaseInfo,
+    page_number: u32,
+) -> Result<AnnotatedPage> {
+    let page_info = database_info
+        .get_page_info(page_number)
+        .ok_or_else(|| anyhow::anyhow!("Page {} not found", page_number))?;
+    let page_size = database_info.header.actual_page_size();
+    let usable_size = page_size - database_info.header.reserved_space as usize;
+
+    let bytes = read_page(&mut open_database(database_info)?, page_number, page_size)?;
+    let fields = annotate_page(&bytes, page_info, usable_size);
+
+    Ok(AnnotatedPage {
+        page_number,
+        bytes,
+        fields,
+    })
+}
+
+/// Split every region of `page_info`'s layout into the fields SQLite stores
+/// there. Bytes a region holds beyond its known fields, such as the payload
+/// of a cell that overlaps another, get a field named after the region.
+pub fn annotate_page(page: &[u8], page_info: &PageInfo, usable_size: usize) -> Vec<PageField> {
+    let mut fields = Vec::new();
+    for region in page_info.layout(page.len(), usable_size) {
+        let mut writer = FieldWriter {
+            page,
+            region,
+            cursor: region.offset,
+            end: region.end(),
+            fields: &mut fields,
+        };
+        match region.kind {
+            PageRegionKind::DatabaseHeader => writer.database_header(),
+            PageRegionKind::PageHeader => writer.page_header(),
+            PageRegionKind::CellPointers => {
+                for index in 0..region.length / 2 {
+                    writer.uint(2, &format!("Cell {} pointer", index));
+                }
+            }
+            PageRegionKind::Cell(index) => {
+                if let Some(cell) = page_info.cells.iter().find(|cell| cell.index == index) {
+                    writer.cell(&page_info.page_type, cell);
+                }
+            }
+            PageRegionKind::Freeblock => {
+                writer.uint(2, "Next freeblock");
+                writer.uint(2, "Freeblock size");
+                writer.rest("Free bytes");
+            }
+            PageRegionKind::Content => writer.content(page_info),
+            _ => {}
+        }
+        writer.rest(&region.label());
+    }
+    fields
+}
+
+/// Appends fields for consecutive bytes of one region, never past its end
+struct FieldWriter<'a> {
+    page: &'a [u8],
+    region: PageRegion,
+    cursor: u32,
+    /// Where fields stop, the end of the region or of a cell's local payload
+    end: u32,
+    fields: &'a mut Vec<PageField>,
+}
+
+impl FieldWriter<'_> {
+    fn field(&mut self, length: u32, name: &str, value: Option<String>) {
+        let length = length.min(self.end.saturating_sub(self.cursor));
+        if length == 0 {
+            return;
+        }
+        self.fields.push(PageField {
+            region: self.region.kind,
+            offset: self.cursor,
+            length,
+            name: name.to_string(),
+            value,
+        });
+        self.cursor += length;
+    }
+
+    fn bytes(&self, length: u32) -> &[u8] {
+        let start = self.cursor as usize;
+        let end = (self.cursor + length).min(self.end) as usize;
+        &self.page[start.min(end)..end]
+    }
+
+    /// Big-endian unsigned integer of `length` bytes
+    fn uint(&mut self, length: u32, name: &str) -> u64 {
+        let value = self
+            .bytes(length)
+            .iter()
+            .fold(0u64, |value, byte| value << 8 | *byte as u64);
+        self.field(length, name, Some(value.to_string()));
+        value
+    }
+
+    fn varint(&mut self, name: &str) -> Option<u64> {
+        let (value, length) = read_varint(self.bytes(9))?;
+        self.field(length as u32, name, Some(value.to_string()));
+        Some(value)
+    }
+
+    /// Whatever is left before `end`, as one field
+    fn rest(&mut self, name: &str) {
+        self.field(self.end.saturating_sub(self.cursor), name, None);
+    }
+
+    fn database_header(&mut self) {
+        for &(length, name) in DATABASE_HEADER_FIELDS {
+            match name {
+                "Header string" => {
+                    let text = String::from_utf8_lossy(self.bytes(length - 1)).into_owned();
+                    self.field(length, name, Some(text));
+                }
+                "Reserved for expansion" => self.field(length, name, None),
+                _ => {
+                    self.uint(length, name);
+                }
+            }
+        }
+    }
+
+    fn page_header(&mut self) {
+        let page_type = self.bytes(1).first().copied().unwrap_or(0);
+        self.field(
+            1,
+            "Page type",
+            Some(format!(
+                "0x{:02x} {}",
+                page_type,
+                PageType::from_byte(page_type).name()
+            )),
+        );
+        self.uint(2, "First freeblock");
+        self.uint(2, "Cell count");
+        self.uint(2, "Cell content start");
+        self.uint(1, "Fragmented bytes");
+        self.uint(4, "Right-most pointer");
+    }
+
+    fn cell(&mut self, page_type: &PageType, cell: &CellInfo) {
+        let prefix = format!("Cell {}", cell.index);
+        if cell.left_child.is_some() {
+            self.uint(4, &format!("{} left child page", prefix));
+        }
+        if cell.payload_size.is_some() {
+            self.varint(&format!("{} payload size varint", prefix));
+        }
+        if matches!(
+            page_type,
+            PageType::TableBTreeLeaf | PageType::TableBTreeInterior
+        ) {
+            self.varint(&format!("{} rowid varint", prefix));
+        }
+        if cell.payload_size.is_none() || self.cursor != cell.payload_offset {
+            return;
+        }
+
+        let region_end = self.end;
+        let payload_end = cell.payload_offset + cell.local_payload_size as u32;
+        self.end = payload_end.min(region_end);
+        self.record(&prefix);
+        self.rest(&format!("{} payload", prefix));
+        self.end = region_end;
+
+        if cell.first_overflow_page.is_some() {
+            self.uint(4, &format!("{} first overflow page", prefix));
+        }
+    }
+
+    /// The record header's serial types, then the column values they describe
+    fn record(&mut self, prefix: &str) {
+        let header_start = self.cursor;
+        let Some(header_size) = self.varint(&format!("{} record header size", prefix)) else {
+            return;
+        };
+        let header_end = (header_start as u64).saturating_add(header_size);
+
+        let mut serial_types = Vec::new();
+        while (self.cursor as u64) < header_end && self.cursor < self.end {
+            let name = format!("{} column {} serial type", prefix, serial_types.len());
+            let Some(serial_type) = self.varint(&name) else {
+                return;
+            };
+            serial_types.push(serial_type);
+        }
+
+        for (column, serial_type) in serial_types.into_iter().enumerate() {
+            let length = u32::try_from(serial_type_size(serial_type)).unwrap_or(u32::MAX);
+            self.field(length, &format!("{} column {}", prefix, column), None);
+        }
+    }
+
+    /// Fields of pages outside b-trees
+    fn content(&mut self, page_info: &PageInfo) {
+        match page_info.page_type {
+            PageType::PayloadOverflow => {
+                self.uint(4, "Next overflow page");
+                self.rest("Overflow payload");
+            }
+            PageType::FreelistTrunk => {
+                self.uint(4, "Next trunk page");
+                let leaf_count = self.uint(4, "Leaf page count");
+                for index in 0..leaf_count {
+                    if self.cursor >= self.end {
+                        break;
+                    }
+                    self.uint(4, &format!("Leaf page {}", index));
+                }
+                self.rest("Unused");
+            }
+            PageType::PointerMap => {
+                let mut described_page = page_info.page_number + 1;
+                while self.end - self.cursor >= 5 {
+                    self.uint(1, &format!("Page {} pointer type", described_page));
+                    self.uint(4, &format!("Page {} parent", described_page));
+                    described_page += 1;
+                }
+                self.rest("Unused");
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Bytes a search query stands for: pairs of hex digits, optionally spaced
+/// apart, or the query's text as UTF-8
+pub fn search_bytes(query: &str, hex: bool) -> Option<Vec<u8>> {
+    if !hex {
+        return (!query.is_empty()).then(|| query.as_bytes().to_vec());
+    }
+
+    let digits: String = query.split_whitespace().collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(digits.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// Start offsets of every occurrence of `needle` in `haystack`, overlapping
+/// ones included
+pub fn find_all(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(|(_, window)| *window == needle)
+        .map(|(offset, _)| offset)
+        .collect()
+}
+
+/// Space-separated lowercase hex, as copied from the hex view
+pub fn format_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_table_leaf() {
+        // Leaf page 2 holding one cell at offset 500: payload size 3, rowid 7,
+        // then a record with a one-byte integer column
+        let mut page = vec![0u8; 512];
+        page[0] = 0x0d;
+        page[3..5].copy_from_slice(&1u16.to_be_bytes());
+        page[5..7].copy_from_slice(&500u16.to_be_bytes());
+        page[8..10].copy_from_slice(&500u16.to_be_bytes());
+        page[500..505].copy_from_slice(&[0x03, 0x07, 0x02, 0x01, 0x2a]);
+        let cell = CellInfo {
+            index: 0,
+            offset: 500,
+            size: 5,
+            left_child: None,
+            rowid: Some(7),
+            payload_size: Some(3),
+            payload_offset: 502,
+            local_payload_size: 3,
+            first_overflow_page: None,
+        };
+        let page_info =
+            PageInfo::new(2, PageType::TableBTreeLeaf, 1, 490, 0, None).with_cells(vec![cell]);
+
+        let fields = annotate_page(&page, &page_info, 512);
+        assert_eq!(fields.first().map(|field| field.offset), Some(0));
+        assert_eq!(fields.last().map(PageField::end), Some(512));
+        assert!(
+            fields
+                .windows(2)
+                .all(|pair| pair[0].end() == pair[1].offset)
+        );
+
+        let annotated = AnnotatedPage {
+            page_number: 2,
+            bytes: page,
+            fields,
+        };
+        let names: Vec<_> = (500..505)
+            .map(|offset| annotated.field_at(offset).unwrap().name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "Cell 0 payload size varint",
+                "Cell 0 rowid varint",
+                "Cell 0 record header size",
+                "Cell 0 column 0 serial type",
+                "Cell 0 column 0",
+            ]
+        );
+        assert_eq!(annotated.field_at(501).unwrap().value.as_deref(), Some("7"));
+        assert_eq!(annotated.field_at(3).unwrap().name, "Cell count");
+        assert_eq!(annotated.field_at(8).unwrap().name, "Cell 0 pointer");
+        assert_eq!(
+            annotated.field_at(300).unwrap().region,
+            PageRegionKind::Unallocated
+        );
+    }
+
+    #[test]
+    fn test_annotate_corrupt_record_header() {
+        // The record header size is the largest nine-byte varint
+        let mut page = vec![0u8; 512];
+        page[0] = 0x0d;
+        page[3..5].copy_from_slice(&1u16.to_be_bytes());
+        page[5..7].copy_from_slice(&500u16.to_be_bytes());
+        page[8..10].copy_from_slice(&500u16.to_be_bytes());
+        page[500..502].copy_from_slice(&[0x0a, 0x07]);
+        page[502..511].fill(0xff);
+        let cell = CellInfo {
+            index: 0,
+            offset: 500,
+            size: 12,
+            left_child: None,
+            rowid: Some(7),
+            payload_size: Some(10),
+            payload_offset: 502,
+            local_payload_size: 10,
+            first_overflow_page: None,
+        };
+        let page_info =
+            PageInfo::new(2, PageType::TableBTreeLeaf, 1, 490, 0, None).with_cells(vec![cell]);
+
+        let fields = annotate_page(&page, &page_info, 512);
+        assert_eq!(fields.last().map(PageField::end), Some(512));
+        assert!(
+            fields
+                .windows(2)
+                .all(|pair| pair[0].end() == pair[1].offset)
+        );
+    }
+
+    #[test]
+    fn test_search_bytes() {
+        assert_eq!(search_bytes("0d 00 ff", true), Some(vec![0x0d, 0x00, 0xff]));
+        assert_eq!(search_bytes("0D00", true), Some(vec![0x0d, 0x00]));
+        assert_eq!(search_bytes("0d0", true), None);
+        assert_eq!(search_bytes("zz", true), None);
+        assert_eq!(search_bytes("abc", false), Some(b"abc".to_vec()));
+        assert_eq!(find_all(b"aaab", b"aa"), vec![0, 1]);
+        assert_eq!(format_hex(&[0x0d, 0xff]), "0d ff");
+    }
+}
@@ -0,0 +1,61 @@
+use crate::models::{BTree, BTreeNode, PageInfo, PageOwner};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+/// Every page of the b-tree rooted at `root_page`, in depth-first order.
+///
+/// Child pointers that leave the file or lead to non b-tree pages are not
+/// followed, and each page is visited at most once so a looping tree still
+/// terminates.
+pub fn walk_btree(pages: &BTreeMap<u32, PageInfo>, root_page: u32) -> Vec<u32> {
+    let mut visited = HashSet::new();
+    let mut order = Vec::new();
+    let mut stack = vec![root_page];
+
+    while let Some(page_number) = stack.pop() {
+        let Some(page) = pages.get(&page_number) else {
+            continue;
+        };
+        if !page.page_type.is_btree() || !visited.insert(page_number) {
+            continue;
+        }
+
+        order.push(page_number);
+
+        // Push in reverse so children are visited left to right
+        let children: Vec<u32> = page.child_pages().collect();
+        stack.extend(children.into_iter().rev());
+    }
+
+    order
+}
+
+/// Reconstruct the b-tree rooted at `root_page`, recording each page's depth
+/// and the children it points to.
+pub fn build_btree(pages: &BTreeMap<u32, PageInfo>, root_page: u32, owner: PageOwner) -> BTree {
+    let mut visited = HashSet::new();
+    let mut nodes = Vec::new();
+    let mut queue = VecDeque::from([(root_page, 0u32)]);
+
+    while let Some((page_number, depth)) = queue.pop_front() {
+        let Some(page) = pages.get(&page_number) else {
+            continue;
+        };
+        if !page.page_type.is_btree() || !visited.insert(page_number) {
+            continue;
+        }
+
+        let children: Vec<u32> = page.child_pages().collect();
+        queue.extend(children.iter().map(|&child| (child, depth + 1)));
+        nodes.push(BTreeNode {
+            page_number,
+            depth,
+            children,
+        });
+    }
+
+    BTree {
+        root_page,
+        owner,
+        nodes,
+    }
+}
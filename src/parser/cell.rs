@@ -0,0 +1,316 @@
+use crate::models::{CellInfo, DatabaseHeader, Freeblock, PageType};
+
+/// Read an SQLite varint (1 to 9 bytes, big-endian, high bit as continuation flag).
+///
+/// Returns the value and the number of bytes consumed, or `None` if the buffer
+/// ends before the varint does.
+pub fn read_varint(buf: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for i in 0..9 {
+        let byte = *buf.get(i)?;
+        if i == 8 {
+            // The ninth byte contributes all eight bits
+            return Some(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Number of payload bytes stored on the b-tree page itself for a cell with
+/// `payload_size` bytes of payload, following SQLite's local payload formula.
+pub fn local_payload_size(
+    payload_size: u64,
+    page_type: &PageType,
+    usable_size: usize,
+    header: &DatabaseHeader,
+) -> u64 {
+    let usable = usable_size as u64;
+    let max_local = if *page_type == PageType::TableBTreeLeaf {
+        usable.saturating_sub(35)
+    } else {
+        (usable.saturating_sub(12) * header.max_embedded_payload_fraction as u64 / 255)
+            .saturating_sub(23)
+    };
+    let min_fraction = if *page_type == PageType::TableBTreeLeaf {
+        header.leaf_payload_fraction
+    } else {
+        header.min_embedded_payload_fraction
+    };
+    let min_local = (usable.saturating_sub(12) * min_fraction as u64 / 255).saturating_sub(23);
+
+    if payload_size <= max_local {
+        return payload_size;
+    }
+
+    let surplus = min_local + payload_size.saturating_sub(min_local) % usable.saturating_sub(4).max(1);
+    if surplus <= max_local {
+        surplus
+    } else {
+        min_local
+    }
+}
+
+/// Decode the cell pointer array of a b-tree page and every cell it points to.
+///
+/// `pointer_array_offset` is the offset within `page` of the first cell
+/// pointer. Cells whose pointer or body lies outside the page are skipped.
+pub fn parse_cells(
+    page: &[u8],
+    page_type: &PageType,
+    pointer_array_offset: usize,
+    cell_count: u16,
+    usable_size: usize,
+    header: &DatabaseHeader,
+) -> Vec<CellInfo> {
+    if !page_type.is_btree() {
+        return Vec::new();
+    }
+
+    let mut cells = Vec::with_capacity(cell_count as usize);
+    for index in 0..cell_count {
+        let pointer_offset = pointer_array_offset + index as usize * 2;
+        let Some(pointer) = page.get(pointer_offset..pointer_offset + 2) else {
+            break;
+        };
+        let cell_offset = u16::from_be_bytes([pointer[0], pointer[1]]) as usize;
+
+        if let Some(cell) = parse_cell(page, page_type, index, cell_offset, usable_size, header) {
+            cells.push(cell);
+        }
+    }
+
+    cells
+}
+
+fn parse_cell(
+    page: &[u8],
+    page_type: &PageType,
+    index: u16,
+    cell_offset: usize,
+    usable_size: usize,
+    header: &DatabaseHeader,
+) -> Option<CellInfo> {
+    let mut offset = cell_offset;
+
+    // Interior cells start with a 4-byte left child pointer
+    let left_child = if page_type.has_rightmost_pointer() {
+        let child = page.get(offset..offset + 4)?;
+        offset += 4;
+        Some(u32::from_be_bytes([child[0], child[1], child[2], child[3]]))
+    } else {
+        None
+    };
+
+    // Table interior cells carry no payload, only the key
+    let payload_size = if *page_type == PageType::TableBTreeInterior {
+        None
+    } else {
+        let (payload_size, len) = read_varint(page.get(offset..)?)?;
+        offset += len;
+        Some(payload_size)
+    };
+
+    let rowid = if matches!(
+        page_type,
+        PageType::TableBTreeLeaf | PageType::TableBTreeInterior
+    ) {
+        let (rowid, len) = read_varint(page.get(offset..)?)?;
+        offset += len;
+        Some(rowid as i64)
+    } else {
+        None
+    };
+
+    let payload_offset = offset;
+    let local_payload_size = payload_size.map_or(0, |size| {
+        local_payload_size(size, page_type, usable_size, header)
+    });
+    offset += local_payload_size as usize;
+
+    let first_overflow_page = if payload_size.is_some_and(|size| size > local_payload_size) {
+        let pointer = page.get(offset..offset + 4)?;
+        offset += 4;
+        Some(u32::from_be_bytes([pointer[0], pointer[1], pointer[2], pointer[3]]))
+    } else {
+        None
+    };
+
+    if offset > page.len() {
+        return None;
+    }
+
+    Some(CellInfo {
+        index,
+        offset: cell_offset as u32,
+        // SQLite never allocates less than 4 bytes for a cell
+        size: ((offset - cell_offset) as u32).max(4),
+        left_child,
+        rowid,
+        payload_size,
+        payload_offset: payload_offset as u32,
+        local_payload_size,
+        first_overflow_page,
+    })
+}
+
+/// Follow a page's freeblock chain from the header's first freeblock offset.
+///
+/// Each freeblock starts with the offset of the next one and its own size,
+/// and the chain must ascend through the cell content area. Returns the
+/// blocks up to the first broken link, with a description of the break.
+pub fn parse_freeblocks(
+    page: &[u8],
+    first_freeblock: u16,
+    content_start: usize,
+    usable_size: usize,
+) -> (Vec<Freeblock>, Option<String>) {
+    let mut freeblocks = Vec::new();
+    let mut offset = first_freeblock as usize;
+    let mut previous_end = 0;
+    while offset != 0 {
+        if offset < previous_end {
+            let problem = format!(
+                "Freeblock chain loops back to offset {}, before the end of the previous block",
+                offset
+            );
+            return (freeblocks, Some(problem));
+        }
+        if offset < content_start || offset + 4 > usable_size {
+            let problem = format!(
+                "Freeblock at offset {} lies outside the content area {}..{}",
+                offset, content_start, usable_size
+            );
+            return (freeblocks, Some(problem));
+        }
+
+        let next = u16::from_be_bytes([page[offset], page[offset + 1]]) as usize;
+        let size = u16::from_be_bytes([page[offset + 2], page[offset + 3]]) as usize;
+        if size < 4 || offset + size > usable_size {
+            let problem = format!(
+                "Freeblock at offset {} with size {} runs off the page",
+                offset, size
+            );
+            return (freeblocks, Some(problem));
+        }
+
+        freeblocks.push(Freeblock {
+            offset: offset as u32,
+            size: size as u32,
+        });
+        previous_end = offset + size;
+        offset = next;
+    }
+    (freeblocks, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> DatabaseHeader {
+        DatabaseHeader {
+            max_embedded_payload_fraction: 64,
+            min_embedded_payload_fraction: 32,
+            leaf_payload_fraction: 32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_read_varint() {
+        assert_eq!(read_varint(&[0x05]), Some((5, 1)));
+        assert_eq!(read_varint(&[0x81, 0x00]), Some((128, 2)));
+        assert_eq!(read_varint(&[0xff; 9]), Some((u64::MAX, 9)));
+        assert_eq!(read_varint(&[0x81]), None);
+    }
+
+    #[test]
+    fn test_local_payload_size_table_leaf() {
+        let header = header();
+        // Fits entirely on a 4096-byte page
+        assert_eq!(local_payload_size(4061, &PageType::TableBTreeLeaf, 4096, &header), 4061);
+        // One byte over the limit keeps only the minimum local payload on the page
+        assert_eq!(local_payload_size(4062, &PageType::TableBTreeLeaf, 4096, &header), 489);
+        assert_eq!(local_payload_size(10000, &PageType::TableBTreeLeaf, 4096, &header), 1816);
+    }
+
+    #[test]
+    fn test_local_payload_size_index() {
+        let header = header();
+        assert_eq!(local_payload_size(1002, &PageType::IndexBTreeLeaf, 4096, &header), 1002);
+        assert_eq!(local_payload_size(1003, &PageType::IndexBTreeLeaf, 4096, &header), 489);
+    }
+
+    #[test]
+    fn test_parse_cells_table_leaf() {
+        let header = header();
+        let mut page = vec![0u8; 512];
+        // Cell pointer array at offset 8 with two cells
+        page[8..10].copy_from_slice(&500u16.to_be_bytes());
+        page[10..12].copy_from_slice(&490u16.to_be_bytes());
+        // Cell 0: payload size 3, rowid 1, three payload bytes
+        page[500..505].copy_from_slice(&[0x03, 0x01, 0xaa, 0xbb, 0xcc]);
+        // Cell 1: payload size 2, rowid 300 (two-byte varint), two payload bytes
+        page[490..495].copy_from_slice(&[0x02, 0x82, 0x2c, 0xdd, 0xee]);
+
+        let cells = parse_cells(&page, &PageType::TableBTreeLeaf, 8, 2, 512, &header);
+
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].offset, 500);
+        assert_eq!(cells[0].size, 5);
+        assert_eq!(cells[0].rowid, Some(1));
+        assert_eq!(cells[0].payload_size, Some(3));
+        assert_eq!(cells[1].rowid, Some(300));
+        assert_eq!(cells[1].size, 5);
+        assert!(cells.iter().all(|cell| cell.first_overflow_page.is_none()));
+    }
+
+    #[test]
+    fn test_parse_cells_interior_reports_children() {
+        let header = header();
+        let mut page = vec![0u8; 512];
+        page[12..14].copy_from_slice(&505u16.to_be_bytes());
+        page[505..510].copy_from_slice(&[0x00, 0x00, 0x00, 0x07, 0x2a]);
+
+        let cells = parse_cells(&page, &PageType::TableBTreeInterior, 12, 1, 512, &header);
+
+        assert_eq!(cells[0].left_child, Some(7));
+        assert_eq!(cells[0].rowid, Some(42));
+        assert_eq!(cells[0].payload_size, None);
+    }
+
+    #[test]
+    fn test_parse_freeblocks() {
+        let mut page = vec![0u8; 512];
+        page[300..304].copy_from_slice(&[0x01, 0x90, 0x00, 0x10]);
+        page[400..404].copy_from_slice(&[0x00, 0x00, 0x00, 0x08]);
+        let (freeblocks, problem) = parse_freeblocks(&page, 300, 300, 512);
+        assert_eq!(
+            freeblocks,
+            vec![
+                Freeblock { offset: 300, size: 16 },
+                Freeblock { offset: 400, size: 8 },
+            ]
+        );
+        assert_eq!(problem, None);
+
+        // The second block points back at the first
+        page[400..402].copy_from_slice(&300u16.to_be_bytes());
+        let (freeblocks, problem) = parse_freeblocks(&page, 300, 300, 512);
+        assert_eq!(freeblocks.len(), 2);
+        assert!(problem.unwrap().contains("loops"));
+
+        // The second block is larger than the rest of the page
+        page[400..404].copy_from_slice(&[0x00, 0x00, 0x01, 0x00]);
+        let (freeblocks, problem) = parse_freeblocks(&page, 300, 300, 512);
+        assert_eq!(freeblocks.len(), 1);
+        assert!(problem.unwrap().contains("runs off"));
+
+        // A chain starting before the content area
+        assert!(parse_freeblocks(&page, 300, 350, 512).1.is_some());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::models::{DatabaseDiff, DatabaseInfo, PageDiffKind, PageFieldDiff, PageInfo};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Align two parsed databases by page number and classify every page
+pub fn diff_databases(left: Arc<DatabaseInfo>, right: Arc<DatabaseInfo>) -> DatabaseDiff {
+    let mut pages = left
+        .pages
+        .values()
+        .map(|page| {
+            let kind = match right.pages.get(&page.page_number) {
+                None => PageDiffKind::Removed,
+                Some(other) if other.page_type != page.page_type => PageDiffKind::TypeChanged,
+                Some(other) if other.content_hash == page.content_hash => PageDiffKind::Identical,
+                Some(_) => PageDiffKind::Modified,
+            };
+            (page.page_number, kind)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    for page_number in right.pages.keys() {
+        pages.entry(*page_number).or_insert(PageDiffKind::Added);
+    }
+
+    DatabaseDiff { left, right, pages }
+}
+
+/// The fields shown when comparing one page across two files. A field
+/// differs when its rendered values do.
+pub fn compare_page_fields(
+    left: Option<&PageInfo>,
+    right: Option<&PageInfo>,
+) -> Vec<PageFieldDiff> {
+    let field = |name: &'static str, value: fn(&PageInfo) -> String| PageFieldDiff {
+        name,
+        left: left.map(value),
+        right: right.map(value),
+    };
+
+    vec![
+        field("Page type", |page| page.page_type.name().to_string()),
+        field("Owner", |page| {
+            page.owner
+                .as_ref()
+                .map_or_else(|| "-".to_string(), |owner| owner.label())
+        }),
+        field("Cells", |page| page.cell_count.to_string()),
+        field("Free space", |page| format!("{} bytes", page.total_free_space())),
+        field("Unallocated", |page| format!("{} bytes", page.free_space)),
+        field("Freeblocks", |page| {
+            format!("{} bytes in {}", page.freeblock_space(), page.freeblocks.len())
+        }),
+        field("Fragmented", |page| {
+            format!("{} bytes", page.fragmented_bytes)
+        }),
+        field("Right-most pointer", |page| {
+            page.rightmost_pointer
+                .map_or_else(|| "-".to_string(), |pointer| pointer.to_string())
+        }),
+        field("Children", |page| {
+            page.child_pages()
+                .map(|child| child.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }),
+        field("Row ids", |page| {
+            let first = page.cells.first().and_then(|cell| cell.rowid);
+            let last = page.cells.last().and_then(|cell| cell.rowid);
+            match (first, last) {
+                (Some(first), Some(last)) => format!("{}..={}", first, last),
+                _ => "-".to_string(),
+            }
+        }),
+        field("Overflow of", |page| {
+            page.overflow_owner.as_ref().map_or_else(
+                || "-".to_string(),
+                |owner| format!("page {} cell {}", owner.page_number, owner.cell_index),
+            )
+        }),
+        field("Pointer map", |page| {
+            page.ptrmap_entry.as_ref().map_or_else(
+                || "-".to_string(),
+                |entry| format!("{} of {}", entry.entry_type.name(), entry.parent_page),
+            )
+        }),
+        field("Content hash", |page| format!("{:016x}", page.content_hash)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DatabaseHeader, PageType};
+
+    fn database(pages: &[(u32, PageType, u64)]) -> Arc<DatabaseInfo> {
+        let pages = pages
+            .iter()
+            .map(|(page_number, page_type, hash)| {
+                let page = PageInfo::new(*page_number, page_type.clone(), 0, 0, 0, None)
+                    .with_content_hash(*hash);
+                (*page_number, page)
+            })
+            .collect::<BTreeMap<_, _>>();
+        Arc::new(DatabaseInfo::new(
+            DatabaseHeader::default(),
+            Arc::new(pages),
+            0,
+        ))
+    }
+
+    #[test]
+    fn test_diff_classifies_pages() {
+        let left = database(&[
+            (1, PageType::TableBTreeLeaf, 1),
+            (2, PageType::TableBTreeLeaf, 2),
+            (3, PageType::IndexBTreeLeaf, 3),
+            (4, PageType::PayloadOverflow, 4),
+        ]);
+        let right = database(&[
+            (1, PageType::TableBTreeLeaf, 1),
+            (2, PageType::TableBTreeLeaf, 20),
+            (3, PageType::FreelistLeaf, 3),
+        ]);
+
+        let diff = diff_databases(left.clone(), right.clone());
+        assert_eq!(
+            diff.pages,
+            BTreeMap::from([
+                (1, PageDiffKind::Identical),
+                (2, PageDiffKind::Modified),
+                (3, PageDiffKind::TypeChanged),
+                (4, PageDiffKind::Removed),
+            ])
+        );
+        assert_eq!(diff.changed_count(), 3);
+
+        let reversed = diff_databases(right, left);
+        assert_eq!(reversed.pages[&4], PageDiffKind::Added);
+    }
+
+    #[test]
+    fn test_compare_page_fields_marks_differences() {
+        let left = PageInfo::new(2, PageType::TableBTreeLeaf, 3, 100, 0, None);
+        let right = PageInfo::new(2, PageType::TableBTreeLeaf, 5, 60, 0, None);
+
+        let differing = compare_page_fields(Some(&left), Some(&right))
+            .into_iter()
+            .filter(PageFieldDiff::differs)
+            .map(|field| field.name)
+            .collect::<Vec<_>>();
+        assert_eq!(differing, vec!["Cells", "Free space", "Unallocated"]);
+
+        let removed = compare_page_fields(Some(&left), None);
+        assert!(
+            removed
+                .iter()
+                .all(|field| field.differs() && field.right.is_none())
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::models::{DatabaseHeader, FreelistInfo};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::HashSet;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Follow the freelist trunk chain starting at `header.first_freelist_trunk_page`.
+///
+/// Every trunk page stores the next trunk page number, the number of leaf
+/// pointers it holds and then the leaf page numbers themselves. Problems such
+/// as loops or out-of-range pointers are recorded in `FreelistInfo::errors`
+/// rather than aborting, so a damaged freelist still yields a partial layout.
+pub fn walk_freelist<R: Read + Seek>(
+    reader: &mut R,
+    header: &DatabaseHeader,
+    page_size: usize,
+    total_pages: u32,
+) -> FreelistInfo {
+    let mut info = FreelistInfo {
+        expected_total: header.total_freelist_pages,
+        ..Default::default()
+    };
+
+    let mut visited = HashSet::new();
+    let mut trunk_page = header.first_freelist_trunk_page;
+
+    while trunk_page != 0 {
+        if trunk_page > total_pages {
+            info.errors.push(format!(
+                "Freelist trunk page {} is beyond the end of the file ({} pages)",
+                trunk_page, total_pages
+            ));
+            break;
+        }
+
+        if !visited.insert(trunk_page) {
+            info.errors.push(format!(
+                "Freelist trunk chain loops back to page {}",
+                trunk_page
+            ));
+            break;
+        }
+
+        let (next_trunk, leaves) = match read_trunk_page(reader, trunk_page, page_size) {
+            Ok(trunk) => trunk,
+            Err(e) => {
+                info.errors.push(format!(
+                    "Failed to read freelist trunk page {}: {}",
+                    trunk_page, e
+                ));
+                break;
+            }
+        };
+
+        info.trunk_pages.push(trunk_page);
+
+        for leaf_page in leaves {
+            if leaf_page == 0 || leaf_page > total_pages {
+                info.errors.push(format!(
+                    "Freelist trunk page {} points to invalid leaf page {}",
+                    trunk_page, leaf_page
+                ));
+                continue;
+            }
+
+            if !visited.insert(leaf_page) {
+                info.errors.push(format!(
+                    "Freelist page {} is listed more than once",
+                    leaf_page
+                ));
+                continue;
+            }
+
+            info.leaf_pages.push(leaf_page);
+        }
+
+        trunk_page = next_trunk;
+    }
+
+    info
+}
+
+fn read_trunk_page<R: Read + Seek>(
+    reader: &mut R,
+    page_number: u32,
+    page_size: usize,
+) -> Result<(u32, Vec<u32>)> {
+    let page_offset = ((page_number - 1) as u64) * (page_size as u64);
+    reader.seek(SeekFrom::Start(page_offset))?;
+
+    let next_trunk = reader.read_u32::<BigEndian>()?;
+    let leaf_count = reader.read_u32::<BigEndian>()?;
+
+    // A trunk page can hold at most (page_size / 4) - 2 leaf pointers
+    let max_leaves = page_size / 4 - 2;
+    if leaf_count as usize > max_leaves {
+        return Err(anyhow::anyhow!(
+            "leaf count {} exceeds the maximum of {}",
+            leaf_count,
+            max_leaves
+        ));
+    }
+
+    let mut leaves = Vec::with_capacity(leaf_count as usize);
+    for _ in 0..leaf_count {
+        leaves.push(reader.read_u32::<BigEndian>()?);
+    }
+
+    Ok((next_trunk, leaves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const PAGE_SIZE: usize = 512;
+
+    fn write_trunk(image: &mut [u8], page_number: u32, next_trunk: u32, leaves: &[u32]) {
+        let offset = (page_number as usize - 1) * PAGE_SIZE;
+        image[offset..offset + 4].copy_from_slice(&next_trunk.to_be_bytes());
+        image[offset + 4..offset + 8].copy_from_slice(&(leaves.len() as u32).to_be_bytes());
+        for (i, leaf) in leaves.iter().enumerate() {
+            let leaf_offset = offset + 8 + i * 4;
+            image[leaf_offset..leaf_offset + 4].copy_from_slice(&leaf.to_be_bytes());
+        }
+    }
+
+    fn header(first_trunk: u32, total_freelist_pages: u32) -> DatabaseHeader {
+        DatabaseHeader {
+            page_size: PAGE_SIZE as u16,
+            first_freelist_trunk_page: first_trunk,
+            total_freelist_pages,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_walk_multi_trunk_chain() {
+        let mut image = vec![0u8; PAGE_SIZE * 8];
+        write_trunk(&mut image, 2, 5, &[3, 4]);
+        write_trunk(&mut image, 5, 0, &[6, 7, 8]);
+
+        let info = walk_freelist(&mut Cursor::new(image), &header(2, 7), PAGE_SIZE, 8);
+
+        assert_eq!(info.trunk_pages, vec![2, 5]);
+        assert_eq!(info.leaf_pages, vec![3, 4, 6, 7, 8]);
+        assert!(!info.has_mismatch());
+        assert!(info.errors.is_empty());
+    }
+
+    #[test]
+    fn test_walk_detects_loop_and_count_mismatch() {
+        let mut image = vec![0u8; PAGE_SIZE * 4];
+        write_trunk(&mut image, 2, 3, &[4]);
+        write_trunk(&mut image, 3, 2, &[]);
+
+        let info = walk_freelist(&mut Cursor::new(image), &header(2, 5), PAGE_SIZE, 4);
+
+        assert_eq!(info.trunk_pages, vec![2, 3]);
+        assert_eq!(info.leaf_pages, vec![4]);
+        assert!(info.has_mismatch());
+        assert_eq!(info.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_walk_rejects_out_of_range_leaf() {
+        let mut image = vec![0u8; PAGE_SIZE * 3];
+        write_trunk(&mut image, 2, 0, &[3, 99]);
+
+        let info = walk_freelist(&mut Cursor::new(image), &header(2, 3), PAGE_SIZE, 3);
+
+        assert_eq!(info.leaf_pages, vec![3]);
+        assert_eq!(info.errors.len(), 1);
+    }
+}
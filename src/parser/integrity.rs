@@ -0,0 +1,228 @@
+use crate::models::{FindingKind, IntegrityFinding, PageInfo};
+use std::collections::{BTreeMap, HashMap};
+
+/// SQLite defragments a page before its fragmented byte count passes this
+const MAX_FRAGMENTED_BYTES: u8 = 60;
+
+/// Check the layout of one b-tree page against the rules SQLite keeps: cell
+/// pointers inside the content area, cells that do not overlap, a content
+/// area after the pointer array and an intact freeblock chain.
+/// `cell_content_start` is the raw header field and `freeblock_problem` the
+/// break `parse_freeblocks` found in the chain, if any.
+pub fn check_page_layout(
+    page: &[u8],
+    page_info: &PageInfo,
+    cell_content_start: u16,
+    freeblock_problem: Option<String>,
+    usable_size: usize,
+) -> Vec<IntegrityFinding> {
+    let mut findings = Vec::new();
+    if !page_info.page_type.is_btree() {
+        return findings;
+    }
+
+    let header_offset = if page_info.page_number == 1 { 100 } else { 0 };
+    let page_header_size = if page_info.rightmost_pointer.is_some() { 12 } else { 8 };
+    let pointer_array_start = header_offset + page_header_size;
+    let pointer_array_end = pointer_array_start + page_info.cell_count as usize * 2;
+    let content_start = if cell_content_start == 0 {
+        65536
+    } else {
+        cell_content_start as usize
+    };
+
+    if content_start < pointer_array_end {
+        findings.push(finding(
+            FindingKind::ContentInPointerArray,
+            format!(
+                "Cell content starts at offset {}, inside the pointer array ending at {}",
+                content_start, pointer_array_end
+            ),
+        ));
+    }
+
+    let content_area = content_start.max(pointer_array_end)..usable_size;
+    for index in 0..page_info.cell_count as usize {
+        let pointer_offset = pointer_array_start + index * 2;
+        let Some(pointer) = page.get(pointer_offset..pointer_offset + 2) else {
+            break;
+        };
+        let cell_offset = u16::from_be_bytes([pointer[0], pointer[1]]) as usize;
+        if !content_area.contains(&cell_offset) {
+            findings.push(finding(
+                FindingKind::CellPointerOutOfBounds,
+                format!(
+                    "Cell {} points at offset {}, outside the content area {}..{}",
+                    index, cell_offset, content_area.start, content_area.end
+                ),
+            ));
+        }
+    }
+
+    let mut cells: Vec<_> = page_info.cells.iter().collect();
+    cells.sort_by_key(|cell| cell.offset);
+    for pair in cells.windows(2) {
+        let (first, second) = (pair[0], pair[1]);
+        if first.offset + first.size > second.offset {
+            findings.push(finding(
+                FindingKind::CellsOverlap,
+                format!(
+                    "Cell {} at offset {} overlaps cell {} at offset {}",
+                    first.index, first.offset, second.index, second.offset
+                ),
+            ));
+        }
+    }
+
+    if let Some(problem) = freeblock_problem {
+        findings.push(finding(FindingKind::FreeblockChain, problem));
+    }
+
+    if page_info.fragmented_bytes > MAX_FRAGMENTED_BYTES {
+        findings.push(finding(
+            FindingKind::Fragmentation,
+            format!(
+                "{} fragmented bytes, above the limit of {}",
+                page_info.fragmented_bytes, MAX_FRAGMENTED_BYTES
+            ),
+        ));
+    }
+
+    findings
+}
+
+/// Check the links between b-tree pages: every child pointer must name a
+/// page in the file, and no page may be the child of two interior cells
+pub fn check_child_pointers(pages: &mut BTreeMap<u32, PageInfo>, total_pages: u32) {
+    let mut parents: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut past_end = Vec::new();
+    for (&page_number, page_info) in pages.iter() {
+        for child in page_info.child_pages() {
+            if child == 0 || child > total_pages {
+                past_end.push((page_number, child));
+            } else {
+                parents.entry(child).or_default().push(page_number);
+            }
+        }
+    }
+
+    for (page_number, child) in past_end {
+        if let Some(page) = pages.get_mut(&page_number) {
+            page.findings.push(finding(
+                FindingKind::ChildPastEnd,
+                format!(
+                    "Child pointer to page {} outside the file's {} pages",
+                    child, total_pages
+                ),
+            ));
+        }
+    }
+
+    for (child, claimed_by) in parents {
+        if claimed_by.len() < 2 {
+            continue;
+        }
+        if let Some(page) = pages.get_mut(&child) {
+            let claimed_by: Vec<_> = claimed_by.iter().map(u32::to_string).collect();
+            page.findings.push(finding(
+                FindingKind::MultipleParents,
+                format!("Child of more than one cell, on pages {}", claimed_by.join(", ")),
+            ));
+        }
+    }
+}
+
+fn finding(kind: FindingKind, message: String) -> IntegrityFinding {
+    IntegrityFinding { kind, message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CellInfo, PageType};
+
+    const USABLE_SIZE: usize = 512;
+
+    fn cell(index: u16, offset: u32, size: u32, left_child: Option<u32>) -> CellInfo {
+        CellInfo {
+            index,
+            offset,
+            size,
+            left_child,
+            rowid: Some(index as i64),
+            payload_size: None,
+            payload_offset: offset,
+            local_payload_size: 0,
+            first_overflow_page: None,
+        }
+    }
+
+    /// Leaf page 2 whose pointer array holds `pointers`
+    fn leaf_page(pointers: &[u16]) -> Vec<u8> {
+        let mut page = vec![0u8; USABLE_SIZE];
+        page[0] = 0x0d;
+        for (index, pointer) in pointers.iter().enumerate() {
+            page[8 + index * 2..10 + index * 2].copy_from_slice(&pointer.to_be_bytes());
+        }
+        page
+    }
+
+    fn kinds(findings: &[IntegrityFinding]) -> Vec<FindingKind> {
+        findings.iter().map(|finding| finding.kind).collect()
+    }
+
+    #[test]
+    fn test_check_page_layout() {
+        let page = leaf_page(&[400, 450]);
+        let page_info = PageInfo::new(2, PageType::TableBTreeLeaf, 2, 0, 0, None)
+            .with_cells(vec![cell(0, 400, 50, None), cell(1, 450, 20, None)]);
+        assert!(check_page_layout(&page, &page_info, 400, None, USABLE_SIZE).is_empty());
+
+        // A pointer into the header, cells overlapping and too many fragments
+        let page = leaf_page(&[4, 420, 400]);
+        let page_info = PageInfo::new(2, PageType::TableBTreeLeaf, 3, 0, 61, None)
+            .with_cells(vec![cell(1, 420, 30, None), cell(2, 400, 40, None)]);
+        assert_eq!(
+            kinds(&check_page_layout(&page, &page_info, 400, None, USABLE_SIZE)),
+            vec![
+                FindingKind::CellPointerOutOfBounds,
+                FindingKind::CellsOverlap,
+                FindingKind::Fragmentation,
+            ]
+        );
+
+        // Content starting inside the pointer array
+        let page_info = PageInfo::new(2, PageType::TableBTreeLeaf, 3, 0, 0, None);
+        assert_eq!(
+            kinds(&check_page_layout(&page, &page_info, 10, None, USABLE_SIZE))[0],
+            FindingKind::ContentInPointerArray
+        );
+    }
+
+    #[test]
+    fn test_check_child_pointers() {
+        let mut pages = BTreeMap::new();
+        pages.insert(
+            2,
+            PageInfo::new(2, PageType::TableBTreeInterior, 1, 0, 0, Some(4))
+                .with_cells(vec![cell(0, 500, 8, Some(3))]),
+        );
+        pages.insert(
+            5,
+            PageInfo::new(5, PageType::TableBTreeInterior, 1, 0, 0, Some(9))
+                .with_cells(vec![cell(0, 500, 8, Some(3))]),
+        );
+        for page_number in [3, 4] {
+            pages.insert(
+                page_number,
+                PageInfo::new(page_number, PageType::TableBTreeLeaf, 0, 0, 0, None),
+            );
+        }
+
+        check_child_pointers(&mut pages, 5);
+        assert_eq!(kinds(&pages[&3].findings), vec![FindingKind::MultipleParents]);
+        assert_eq!(kinds(&pages[&5].findings), vec![FindingKind::ChildPastEnd]);
+        assert!(pages[&2].findings.is_empty());
+        assert!(pages[&4].findings.is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::models::{DatabaseInfo, JournalHeader, JournalInfo, JournalPage, PageInfo};
+use crate::parser::sqlite_parser::{parse_page, read_page};
+use crate::parser::wal::open_database;
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Every rollback journal segment starts with these eight bytes
+const JOURNAL_MAGIC: [u8; 8] = [0xd9, 0xd5, 0x05, 0xf9, 0x20, 0xa1, 0x63, 0xd7];
+
+/// Magic, record count, nonce, initial size, sector size and page size
+const JOURNAL_HEADER_SIZE: u64 = 28;
+
+/// Record count SQLite writes when the count is derived from the file size
+const RECORD_COUNT_FROM_SIZE: u32 = 0xffff_ffff;
+
+/// Path of the rollback journal SQLite keeps next to `db_path`
+pub fn journal_path(db_path: &Path) -> PathBuf {
+    let mut path = OsString::from(db_path.as_os_str());
+    path.push("-journal");
+    PathBuf::from(path)
+}
+
+/// Decode the rollback journal next to `db_path`.
+///
+/// Returns `None` when there is no journal, it is empty (TRUNCATE mode) or
+/// its header was zeroed (PERSIST mode), since none of those hold a
+/// transaction that would be rolled back.
+pub fn read_journal_file(db_path: &Path) -> Result<Option<JournalInfo>> {
+    let path = journal_path(db_path);
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let journal_len = file.metadata()?.len();
+    let Some(mut journal) = parse_journal(&mut BufReader::new(file), path, journal_len)? else {
+        return Ok(None);
+    };
+
+    // SQLite ignores a journal next to an empty database
+    journal.is_hot = std::fs::metadata(db_path).is_ok_and(|metadata| metadata.len() > 0);
+    Ok(Some(journal))
+}
+
+/// Read every journal segment: a header padded to the sector size followed
+/// by page records of a page number, the original page image and a checksum.
+pub fn parse_journal<R: Read + Seek>(
+    reader: &mut R,
+    path: PathBuf,
+    journal_len: u64,
+) -> Result<Option<JournalInfo>> {
+    let mut header = None;
+    let mut pages = Vec::new();
+    let mut errors = Vec::new();
+    let mut segment_start = 0u64;
+
+    while segment_start + JOURNAL_HEADER_SIZE <= journal_len {
+        reader.seek(SeekFrom::Start(segment_start))?;
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+
+        if magic != JOURNAL_MAGIC {
+            if header.is_some() {
+                break;
+            }
+            if magic == [0u8; 8] {
+                return Ok(None);
+            }
+            return Err(anyhow::anyhow!("Not a rollback journal (bad magic)"));
+        }
+
+        let record_count = reader.read_u32::<BigEndian>()?;
+        let segment = JournalHeader {
+            record_count: (record_count != RECORD_COUNT_FROM_SIZE).then_some(record_count),
+            nonce: reader.read_u32::<BigEndian>()?,
+            initial_page_count: reader.read_u32::<BigEndian>()?,
+            sector_size: reader.read_u32::<BigEndian>()?,
+            page_size: reader.read_u32::<BigEndian>()?,
+        };
+
+        let page_size = segment.page_size as u64;
+        let sector_size = segment.sector_size as u64;
+        if !(512..=65536).contains(&page_size) || !page_size.is_power_of_two() {
+            errors.push(format!(
+                "Journal segment at byte {} has invalid page size {}",
+                segment_start, page_size
+            ));
+            break;
+        }
+        if sector_size < JOURNAL_HEADER_SIZE || !sector_size.is_power_of_two() {
+            errors.push(format!(
+                "Journal segment at byte {} has invalid sector size {}",
+                segment_start, sector_size
+            ));
+            break;
+        }
+
+        let record_size = page_size + 8;
+        let records_start = segment_start + sector_size;
+        let record_count = segment.record_count.map_or_else(
+            || journal_len.saturating_sub(records_start) / record_size,
+            |count| count as u64,
+        );
+
+        let mut page = vec![0u8; page_size as usize];
+        for record in 0..record_count {
+            let offset = records_start + record * record_size;
+            if offset + record_size > journal_len {
+                errors.push(format!(
+                    "Journal ends inside record {} of the segment at byte {}",
+                    record, segment_start
+                ));
+                break;
+            }
+
+            reader.seek(SeekFrom::Start(offset))?;
+            let page_number = reader.read_u32::<BigEndian>()?;
+            reader.read_exact(&mut page)?;
+            let checksum = reader.read_u32::<BigEndian>()?;
+
+            pages.push(JournalPage {
+                page_number,
+                offset: offset + 4,
+                checksum,
+                checksum_valid: journal_checksum(&page, segment.nonce) == checksum,
+            });
+        }
+
+        let derived_count = segment.record_count.is_none();
+        if header.is_none() {
+            header = Some(segment);
+        }
+        if derived_count {
+            break;
+        }
+
+        // The next segment header starts on the sector boundary after the records
+        let segment_end = records_start + record_count * record_size;
+        segment_start = segment_end.div_ceil(sector_size) * sector_size;
+    }
+
+    Ok(header.map(|header| JournalInfo {
+        path,
+        header,
+        pages,
+        errors,
+        is_hot: false,
+    }))
+}
+
+/// Original image of `page_number` saved in the journal, if it was journaled
+pub fn read_journal_page(journal: &JournalInfo, page_number: u32) -> Result<Option<Vec<u8>>> {
+    let Some(record) = journal.page(page_number) else {
+        return Ok(None);
+    };
+
+    let mut file = File::open(&journal.path)?;
+    file.seek(SeekFrom::Start(record.offset))?;
+    let mut page = vec![0u8; journal.header.page_size as usize];
+    file.read_exact(&mut page)?;
+    Ok(Some(page))
+}
+
+/// Journaled original of a page, decoded and compared with its current version
+#[derive(Debug, Clone)]
+pub struct JournalBeforeImage {
+    pub page: PageInfo,
+    pub checksum_valid: bool,
+    /// Bytes that differ between the journaled image and the current page
+    pub changed_bytes: usize,
+}
+
+/// Decode the image a rollback would restore for `page_number`
+pub fn read_before_image(
+    database_info: &DatabaseInfo,
+    page_number: u32,
+) -> Result<Option<JournalBeforeImage>> {
+    let Some(journal) = database_info.journal.as_ref() else {
+        return Ok(None);
+    };
+    let Some(record) = journal.page(page_number) else {
+        return Ok(None);
+    };
+
+    let page_size = database_info.header.actual_page_size();
+    if journal.header.page_size as usize != page_size {
+        return Err(anyhow::anyhow!(
+            "Journal page size {} does not match the database page size {}",
+            journal.header.page_size,
+            page_size
+        ));
+    }
+    let usable_size = page_size - database_info.header.reserved_space as usize;
+
+    let before = read_journal_page(journal, page_number)?.unwrap_or_default();
+    let current = read_page(&mut open_database(database_info)?, page_number, page_size)?;
+    let changed_bytes = before
+        .iter()
+        .zip(&current)
+        .filter(|(before, current)| before != current)
+        .count();
+
+    Ok(Some(JournalBeforeImage {
+        page: parse_page(
+            &before,
+            page_number,
+            page_size,
+            usable_size,
+            &database_info.header,
+        )?,
+        checksum_valid: record.checksum_valid,
+        changed_bytes,
+    }))
+}
+
+/// SQLite's journal checksum: the nonce plus every 200th byte of the page,
+/// walking backwards from 200 bytes before its end
+pub fn journal_checksum(page: &[u8], nonce: u32) -> u32 {
+    (1..)
+        .map(|step| page.len() as isize - 200 * step)
+        .take_while(|&index| index > 0)
+        .fold(nonce, |checksum, index| {
+            checksum.wrapping_add(page[index as usize] as u32)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const PAGE_SIZE: usize = 1024;
+    const SECTOR_SIZE: usize = 512;
+
+    fn segment_header(record_count: u32, nonce: u32) -> Vec<u8> {
+        let mut header = JOURNAL_MAGIC.to_vec();
+        header.extend_from_slice(&record_count.to_be_bytes());
+        header.extend_from_slice(&nonce.to_be_bytes());
+        header.extend_from_slice(&4u32.to_be_bytes());
+        header.extend_from_slice(&(SECTOR_SIZE as u32).to_be_bytes());
+        header.extend_from_slice(&(PAGE_SIZE as u32).to_be_bytes());
+        header.resize(SECTOR_SIZE, 0);
+        header
+    }
+
+    fn record(page_number: u32, fill: u8, nonce: u32) -> Vec<u8> {
+        let page = vec![fill; PAGE_SIZE];
+        let mut record = page_number.to_be_bytes().to_vec();
+        record.extend_from_slice(&page);
+        record.extend_from_slice(&journal_checksum(&page, nonce).to_be_bytes());
+        record
+    }
+
+    #[test]
+    fn test_parse_journal_segments() {
+        let nonce = 0x1234;
+        let mut journal = segment_header(2, nonce);
+        journal.extend(record(1, 0xaa, nonce));
+        journal.extend(record(3, 0xbb, nonce));
+        // Second segment starts at the next sector boundary
+        journal.resize(journal.len().div_ceil(SECTOR_SIZE) * SECTOR_SIZE, 0);
+        journal.extend(segment_header(RECORD_COUNT_FROM_SIZE, nonce));
+        journal.extend(record(2, 0xcc, nonce));
+
+        let len = journal.len() as u64;
+        let info = parse_journal(&mut Cursor::new(journal), PathBuf::from("t-journal"), len)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(info.header.record_count, Some(2));
+        assert_eq!(info.header.initial_page_count, 4);
+        assert_eq!(
+            info.pages
+                .iter()
+                .map(|page| page.page_number)
+                .collect::<Vec<_>>(),
+            vec![1, 3, 2]
+        );
+        assert!(info.pages.iter().all(|page| page.checksum_valid));
+        assert_eq!(info.pages[0].offset, SECTOR_SIZE as u64 + 4);
+        assert!(info.errors.is_empty());
+    }
+
+    #[test]
+    fn test_parse_journal_bad_checksum_and_truncation() {
+        let nonce = 7;
+        let mut journal = segment_header(3, nonce);
+        let mut damaged = record(2, 0x11, nonce);
+        damaged[4 + PAGE_SIZE - 200] ^= 0xff;
+        journal.extend(damaged);
+        journal.extend(&record(5, 0x22, nonce)[..100]);
+
+        let len = journal.len() as u64;
+        let info = parse_journal(&mut Cursor::new(journal), PathBuf::from("t-journal"), len)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(info.pages.len(), 1);
+        assert!(!info.pages[0].checksum_valid);
+        assert_eq!(info.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_zeroed_journal_is_inactive() {
+        let journal = vec![0u8; SECTOR_SIZE];
+        let len = journal.len() as u64;
+
+        let info =
+            parse_journal(&mut Cursor::new(journal), PathBuf::from("t-journal"), len).unwrap();
+
+        assert!(info.is_none());
+    }
+}
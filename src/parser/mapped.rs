@@ -0,0 +1,120 @@
+use crate::models::{DatabaseView, WalInfo};
+use crate::parser::journal::journal_path;
+use crate::parser::wal::{overlay_pages, wal_path};
+use anyhow::Result;
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Page reader over the database as a `DatabaseView` sees it, which any
+/// number of threads can share. An idle main file is memory-mapped and its
+/// pages borrowed without copying; pages with a committed WAL frame in the
+/// view, and every page of a file in use, are read with positional reads.
+///
+/// Like `WalOverlay`, pages past the end of the main file read as zeros.
+pub struct MappedDatabase {
+    main: PageSource,
+    wal: Option<File>,
+    /// Offset of the page image within the WAL for every overlaid page
+    page_offsets: HashMap<u32, u64>,
+    page_size: usize,
+}
+
+enum PageSource {
+    Mapped(Mmap),
+    File(File),
+}
+
+impl MappedDatabase {
+    pub fn open(
+        path: &Path,
+        view: DatabaseView,
+        wal: Option<&WalInfo>,
+        page_size: usize,
+    ) -> Result<Self> {
+        let file = File::open(path)?;
+        let in_use = wal_path(path).exists() || journal_path(path).exists();
+        let main = if in_use {
+            PageSource::File(file)
+        } else {
+            // SAFETY: the map is never written through, but another process
+            // truncating the file would make touching the lost pages raise
+            // SIGBUS and kill the app. Outside journal_mode OFF and MEMORY,
+            // SQLite only shrinks a database while a -journal or -wal exists
+            // beside it, so a file with neither is safe unless a writer
+            // starts a VACUUM during the parse.
+            PageSource::Mapped(unsafe { Mmap::map(&file)? })
+        };
+
+        // Checkpoints truncate and reset the WAL routinely, so it is never mapped
+        let overlay = wal.and_then(|wal| {
+            overlay_pages(wal, wal.frame_limit(view)).map(|(offsets, _)| (wal, offsets))
+        });
+        let (wal, page_offsets) = match overlay {
+            Some((wal, page_offsets)) => (Some(File::open(&wal.path)?), page_offsets),
+            None => (None, HashMap::new()),
+        };
+
+        Ok(Self {
+            main,
+            wal,
+            page_offsets,
+            page_size,
+        })
+    }
+
+    /// Bytes of a single page, borrowed from the map unless it was read from
+    /// a file or had to be zero-filled past the end of one
+    pub fn page(&self, page_number: u32) -> io::Result<Cow<'_, [u8]>> {
+        if let (Some(&frame_offset), Some(wal)) =
+            (self.page_offsets.get(&page_number), self.wal.as_ref())
+        {
+            return self.read_page_at(wal, frame_offset).map(Cow::Owned);
+        }
+
+        let offset = (page_number as u64 - 1) * self.page_size as u64;
+        let map = match &self.main {
+            PageSource::Mapped(map) => map,
+            PageSource::File(file) => return self.read_page_at(file, offset).map(Cow::Owned),
+        };
+
+        let offset = offset as usize;
+        match map.get(offset..offset + self.page_size) {
+            Some(page) => Ok(Cow::Borrowed(page)),
+            None => {
+                let mut page = vec![0u8; self.page_size];
+                if let Some(partial) = map.get(offset..) {
+                    page[..partial.len()].copy_from_slice(partial);
+                }
+                Ok(Cow::Owned(page))
+            }
+        }
+    }
+
+    fn read_page_at(&self, file: &File, offset: u64) -> io::Result<Vec<u8>> {
+        let mut page = vec![0u8; self.page_size];
+        let mut filled = 0;
+        while filled < page.len() {
+            match read_at(file, &mut page[filled..], offset + filled as u64) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(page)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
@@ -1,18 +1,48 @@
+pub mod annotate;
+pub mod btree;
+pub mod cell;
+pub mod diff;
+pub mod freelist;
+pub mod integrity;
+pub mod journal;
+pub mod mapped;
+pub mod overflow;
+pub mod ptrmap;
+pub mod reachability;
+pub mod record;
+pub mod schema;
+pub mod shm;
 pub mod sqlite_parser;
+pub mod wal;
 
-use crate::models::DatabaseInfo;
+use crate::models::{DatabaseInfo, DatabaseView};
 use anyhow::Result;
-use std::{path::Path, sync::Arc};
+use std::{collections::BTreeSet, path::Path, sync::Arc};
 use std::sync::atomic::AtomicBool;
 
-pub use sqlite_parser::{ProgressCallback, BatchParseConfig};
+pub use sqlite_parser::{ProgressCallback, BatchParseConfig, PageReadStrategy, ParseCancelledError};
 
 pub trait DatabaseParser {
     fn parse_file<P: AsRef<Path> + Send>(&self, path: P) -> Result<Arc<DatabaseInfo>>;
+
+    fn parse_file_view<P: AsRef<Path> + Send>(
+        &self,
+        path: P,
+        view: DatabaseView,
+    ) -> Result<Arc<DatabaseInfo>>;
+
+    /// Parse a changed file again, returning the pages that changed since `previous`
+    fn reparse_file_view<P: AsRef<Path> + Send>(
+        &self,
+        path: P,
+        view: DatabaseView,
+        previous: &Arc<DatabaseInfo>,
+    ) -> Result<(Arc<DatabaseInfo>, BTreeSet<u32>)>;
     
     fn parse_file_with_progress<P: AsRef<Path> + Send>(
         &self,
         path: P,
+        view: DatabaseView,
         progress_callback: Option<ProgressCallback>,
         cancel_flag: Option<Arc<AtomicBool>>,
         config: Option<BatchParseConfig>,
@@ -25,16 +55,35 @@ impl DatabaseParser for SqliteParser {
     fn parse_file<P: AsRef<Path> + Send>(&self, path: P) -> Result<Arc<DatabaseInfo>> {
         sqlite_parser::parse_database_file(path.as_ref())
     }
+
+    fn parse_file_view<P: AsRef<Path> + Send>(
+        &self,
+        path: P,
+        view: DatabaseView,
+    ) -> Result<Arc<DatabaseInfo>> {
+        sqlite_parser::parse_database_view(path.as_ref(), view, None, None, None)
+    }
+
+    fn reparse_file_view<P: AsRef<Path> + Send>(
+        &self,
+        path: P,
+        view: DatabaseView,
+        previous: &Arc<DatabaseInfo>,
+    ) -> Result<(Arc<DatabaseInfo>, BTreeSet<u32>)> {
+        sqlite_parser::reparse_database_view(path.as_ref(), view, previous, None)
+    }
     
     fn parse_file_with_progress<P: AsRef<Path> + Send>(
         &self,
         path: P,
+        view: DatabaseView,
         progress_callback: Option<ProgressCallback>,
         cancel_flag: Option<Arc<AtomicBool>>,
         config: Option<BatchParseConfig>,
     ) -> Result<Arc<DatabaseInfo>> {
-        sqlite_parser::parse_database_file_with_progress(
+        sqlite_parser::parse_database_view(
             path.as_ref(),
+            view,
             progress_callback,
             cancel_flag,
             config,
@@ -45,3 +94,52 @@ impl DatabaseParser for SqliteParser {
 pub fn create_sqlite_parser() -> SqliteParser {
     SqliteParser
 }
+
+/// Databases built by hand for tests across the crate
+#[cfg(test)]
+pub(crate) mod test_support {
+    /// Database image whose first page holds an empty schema and every other
+    /// page is a table leaf with `rows_on(page_number)` one-column rows
+    pub fn database_image(
+        page_size: usize,
+        page_count: usize,
+        change_counter: u32,
+        rows_on: impl Fn(usize) -> usize,
+    ) -> Vec<u8> {
+        let mut data = vec![0u8; page_size * page_count];
+        data[..16].copy_from_slice(b"SQLite format 3\0");
+        data[16..18].copy_from_slice(&(page_size as u16).to_be_bytes());
+        data[18] = 1;
+        data[19] = 1;
+        data[21] = 64;
+        data[22] = 32;
+        data[23] = 32;
+        data[24..28].copy_from_slice(&change_counter.to_be_bytes());
+        data[28..32].copy_from_slice(&(page_count as u32).to_be_bytes());
+        data[44..48].copy_from_slice(&4u32.to_be_bytes());
+        data[56..60].copy_from_slice(&1u32.to_be_bytes());
+
+        for page_number in 1..=page_count {
+            let page = &mut data[(page_number - 1) * page_size..page_number * page_size];
+            let header_offset = if page_number == 1 { 100 } else { 0 };
+            let cell_count = if page_number == 1 { 0 } else { rows_on(page_number) };
+
+            let mut content_start = page_size;
+            for cell in 0..cell_count {
+                content_start -= 5;
+                // Payload of 3 bytes, rowid, then a record holding one 1-byte integer
+                page[content_start..content_start + 5]
+                    .copy_from_slice(&[3, cell as u8 + 1, 2, 1, page_number as u8]);
+                let pointer = header_offset + 8 + cell * 2;
+                page[pointer..pointer + 2].copy_from_slice(&(content_start as u16).to_be_bytes());
+            }
+
+            page[header_offset] = 0x0d;
+            page[header_offset + 3..header_offset + 5]
+                .copy_from_slice(&(cell_count as u16).to_be_bytes());
+            page[header_offset + 5..header_offset + 7]
+                .copy_from_slice(&(content_start as u16).to_be_bytes());
+        }
+        data
+    }
+}
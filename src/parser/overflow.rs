@@ -0,0 +1,110 @@
+use crate::models::{OverflowOwner, PageInfo, PageType};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::{BTreeMap, HashSet};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Follow the overflow chain of every cell whose payload spills off its page
+/// and retag the pages it passes through as `PageType::PayloadOverflow`,
+/// linked back to the owning cell.
+///
+/// Returns a description of every chain that could not be followed to its end.
+pub fn follow_overflow_chains<R: Read + Seek>(
+    reader: &mut R,
+    pages: &mut BTreeMap<u32, PageInfo>,
+    page_size: usize,
+    usable_size: usize,
+    total_pages: u32,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut claimed = HashSet::new();
+    let content_per_page = (usable_size as u64).saturating_sub(4).max(1);
+
+    // (owner page, cell index, first overflow page, overflow bytes)
+    let spilled: Vec<(u32, u16, u32, u64)> = pages
+        .values()
+        .flat_map(|page| {
+            page.cells.iter().filter_map(|cell| {
+                Some((
+                    page.page_number,
+                    cell.index,
+                    cell.first_overflow_page?,
+                    cell.overflow_bytes(),
+                ))
+            })
+        })
+        .collect();
+
+    for (owner_page, cell_index, first_overflow_page, overflow_bytes) in spilled {
+        let chain_length = overflow_bytes.div_ceil(content_per_page) as u32;
+        let mut overflow_page = first_overflow_page;
+
+        for chain_position in 1..=chain_length {
+            if overflow_page == 0 || overflow_page > total_pages {
+                errors.push(format!(
+                    "Overflow chain of page {} cell {} points to invalid page {}",
+                    owner_page, cell_index, overflow_page
+                ));
+                break;
+            }
+
+            if !claimed.insert(overflow_page) {
+                errors.push(format!(
+                    "Overflow page {} is claimed by more than one chain (page {} cell {})",
+                    overflow_page, owner_page, cell_index
+                ));
+                break;
+            }
+
+            if let Some(existing) = pages.get(&overflow_page)
+                && matches!(
+                    existing.page_type,
+                    PageType::FreelistTrunk | PageType::FreelistLeaf
+                )
+            {
+                errors.push(format!(
+                    "Overflow chain of page {} cell {} runs into freelist page {}",
+                    owner_page, cell_index, overflow_page
+                ));
+                break;
+            }
+
+            let content_hash = pages.get(&overflow_page).map_or(0, |page| page.content_hash);
+            let page_info = PageInfo::new(overflow_page, PageType::PayloadOverflow, 0, 0, 0, None)
+                .with_content_hash(content_hash)
+                .with_overflow_owner(OverflowOwner {
+                    page_number: owner_page,
+                    cell_index,
+                    chain_position,
+                    chain_length,
+                });
+            let _ = pages.insert(overflow_page, page_info);
+
+            if chain_position == chain_length {
+                break;
+            }
+
+            match read_next_overflow_page(reader, overflow_page, page_size) {
+                Ok(next_page) => overflow_page = next_page,
+                Err(e) => {
+                    errors.push(format!(
+                        "Failed to read overflow page {}: {}",
+                        overflow_page, e
+                    ));
+                    break;
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+fn read_next_overflow_page<R: Read + Seek>(
+    reader: &mut R,
+    page_number: u32,
+    page_size: usize,
+) -> std::io::Result<u32> {
+    let page_offset = ((page_number - 1) as u64) * (page_size as u64);
+    reader.seek(SeekFrom::Start(page_offset))?;
+    reader.read_u32::<BigEndian>()
+}
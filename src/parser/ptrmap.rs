@@ -0,0 +1,326 @@
+use crate::models::{PageInfo, PageType, PointerMapInfo, PtrMapEntry, PtrMapType};
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek, SeekFrom};
+
+/// Byte offset of the 512-byte range SQLite uses for file locking
+const PENDING_BYTE: u64 = 0x4000_0000;
+
+/// Size in bytes of one pointer-map entry: a type byte and a 4-byte parent page
+const PTRMAP_ENTRY_SIZE: usize = 5;
+
+/// Page that contains the lock-byte range. SQLite never stores data on it.
+pub fn lock_byte_page(page_size: usize) -> u32 {
+    (PENDING_BYTE / page_size as u64) as u32 + 1
+}
+
+/// Pointer-map page holding the entry for `page_number`
+pub fn ptrmap_page_for(page_number: u32, page_size: usize, usable_size: usize) -> u32 {
+    if page_number < 2 {
+        return 0;
+    }
+
+    let pages_per_map_page = (usable_size / PTRMAP_ENTRY_SIZE) as u32 + 1;
+    let map_index = (page_number - 2) / pages_per_map_page;
+    let map_page = map_index * pages_per_map_page + 2;
+
+    if map_page == lock_byte_page(page_size) {
+        map_page + 1
+    } else {
+        map_page
+    }
+}
+
+pub fn is_ptrmap_page(page_number: u32, page_size: usize, usable_size: usize) -> bool {
+    ptrmap_page_for(page_number, page_size, usable_size) == page_number
+}
+
+/// Every pointer-map page in a database of `total_pages` pages
+pub fn ptrmap_pages(total_pages: u32, page_size: usize, usable_size: usize) -> Vec<u32> {
+    let pages_per_map_page = (usable_size / PTRMAP_ENTRY_SIZE) as u32 + 1;
+    (0..)
+        .map(|map_index| ptrmap_page_for(map_index * pages_per_map_page + 2, page_size, usable_size))
+        .take_while(|&page| page <= total_pages)
+        .collect()
+}
+
+/// Decode the pointer-map entry of every page and check it against the page
+/// ownership the parser discovered on its own.
+///
+/// `parents` maps each b-tree child page to the interior page pointing at it.
+/// The decoded entries are attached to the matching `PageInfo`.
+pub fn decode_pointer_map<R: Read + Seek>(
+    reader: &mut R,
+    pages: &mut BTreeMap<u32, PageInfo>,
+    parents: &HashMap<u32, u32>,
+    page_size: usize,
+    usable_size: usize,
+    total_pages: u32,
+) -> Result<PointerMapInfo> {
+    let map_pages = ptrmap_pages(total_pages, page_size, usable_size);
+    let lock_page = lock_byte_page(page_size);
+
+    let mut map_contents = HashMap::new();
+    for &map_page in &map_pages {
+        let page_offset = ((map_page - 1) as u64) * (page_size as u64);
+        reader.seek(SeekFrom::Start(page_offset))?;
+        let mut content = vec![0u8; usable_size];
+        reader.read_exact(&mut content)?;
+        map_contents.insert(map_page, content);
+    }
+
+    // Overflow pages indexed by (owner page, cell, chain position) so an
+    // Overflow2 entry can be checked against the previous page in its chain
+    let overflow_positions: HashMap<(u32, u16, u32), u32> = pages
+        .values()
+        .filter_map(|page| {
+            let owner = page.overflow_owner.as_ref()?;
+            Some((
+                (owner.page_number, owner.cell_index, owner.chain_position),
+                page.page_number,
+            ))
+        })
+        .collect();
+
+    let mut entries = Vec::new();
+    for page_number in 3..=total_pages {
+        if page_number == lock_page || is_ptrmap_page(page_number, page_size, usable_size) {
+            continue;
+        }
+
+        let map_page = ptrmap_page_for(page_number, page_size, usable_size);
+        let Some(content) = map_contents.get(&map_page) else {
+            continue;
+        };
+
+        let offset = PTRMAP_ENTRY_SIZE * (page_number - map_page - 1) as usize;
+        let Some(raw) = content.get(offset..offset + PTRMAP_ENTRY_SIZE) else {
+            continue;
+        };
+
+        let entry_type = PtrMapType::from_byte(raw[0]);
+        let parent_page = u32::from_be_bytes([raw[1], raw[2], raw[3], raw[4]]);
+        entries.push((page_number, entry_type, parent_page));
+    }
+
+    let mut info = PointerMapInfo {
+        ptrmap_pages: map_pages,
+        entry_count: entries.len(),
+        mismatch_count: 0,
+    };
+
+    for (page_number, entry_type, parent_page) in entries {
+        let mismatch = pages.get(&page_number).and_then(|page| {
+            check_entry(page, entry_type, parent_page, parents, &overflow_positions)
+        });
+
+        if mismatch.is_some() {
+            info.mismatch_count += 1;
+        }
+
+        if let Some(page) = pages.get_mut(&page_number) {
+            page.ptrmap_entry = Some(PtrMapEntry {
+                entry_type,
+                parent_page,
+                mismatch,
+            });
+        }
+    }
+
+    Ok(info)
+}
+
+fn check_entry(
+    page: &PageInfo,
+    entry_type: PtrMapType,
+    parent_page: u32,
+    parents: &HashMap<u32, u32>,
+    overflow_positions: &HashMap<(u32, u16, u32), u32>,
+) -> Option<String> {
+    match entry_type {
+        PtrMapType::RootPage => {
+            if !page.page_type.is_btree() {
+                Some(format!(
+                    "Marked as a b-tree root but parsed as {}",
+                    page.page_type.name()
+                ))
+            } else {
+                parents.get(&page.page_number).map(|parent| {
+                    format!(
+                        "Marked as a b-tree root but interior page {} points to it",
+                        parent
+                    )
+                })
+            }
+        }
+        PtrMapType::FreePage => match page.page_type {
+            PageType::FreelistTrunk | PageType::FreelistLeaf => None,
+            _ => Some(format!(
+                "Marked as free but parsed as {}",
+                page.page_type.name()
+            )),
+        },
+        PtrMapType::Overflow1 | PtrMapType::Overflow2 => {
+            let Some(owner) = page.overflow_owner.as_ref() else {
+                return Some(format!(
+                    "Marked as an overflow page but parsed as {}",
+                    page.page_type.name()
+                ));
+            };
+
+            let expected_parent = if owner.chain_position == 1 {
+                Some(owner.page_number)
+            } else {
+                overflow_positions
+                    .get(&(owner.page_number, owner.cell_index, owner.chain_position - 1))
+                    .copied()
+            };
+
+            if (entry_type == PtrMapType::Overflow1) != (owner.chain_position == 1) {
+                Some(format!(
+                    "Entry type {} does not match chain position {}",
+                    entry_type.name(),
+                    owner.chain_position
+                ))
+            } else if expected_parent != Some(parent_page) {
+                Some(format!(
+                    "Parent recorded as page {} but the chain comes from page {}",
+                    parent_page,
+                    expected_parent.map_or("?".to_string(), |page| page.to_string())
+                ))
+            } else {
+                None
+            }
+        }
+        PtrMapType::BTree => {
+            if !page.page_type.is_btree() {
+                Some(format!(
+                    "Marked as a b-tree page but parsed as {}",
+                    page.page_type.name()
+                ))
+            } else if parents.get(&page.page_number) != Some(&parent_page) {
+                Some(format!(
+                    "Parent recorded as page {} but the parser found {}",
+                    parent_page,
+                    parents
+                        .get(&page.page_number)
+                        .map_or("no parent".to_string(), |page| format!("page {}", page))
+                ))
+            } else {
+                None
+            }
+        }
+        PtrMapType::Invalid(byte) => Some(format!("Invalid entry type {}", byte)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OverflowOwner;
+    use std::io::Cursor;
+
+    const PAGE_SIZE: usize = 1024;
+
+    /// Pages 3 to 7 of an auto-vacuum database: table root 3 over leaf 4,
+    /// whose cell 0 spills onto overflow pages 5 and 6, and free page 7
+    fn parsed_pages() -> BTreeMap<u32, PageInfo> {
+        let overflow = |page_number, chain_position| {
+            PageInfo::new(page_number, PageType::PayloadOverflow, 0, 0, 0, None)
+                .with_overflow_owner(OverflowOwner {
+                    page_number: 4,
+                    cell_index: 0,
+                    chain_position,
+                    chain_length: 2,
+                })
+        };
+        BTreeMap::from([
+            (3, PageInfo::new(3, PageType::TableBTreeInterior, 1, 0, 0, Some(4))),
+            (4, PageInfo::new(4, PageType::TableBTreeLeaf, 1, 0, 0, None)),
+            (5, overflow(5, 1)),
+            (6, overflow(6, 2)),
+            (7, PageInfo::new(7, PageType::FreelistLeaf, 0, 0, 0, None)),
+        ])
+    }
+
+    /// Seven-page database whose pointer-map page 2 holds `entries` for
+    /// pages 3 onwards
+    fn database_with_map(entries: &[(u8, u32)]) -> Cursor<Vec<u8>> {
+        let mut data = vec![0u8; PAGE_SIZE * 7];
+        for (index, &(entry_type, parent)) in entries.iter().enumerate() {
+            let offset = PAGE_SIZE + index * PTRMAP_ENTRY_SIZE;
+            data[offset] = entry_type;
+            data[offset + 1..offset + 5].copy_from_slice(&parent.to_be_bytes());
+        }
+        Cursor::new(data)
+    }
+
+    fn decode(entries: &[(u8, u32)]) -> (PointerMapInfo, BTreeMap<u32, PageInfo>) {
+        let mut pages = parsed_pages();
+        let parents = HashMap::from([(4, 3)]);
+        let info = decode_pointer_map(
+            &mut database_with_map(entries),
+            &mut pages,
+            &parents,
+            PAGE_SIZE,
+            PAGE_SIZE,
+            7,
+        )
+        .unwrap();
+        (info, pages)
+    }
+
+    #[test]
+    fn test_decode_pointer_map_matching_entries() {
+        let (info, pages) = decode(&[(1, 0), (5, 3), (3, 4), (4, 5), (2, 0)]);
+
+        assert_eq!(info.ptrmap_pages, vec![2]);
+        assert_eq!(info.entry_count, 5);
+        assert_eq!(info.mismatch_count, 0);
+        let entry = pages[&6].ptrmap_entry.as_ref().unwrap();
+        assert_eq!(entry.entry_type, PtrMapType::Overflow2);
+        assert_eq!(entry.parent_page, 5);
+    }
+
+    #[test]
+    fn test_decode_pointer_map_mismatched_entries() {
+        // Leaf 4 claims the wrong parent, overflow page 6 claims to start its
+        // chain, free page 7 claims to be a root, and root 3 is undecodable
+        let (info, pages) = decode(&[(9, 0), (5, 6), (3, 4), (3, 4), (1, 0)]);
+
+        assert_eq!(info.entry_count, 5);
+        assert_eq!(info.mismatch_count, 4);
+        let mismatch = |page_number| {
+            pages[&page_number]
+                .ptrmap_entry
+                .as_ref()
+                .and_then(|entry| entry.mismatch.clone())
+        };
+        assert_eq!(mismatch(3).as_deref(), Some("Invalid entry type 9"));
+        assert_eq!(
+            mismatch(4).as_deref(),
+            Some("Parent recorded as page 6 but the parser found page 3")
+        );
+        assert_eq!(mismatch(5), None);
+        assert!(mismatch(6).unwrap().contains("chain position 2"));
+        assert!(mismatch(7).unwrap().starts_with("Marked as a b-tree root"));
+    }
+
+    #[test]
+    fn test_ptrmap_page_positions() {
+        // 1024-byte pages hold 204 entries, so map pages repeat every 205 pages
+        assert_eq!(ptrmap_page_for(3, 1024, 1024), 2);
+        assert_eq!(ptrmap_page_for(206, 1024, 1024), 2);
+        assert_eq!(ptrmap_page_for(207, 1024, 1024), 207);
+        assert!(is_ptrmap_page(2, 1024, 1024));
+        assert!(is_ptrmap_page(207, 1024, 1024));
+        assert!(!is_ptrmap_page(208, 1024, 1024));
+        assert_eq!(ptrmap_pages(500, 1024, 1024), vec![2, 207, 412]);
+    }
+
+    #[test]
+    fn test_lock_byte_page() {
+        assert_eq!(lock_byte_page(4096), 262145);
+        assert_eq!(lock_byte_page(65536), 16385);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::models::{BTree, FreelistInfo, PageInfo, PageReference, PageType, Reachability};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Collect every reference to every page, starting from the schema roots,
+/// the freelist and the overflow chains of reachable cells. A page nothing
+/// references is lost and a page referenced more than once is shared.
+/// Pointer-map pages and the lock-byte page sit at fixed positions, so they
+/// need no reference.
+pub fn analyze_reachability(
+    pages: &BTreeMap<u32, PageInfo>,
+    btrees: &[BTree],
+    freelist: &FreelistInfo,
+) -> Reachability {
+    let mut references: BTreeMap<u32, Vec<PageReference>> = BTreeMap::new();
+
+    // A page shared by two trees is listed in both, so each reachable b-tree
+    // page contributes its child pointers once
+    let mut btree_pages = BTreeSet::new();
+    for btree in btrees {
+        references
+            .entry(btree.root_page)
+            .or_default()
+            .push(PageReference::Root(btree.owner.clone()));
+        btree_pages.extend(btree.nodes.iter().map(|node| node.page_number));
+    }
+
+    for page in btree_pages.iter().filter_map(|page_number| pages.get(page_number)) {
+        for child in page.child_pages() {
+            references
+                .entry(child)
+                .or_default()
+                .push(PageReference::Child(page.page_number));
+        }
+        for cell in &page.cells {
+            if let Some(first_overflow_page) = cell.first_overflow_page {
+                references
+                    .entry(first_overflow_page)
+                    .or_default()
+                    .push(PageReference::Overflow {
+                        page_number: page.page_number,
+                        cell_index: cell.index,
+                    });
+            }
+        }
+    }
+
+    for page in pages.values() {
+        if let Some(owner) = page.overflow_owner.as_ref()
+            && owner.chain_position > 1
+            && btree_pages.contains(&owner.page_number)
+        {
+            references
+                .entry(page.page_number)
+                .or_default()
+                .push(PageReference::OverflowChain {
+                    page_number: owner.page_number,
+                    cell_index: owner.cell_index,
+                });
+        }
+    }
+
+    for &page_number in &freelist.trunk_pages {
+        references
+            .entry(page_number)
+            .or_default()
+            .push(PageReference::FreelistTrunk);
+    }
+    for &page_number in &freelist.leaf_pages {
+        references
+            .entry(page_number)
+            .or_default()
+            .push(PageReference::FreelistLeaf);
+    }
+
+    let lost = pages
+        .values()
+        .filter(|page| !matches!(page.page_type, PageType::PointerMap | PageType::LockByte))
+        .filter(|page| !references.contains_key(&page.page_number))
+        .map(|page| page.page_number)
+        .collect();
+    let shared = references
+        .into_iter()
+        .filter(|(page_number, references)| {
+            references.len() > 1 && pages.contains_key(page_number)
+        })
+        .collect();
+
+    Reachability { lost, shared }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BTreeNode, CellInfo, OverflowOwner, PageOwner, PageReach};
+
+    fn owner(name: &str) -> PageOwner {
+        PageOwner {
+            object_type: "table".to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    fn cell(index: u16, left_child: Option<u32>, first_overflow_page: Option<u32>) -> CellInfo {
+        CellInfo {
+            index,
+            offset: 0,
+            size: 0,
+            left_child,
+            rowid: None,
+            payload_size: None,
+            payload_offset: 0,
+            local_payload_size: 0,
+            first_overflow_page,
+        }
+    }
+
+    fn btree(root_page: u32, name: &str, page_numbers: &[u32]) -> BTree {
+        BTree {
+            root_page,
+            owner: owner(name),
+            nodes: page_numbers
+                .iter()
+                .map(|&page_number| BTreeNode {
+                    page_number,
+                    depth: 0,
+                    children: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_analyze_reachability() {
+        // Page 2 is the root of table t, with leaves 3 and 4. Leaf 3 spills
+        // into overflow pages 5 and 6, page 7 is on the freelist, page 8 is
+        // referenced by nothing and page 4 is also claimed by page 1.
+        let mut pages = BTreeMap::new();
+        pages.insert(
+            1,
+            PageInfo::new(1, PageType::TableBTreeLeaf, 1, 0, 0, None)
+                .with_cells(vec![cell(0, None, Some(4))]),
+        );
+        pages.insert(
+            2,
+            PageInfo::new(2, PageType::TableBTreeInterior, 1, 0, 0, Some(4))
+                .with_cells(vec![cell(0, Some(3), None)]),
+        );
+        pages.insert(
+            3,
+            PageInfo::new(3, PageType::TableBTreeLeaf, 1, 0, 0, None)
+                .with_cells(vec![cell(0, None, Some(5))]),
+        );
+        pages.insert(4, PageInfo::new(4, PageType::TableBTreeLeaf, 0, 0, 0, None));
+        for (page_number, chain_position) in [(5, 1), (6, 2)] {
+            pages.insert(
+                page_number,
+                PageInfo::new(page_number, PageType::PayloadOverflow, 0, 0, 0, None)
+                    .with_overflow_owner(OverflowOwner {
+                        page_number: 3,
+                        cell_index: 0,
+                        chain_position,
+                        chain_length: 2,
+                    }),
+            );
+        }
+        pages.insert(7, PageInfo::new(7, PageType::FreelistLeaf, 0, 0, 0, None));
+        pages.insert(8, PageInfo::new(8, PageType::TableBTreeLeaf, 0, 0, 0, None));
+        pages.insert(9, PageInfo::new(9, PageType::PointerMap, 0, 0, 0, None));
+
+        let btrees = vec![
+            btree(1, "sqlite_schema", &[1]),
+            btree(2, "t", &[2, 3, 4]),
+        ];
+        let freelist = FreelistInfo {
+            trunk_pages: Vec::new(),
+            leaf_pages: vec![7],
+            expected_total: 1,
+            errors: Vec::new(),
+        };
+
+        let reachability = analyze_reachability(&pages, &btrees, &freelist);
+        assert_eq!(reachability.lost, BTreeSet::from([8]));
+        assert_eq!(
+            reachability.shared.get(&4),
+            Some(&vec![
+                PageReference::Overflow {
+                    page_number: 1,
+                    cell_index: 0
+                },
+                PageReference::Child(2),
+            ])
+        );
+        assert_eq!(reachability.shared.len(), 1);
+        assert_eq!(reachability.reach(4), PageReach::Shared(2));
+        assert_eq!(reachability.reach(6), PageReach::Reached);
+        assert_eq!(reachability.reach(8), PageReach::Lost);
+        assert_eq!(reachability.reach(9), PageReach::Reached);
+    }
+}
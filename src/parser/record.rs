@@ -0,0 +1,282 @@
+use crate::models::{CellInfo, DatabaseInfo, Record, RecordValue, TextEncoding};
+use crate::parser::cell::read_varint;
+use crate::parser::sqlite_parser::read_page;
+use crate::parser::wal::open_database;
+use anyhow::Result;
+use std::collections::HashSet;
+use std::io::{Read, Seek};
+
+/// Decode every cell payload on `page_number` into records, reading the page
+/// and any overflow pages straight from the database file in the view the
+/// database was parsed with.
+///
+/// Table interior pages carry no payload and yield no records.
+pub fn read_page_records(database_info: &DatabaseInfo, page_number: u32) -> Result<Vec<Record>> {
+    let page_info = database_info
+        .get_page_info(page_number)
+        .ok_or_else(|| anyhow::anyhow!("Page {} not found", page_number))?;
+    let page_size = database_info.header.actual_page_size();
+    let usable_size = page_size - database_info.header.reserved_space as usize;
+    let encoding = database_info.header.encoding();
+
+    let mut file = open_database(database_info)?;
+    let page = read_page(&mut file, page_number, page_size)?;
+
+    let records = page_info
+        .cells
+        .iter()
+        .filter(|cell| cell.payload_size.is_some())
+        .map(|cell| {
+            let (payload, payload_complete) =
+                read_cell_payload(&mut file, &page, cell, page_size, usable_size);
+            let (values, values_complete) = decode_record(&payload, encoding);
+            Record {
+                cell_index: cell.index,
+                rowid: cell.rowid,
+                values,
+                complete: payload_complete && values_complete,
+            }
+        })
+        .collect();
+
+    Ok(records)
+}
+
+/// Assemble the full payload of a cell: the local part from `page` followed
+/// by the content of its overflow chain.
+///
+/// Returns the bytes that could be read and whether the payload is complete.
+pub fn read_cell_payload<R: Read + Seek>(
+    reader: &mut R,
+    page: &[u8],
+    cell: &CellInfo,
+    page_size: usize,
+    usable_size: usize,
+) -> (Vec<u8>, bool) {
+    let Some(payload_size) = cell.payload_size else {
+        return (Vec::new(), true);
+    };
+
+    let local_start = cell.payload_offset as usize;
+    let local_end = local_start + cell.local_payload_size as usize;
+    let Some(local) = page.get(local_start..local_end) else {
+        return (Vec::new(), false);
+    };
+
+    // The payload size comes from the cell and may be garbage, so the buffer
+    // only grows as far as the overflow chain actually reaches
+    let mut payload = local.to_vec();
+
+    let mut overflow_page = cell.first_overflow_page.unwrap_or(0);
+    let mut visited = HashSet::new();
+
+    while (payload.len() as u64) < payload_size {
+        if overflow_page == 0 || !visited.insert(overflow_page) {
+            return (payload, false);
+        }
+
+        let Ok(content) = read_page(reader, overflow_page, page_size) else {
+            return (payload, false);
+        };
+
+        let remaining = payload_size - payload.len() as u64;
+        let chunk_len = remaining.min((usable_size - 4) as u64) as usize;
+        payload.extend_from_slice(&content[4..4 + chunk_len]);
+        overflow_page = u32::from_be_bytes([content[0], content[1], content[2], content[3]]);
+    }
+
+    (payload, true)
+}
+
+/// Decode a payload in SQLite's record format: a header of serial types
+/// followed by the column values they describe.
+///
+/// Returns the values that could be decoded and whether the whole record was
+/// present. A truncated payload still yields its leading values.
+pub fn decode_record(payload: &[u8], encoding: TextEncoding) -> (Vec<RecordValue>, bool) {
+    let Some((header_size, mut header_offset)) = read_varint(payload) else {
+        return (Vec::new(), false);
+    };
+
+    let header_end = header_size as usize;
+    if header_end > payload.len() || header_end < header_offset {
+        return (Vec::new(), false);
+    }
+
+    let mut serial_types = Vec::new();
+    while header_offset < header_end {
+        let Some((serial_type, len)) = read_varint(&payload[header_offset..header_end]) else {
+            return (Vec::new(), false);
+        };
+        serial_types.push(serial_type);
+        header_offset += len;
+    }
+
+    let mut values = Vec::with_capacity(serial_types.len());
+    let mut body_offset = header_end;
+
+    for serial_type in serial_types {
+        let size = serial_type_size(serial_type);
+        let Some(bytes) = payload.get(body_offset..body_offset + size) else {
+            return (values, false);
+        };
+        values.push(decode_value(serial_type, bytes, encoding));
+        body_offset += size;
+    }
+
+    (values, true)
+}
+
+/// Number of body bytes used by a value of the given serial type
+pub fn serial_type_size(serial_type: u64) -> usize {
+    match serial_type {
+        0 | 8 | 9 | 10 | 11 => 0,
+        1 => 1,
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        5 => 6,
+        6 | 7 => 8,
+        n => ((n - 12) / 2) as usize,
+    }
+}
+
+fn decode_value(serial_type: u64, bytes: &[u8], encoding: TextEncoding) -> RecordValue {
+    match serial_type {
+        0 | 10 | 11 => RecordValue::Null,
+        1..=6 => RecordValue::Integer(read_signed(bytes)),
+        7 => RecordValue::Float(f64::from_bits(read_signed(bytes) as u64)),
+        8 => RecordValue::Integer(0),
+        9 => RecordValue::Integer(1),
+        n if n % 2 == 0 => RecordValue::Blob(bytes.to_vec()),
+        _ => RecordValue::Text(decode_text(bytes, encoding)),
+    }
+}
+
+/// Big-endian two's complement integer of 1 to 8 bytes
+fn read_signed(bytes: &[u8]) -> i64 {
+    let mut value: i64 = if bytes.first().is_some_and(|byte| byte & 0x80 != 0) {
+        -1
+    } else {
+        0
+    };
+    for byte in bytes {
+        value = (value << 8) | *byte as i64;
+    }
+    value
+}
+
+fn decode_text(bytes: &[u8], encoding: TextEncoding) -> String {
+    match encoding {
+        TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        TextEncoding::Utf16le => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        TextEncoding::Utf16be => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_cell_payload_with_corrupt_size() {
+        let mut page = vec![0u8; 512];
+        page[100..104].copy_from_slice(b"abcd");
+        let cell = CellInfo {
+            index: 0,
+            offset: 98,
+            size: 6,
+            left_child: None,
+            rowid: Some(1),
+            payload_size: Some(u64::MAX >> 1),
+            payload_offset: 100,
+            local_payload_size: 4,
+            first_overflow_page: None,
+        };
+
+        let (payload, complete) =
+            read_cell_payload(&mut std::io::Cursor::new(Vec::new()), &page, &cell, 512, 512);
+
+        assert_eq!(payload, b"abcd");
+        assert!(!complete);
+    }
+
+    #[test]
+    fn test_decode_record_mixed_values() {
+        // Header: size 6, types NULL, 1-byte int, 2-byte int, float, text(3)
+        let mut payload = vec![0x06, 0x00, 0x01, 0x02, 0x07, 0x13];
+        payload.push(0xff);
+        payload.extend_from_slice(&300i16.to_be_bytes());
+        payload.extend_from_slice(&1.5f64.to_bits().to_be_bytes());
+        payload.extend_from_slice(b"abc");
+
+        let (values, complete) = decode_record(&payload, TextEncoding::Utf8);
+
+        assert!(complete);
+        assert_eq!(
+            values,
+            vec![
+                RecordValue::Null,
+                RecordValue::Integer(-1),
+                RecordValue::Integer(300),
+                RecordValue::Float(1.5),
+                RecordValue::Text("abc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_record_constants_and_blob() {
+        let payload = vec![0x04, 0x08, 0x09, 0x10, 0xde, 0xad];
+
+        let (values, complete) = decode_record(&payload, TextEncoding::Utf8);
+
+        assert!(complete);
+        assert_eq!(
+            values,
+            vec![
+                RecordValue::Integer(0),
+                RecordValue::Integer(1),
+                RecordValue::Blob(vec![0xde, 0xad]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_record_utf16() {
+        let le = vec![0x02, 0x15, b'h', 0x00, b'i', 0x00];
+        let be = vec![0x02, 0x15, 0x00, b'h', 0x00, b'i'];
+
+        assert_eq!(
+            decode_record(&le, TextEncoding::Utf16le).0,
+            vec![RecordValue::Text("hi".to_string())]
+        );
+        assert_eq!(
+            decode_record(&be, TextEncoding::Utf16be).0,
+            vec![RecordValue::Text("hi".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_decode_record_truncated_payload() {
+        // Second value claims 4 bytes of text but only 2 are present
+        let payload = vec![0x03, 0x01, 0x15, 0x2a, b'a', b'b'];
+
+        let (values, complete) = decode_record(&payload, TextEncoding::Utf8);
+
+        assert!(!complete);
+        assert_eq!(values, vec![RecordValue::Integer(42)]);
+    }
+}
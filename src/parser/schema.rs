@@ -0,0 +1,189 @@
+use crate::models::{
+    BTree, DatabaseHeader, PageInfo, PageOwner, PageType, RecordValue, SchemaObject,
+};
+use crate::parser::btree::walk_btree;
+use crate::parser::record::{decode_record, read_cell_payload};
+use crate::parser::sqlite_parser::read_page;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
+
+/// Root page of the `sqlite_schema` table
+const SCHEMA_ROOT_PAGE: u32 = 1;
+
+/// Decode the rows of `sqlite_schema` by walking its b-tree from page 1.
+///
+/// A leaf that cannot be read is skipped, leaving the schema partial.
+/// Returns the decoded rows and a description of every skipped leaf.
+pub fn read_schema<R: Read + Seek>(
+    reader: &mut R,
+    pages: &BTreeMap<u32, PageInfo>,
+    header: &DatabaseHeader,
+    page_size: usize,
+    usable_size: usize,
+) -> (Vec<SchemaObject>, Vec<String>) {
+    let mut schema = Vec::new();
+    let mut errors = Vec::new();
+
+    for page_number in walk_btree(pages, SCHEMA_ROOT_PAGE) {
+        let Some(page_info) = pages.get(&page_number) else {
+            continue;
+        };
+        if page_info.page_type != PageType::TableBTreeLeaf {
+            continue;
+        }
+
+        let page = match read_page(reader, page_number, page_size) {
+            Ok(page) => page,
+            Err(e) => {
+                errors.push(format!(
+                    "Failed to read sqlite_schema page {}: {}",
+                    page_number, e
+                ));
+                continue;
+            }
+        };
+        for cell in &page_info.cells {
+            let (payload, _) = read_cell_payload(reader, &page, cell, page_size, usable_size);
+            let (values, _) = decode_record(&payload, header.encoding());
+            if let Some(object) = schema_object(&values) {
+                schema.push(object);
+            }
+        }
+    }
+
+    (schema, errors)
+}
+
+/// Root page and owner of every b-tree described by the schema, starting
+/// with `sqlite_schema` itself. Views and triggers have no b-tree.
+pub fn schema_roots(schema: &[SchemaObject]) -> Vec<(u32, PageOwner)> {
+    let schema_owner = PageOwner {
+        object_type: "table".to_string(),
+        name: "sqlite_schema".to_string(),
+    };
+
+    std::iter::once((SCHEMA_ROOT_PAGE, schema_owner))
+        .chain(
+            schema
+                .iter()
+                .filter(|object| object.root_page != 0)
+                .map(|object| {
+                    (
+                        object.root_page,
+                        PageOwner {
+                            object_type: object.object_type.clone(),
+                            name: object.name.clone(),
+                        },
+                    )
+                }),
+        )
+        .collect()
+}
+
+/// Tag every page of each b-tree with the object that owns the tree.
+/// Overflow pages inherit the owner of the b-tree page whose cell spilled
+/// into them.
+pub fn assign_owners(pages: &mut BTreeMap<u32, PageInfo>, btrees: &[BTree]) {
+    for btree in btrees {
+        for node in &btree.nodes {
+            if let Some(page) = pages.get_mut(&node.page_number) {
+                page.owner = Some(btree.owner.clone());
+            }
+        }
+    }
+
+    let overflow_owners: Vec<(u32, PageOwner)> = pages
+        .values()
+        .filter_map(|page| {
+            let owning_page = page.overflow_owner.as_ref()?.page_number;
+            let owner = pages.get(&owning_page)?.owner.clone()?;
+            Some((page.page_number, owner))
+        })
+        .collect();
+
+    for (page_number, owner) in overflow_owners {
+        if let Some(page) = pages.get_mut(&page_number) {
+            page.owner = Some(owner);
+        }
+    }
+}
+
+fn schema_object(values: &[RecordValue]) -> Option<SchemaObject> {
+    let [object_type, name, table_name, root_page, sql, ..] = values else {
+        return None;
+    };
+
+    let text = |value: &RecordValue| match value {
+        RecordValue::Text(text) => Some(text.clone()),
+        _ => None,
+    };
+
+    Some(SchemaObject {
+        object_type: text(object_type)?,
+        name: text(name)?,
+        table_name: text(table_name)?,
+        root_page: match root_page {
+            RecordValue::Integer(page) => u32::try_from(*page).unwrap_or(0),
+            _ => 0,
+        },
+        sql: text(sql),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::sqlite_parser::parse_database_file;
+    use std::io::Cursor;
+    use std::path::Path;
+
+    #[test]
+    fn test_read_schema_skips_unreadable_leaves() {
+        let fixture =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/page_size_65536.db");
+        let info = parse_database_file(&fixture).unwrap();
+        let page_size = info.header.actual_page_size();
+        let usable_size = page_size - info.header.reserved_space as usize;
+        assert!(!info.schema.is_empty());
+
+        // Turn page 1 into an interior page over a copy of its leaf on page 4
+        // and a page 5 that lies past the end of the file
+        let mut data = std::fs::read(&fixture).unwrap();
+        data.truncate(page_size * 3);
+        data.extend_from_within(..page_size);
+        let mut pages = (*info.pages).clone();
+        let leaf = pages[&1].clone();
+        let mut root = leaf.clone();
+        root.page_type = PageType::TableBTreeInterior;
+        root.cells.truncate(1);
+        root.cells[0].left_child = Some(5);
+        root.rightmost_pointer = Some(4);
+        pages.insert(1, root);
+        pages.insert(
+            4,
+            PageInfo {
+                page_number: 4,
+                ..leaf.clone()
+            },
+        );
+        pages.insert(
+            5,
+            PageInfo {
+                page_number: 5,
+                ..leaf
+            },
+        );
+
+        let (schema, errors) = read_schema(
+            &mut Cursor::new(data),
+            &pages,
+            &info.header,
+            page_size,
+            usable_size,
+        );
+
+        assert_eq!(schema, info.schema);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("page 5"));
+    }
+}
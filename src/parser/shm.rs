@@ -0,0 +1,400 @@
+use crate::models::{WalCheckpointInfo, WalIndexHeader, WalIndexInfo, WalInfo};
+use crate::parser::wal::wal_checksum;
+use anyhow::Result;
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Size of one wal-index header copy
+const WAL_INDEX_HEADER_SIZE: usize = 48;
+
+/// Both header copies plus the checkpoint info that follows them
+const WAL_INDEX_PREFIX_SIZE: usize = 136;
+
+/// The wal-index is mapped in 32 KiB blocks, each a page-number array and a hash table
+const HASH_BLOCK_SIZE: usize = 32768;
+
+const HASHTABLE_NPAGE: usize = 4096;
+
+/// The first block loses room for the headers at its start
+const HASHTABLE_NPAGE_ONE: usize = HASHTABLE_NPAGE - WAL_INDEX_PREFIX_SIZE / 4;
+
+const HASHTABLE_NSLOT: usize = 2 * HASHTABLE_NPAGE;
+
+const HASHTABLE_HASH_1: u32 = 383;
+
+/// Path of the shared-memory wal-index SQLite keeps next to `db_path`
+pub fn shm_path(db_path: &Path) -> PathBuf {
+    let mut path = OsString::from(db_path.as_os_str());
+    path.push("-shm");
+    PathBuf::from(path)
+}
+
+/// Decode the `-shm` file next to `db_path` and check it against `wal`.
+///
+/// Returns `None` when there is no wal-index, for instance after the last
+/// connection closed and SQLite removed it.
+pub fn read_wal_index_file(db_path: &Path, wal: Option<&WalInfo>) -> Result<Option<WalIndexInfo>> {
+    let path = shm_path(db_path);
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+
+    let mut index = parse_wal_index(&bytes, path)?;
+    index.issues = check_wal_index(&index, wal);
+    Ok(Some(index))
+}
+
+/// Decode the wal-index headers, checkpoint info and hash tables.
+///
+/// The wal-index lives in shared memory and is written in the host's byte
+/// order, which is assumed to be little-endian. Salts are the exception: they
+/// are copied verbatim from the WAL header and are read big-endian so they
+/// compare equal to `WalHeader::salt1` and `salt2`.
+pub fn parse_wal_index(bytes: &[u8], path: PathBuf) -> Result<WalIndexInfo> {
+    if bytes.len() < WAL_INDEX_PREFIX_SIZE {
+        return Err(anyhow::anyhow!(
+            "wal-index is {} bytes, too short for its {}-byte header",
+            bytes.len(),
+            WAL_INDEX_PREFIX_SIZE
+        ));
+    }
+
+    let headers = [
+        parse_header(&bytes[..WAL_INDEX_HEADER_SIZE]),
+        parse_header(&bytes[WAL_INDEX_HEADER_SIZE..2 * WAL_INDEX_HEADER_SIZE]),
+    ];
+
+    let checkpoint = &bytes[2 * WAL_INDEX_HEADER_SIZE..WAL_INDEX_PREFIX_SIZE];
+    let mut read_marks = [0u32; 5];
+    for (slot, mark) in read_marks.iter_mut().enumerate() {
+        *mark = read_u32_le(checkpoint, 4 + slot * 4);
+    }
+    let checkpoint = WalCheckpointInfo {
+        backfill: read_u32_le(checkpoint, 0),
+        read_marks,
+        backfill_attempted: read_u32_le(checkpoint, 32),
+    };
+
+    let max_frame = headers[0].max_frame;
+    // mxFrame may be garbage; the loop stops at the end of the file instead
+    let mut frame_pages = Vec::new();
+    let mut unhashed_frames = Vec::new();
+
+    for frame in 1..=max_frame {
+        let Some((block, index)) = hash_slot(frame) else {
+            break;
+        };
+        let Some(page_number) = page_entry(bytes, block, index) else {
+            break;
+        };
+        frame_pages.push(page_number);
+
+        if !hash_contains(bytes, block, page_number, index) {
+            unhashed_frames.push(frame);
+        }
+    }
+
+    Ok(WalIndexInfo {
+        path,
+        headers,
+        checkpoint,
+        frame_pages,
+        unhashed_frames,
+        issues: Vec::new(),
+    })
+}
+
+/// Compare the wal-index with the WAL it is supposed to describe
+pub fn check_wal_index(index: &WalIndexInfo, wal: Option<&WalInfo>) -> Vec<String> {
+    let mut issues = Vec::new();
+    let header = index.header();
+
+    if !index.headers_match() {
+        issues.push(
+            "The two wal-index header copies differ; a write was in progress or interrupted"
+                .to_string(),
+        );
+    }
+    if !header.checksum_valid {
+        issues.push("wal-index header checksum does not match its contents".to_string());
+    }
+    if !header.is_init {
+        issues.push("wal-index header is not marked as initialised".to_string());
+    }
+    if index.checkpoint.backfill > header.max_frame {
+        issues.push(format!(
+            "nBackfill {} is past mxFrame {}",
+            index.checkpoint.backfill, header.max_frame
+        ));
+    }
+    if (index.frame_pages.len() as u32) < header.max_frame {
+        issues.push(format!(
+            "wal-index hash tables only cover {} of {} frames",
+            index.frame_pages.len(),
+            header.max_frame
+        ));
+    }
+    if !index.unhashed_frames.is_empty() {
+        issues.push(format!(
+            "{} frames cannot be found through the hash tables (first is frame {})",
+            index.unhashed_frames.len(),
+            index.unhashed_frames[0]
+        ));
+    }
+
+    let Some(wal) = wal else {
+        if header.max_frame > 0 {
+            issues.push(format!(
+                "wal-index reports {} frames but there is no WAL file",
+                header.max_frame
+            ));
+        }
+        return issues;
+    };
+
+    if (header.salt1, header.salt2) != (wal.header.salt1, wal.header.salt2) {
+        issues.push(format!(
+            "wal-index salts {:08x} {:08x} do not match the WAL header salts {:08x} {:08x}",
+            header.salt1, header.salt2, wal.header.salt1, wal.header.salt2
+        ));
+        // The rest of the index describes a different WAL generation
+        return issues;
+    }
+
+    if header.page_size != wal.header.page_size {
+        issues.push(format!(
+            "wal-index page size {} does not match the WAL page size {}",
+            header.page_size, wal.header.page_size
+        ));
+    }
+
+    let valid_frames = wal.valid_frame_count() as u32;
+    if header.max_frame != valid_frames {
+        issues.push(format!(
+            "mxFrame is {} but the WAL holds {} valid committed frames",
+            header.max_frame, valid_frames
+        ));
+    }
+
+    if let Some(last_frame) = header
+        .max_frame
+        .checked_sub(1)
+        .and_then(|index| wal.frames.get(index as usize))
+    {
+        if header.frame_checksum != (last_frame.checksum1, last_frame.checksum2) {
+            issues.push(format!(
+                "Recorded checksum of frame {} does not match the WAL",
+                header.max_frame
+            ));
+        }
+        if last_frame.is_commit() && header.page_count != last_frame.commit_size {
+            issues.push(format!(
+                "nPage is {} but frame {} commits a {}-page database",
+                header.page_count, header.max_frame, last_frame.commit_size
+            ));
+        }
+    }
+
+    let mismatched: Vec<u32> = index
+        .frame_pages
+        .iter()
+        .zip(&wal.frames)
+        .filter(|(page, frame)| **page != frame.page_number)
+        .map(|(_, frame)| frame.frame_number)
+        .collect();
+    if let Some(first) = mismatched.first() {
+        issues.push(format!(
+            "{} hash-table entries name a different page than the WAL frame (first is frame {})",
+            mismatched.len(),
+            first
+        ));
+    }
+
+    issues
+}
+
+fn parse_header(raw: &[u8]) -> WalIndexHeader {
+    let size_field = u16::from_le_bytes([raw[14], raw[15]]) as u32;
+    let checksum = (read_u32_le(raw, 40), read_u32_le(raw, 44));
+
+    WalIndexHeader {
+        version: read_u32_le(raw, 0),
+        change_counter: read_u32_le(raw, 8),
+        is_init: raw[12] != 0,
+        big_endian_checksums: raw[13] != 0,
+        // 65536 does not fit in 16 bits and is stored as 1
+        page_size: (size_field & 0xfe00) + ((size_field & 1) << 16),
+        max_frame: read_u32_le(raw, 16),
+        page_count: read_u32_le(raw, 20),
+        frame_checksum: (read_u32_le(raw, 24), read_u32_le(raw, 28)),
+        salt1: u32::from_be_bytes([raw[32], raw[33], raw[34], raw[35]]),
+        salt2: u32::from_be_bytes([raw[36], raw[37], raw[38], raw[39]]),
+        checksum,
+        checksum_valid: wal_checksum(&raw[..40], false, (0, 0)) == checksum,
+    }
+}
+
+/// Hash block holding `frame` and the frame's 1-based index within that block
+fn hash_slot(frame: u32) -> Option<(usize, usize)> {
+    let frame = frame as usize;
+    if frame == 0 {
+        return None;
+    }
+
+    if frame <= HASHTABLE_NPAGE_ONE {
+        Some((0, frame))
+    } else {
+        let offset = frame - HASHTABLE_NPAGE_ONE - 1;
+        Some((1 + offset / HASHTABLE_NPAGE, offset % HASHTABLE_NPAGE + 1))
+    }
+}
+
+/// Page number stored for the frame at 1-based `index` of `block`
+fn page_entry(bytes: &[u8], block: usize, index: usize) -> Option<u32> {
+    let array_start = block * HASH_BLOCK_SIZE + if block == 0 { WAL_INDEX_PREFIX_SIZE } else { 0 };
+    let offset = array_start + (index - 1) * 4;
+    bytes
+        .get(offset..offset + 4)
+        .map(|raw| u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]))
+}
+
+/// Probe the hash table of `block` for `page_number` the way a reader does,
+/// returning whether it leads to `index`
+fn hash_contains(bytes: &[u8], block: usize, page_number: u32, index: usize) -> bool {
+    let table_start = block * HASH_BLOCK_SIZE + HASHTABLE_NPAGE * 4;
+    let mut slot = (page_number.wrapping_mul(HASHTABLE_HASH_1) as usize) & (HASHTABLE_NSLOT - 1);
+
+    for _ in 0..HASHTABLE_NSLOT {
+        let offset = table_start + slot * 2;
+        let Some(raw) = bytes.get(offset..offset + 2) else {
+            return false;
+        };
+        match u16::from_le_bytes([raw[0], raw[1]]) as usize {
+            0 => return false,
+            entry if entry == index => return true,
+            _ => slot = (slot + 1) & (HASHTABLE_NSLOT - 1),
+        }
+    }
+
+    false
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a single-block wal-index describing frames for `pages`
+    fn build_wal_index(pages: &[u32], salts: (u32, u32)) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&3_007_000u32.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&7u32.to_le_bytes());
+        header.extend_from_slice(&[1, 0]);
+        header.extend_from_slice(&4096u16.to_le_bytes());
+        header.extend_from_slice(&(pages.len() as u32).to_le_bytes());
+        header.extend_from_slice(&10u32.to_le_bytes());
+        header.extend_from_slice(&[0u8; 8]);
+        header.extend_from_slice(&salts.0.to_be_bytes());
+        header.extend_from_slice(&salts.1.to_be_bytes());
+        let checksum = wal_checksum(&header, false, (0, 0));
+        header.extend_from_slice(&checksum.0.to_le_bytes());
+        header.extend_from_slice(&checksum.1.to_le_bytes());
+
+        let mut bytes = vec![0u8; HASH_BLOCK_SIZE];
+        bytes[..48].copy_from_slice(&header);
+        bytes[48..96].copy_from_slice(&header);
+        bytes[96..100].copy_from_slice(&1u32.to_le_bytes());
+        for slot in 0..5 {
+            let mark = if slot == 2 { 1 } else { WalCheckpointInfo::READ_MARK_UNUSED };
+            bytes[100 + slot * 4..104 + slot * 4].copy_from_slice(&mark.to_le_bytes());
+        }
+
+        for (position, &page) in pages.iter().enumerate() {
+            let index = position + 1;
+            let offset = WAL_INDEX_PREFIX_SIZE + position * 4;
+            bytes[offset..offset + 4].copy_from_slice(&page.to_le_bytes());
+
+            let mut slot = (page * HASHTABLE_HASH_1) as usize & (HASHTABLE_NSLOT - 1);
+            loop {
+                let offset = HASHTABLE_NPAGE * 4 + slot * 2;
+                if bytes[offset..offset + 2] == [0, 0] {
+                    bytes[offset..offset + 2].copy_from_slice(&(index as u16).to_le_bytes());
+                    break;
+                }
+                slot = (slot + 1) & (HASHTABLE_NSLOT - 1);
+            }
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn test_parse_wal_index() {
+        let bytes = build_wal_index(&[2, 5, 2], (0xaabb_ccdd, 0x1122_3344));
+
+        let index = parse_wal_index(&bytes, PathBuf::from("test.db-shm")).unwrap();
+        let header = index.header();
+
+        assert!(index.headers_match());
+        assert!(header.checksum_valid);
+        assert!(header.is_init);
+        assert_eq!(header.page_size, 4096);
+        assert_eq!(header.max_frame, 3);
+        assert_eq!(header.salt1, 0xaabb_ccdd);
+        assert_eq!(index.checkpoint.backfill, 1);
+        assert_eq!(index.frame_pages, vec![2, 5, 2]);
+        assert!(index.unhashed_frames.is_empty());
+        assert_eq!(index.pinning_read_marks(), vec![(2, 1)]);
+    }
+
+    #[test]
+    fn test_wal_index_detects_damage() {
+        let mut bytes = build_wal_index(&[2, 5, 2], (1, 2));
+        // Clear the hash table so frame lookups fail, and touch the first header copy
+        bytes[HASHTABLE_NPAGE * 4..].fill(0);
+        bytes[8] ^= 1;
+
+        let index = parse_wal_index(&bytes, PathBuf::from("test.db-shm")).unwrap();
+        let issues = check_wal_index(&index, None);
+
+        assert!(!index.headers_match());
+        assert!(!index.header().checksum_valid);
+        assert_eq!(index.unhashed_frames, vec![1, 2, 3]);
+        assert_eq!(issues.len(), 4);
+    }
+
+    #[test]
+    fn test_wal_index_with_garbage_max_frame() {
+        let mut bytes = build_wal_index(&[2, 5, 2], (1, 2));
+        bytes[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let index = parse_wal_index(&bytes, PathBuf::from("test.db-shm")).unwrap();
+
+        assert_eq!(index.frame_pages[..3], [2, 5, 2]);
+        assert!(index.frame_pages.len() <= bytes.len() / 4);
+    }
+
+    #[test]
+    fn test_hash_slot_blocks() {
+        assert_eq!(hash_slot(1), Some((0, 1)));
+        assert_eq!(hash_slot(4062), Some((0, 4062)));
+        assert_eq!(hash_slot(4063), Some((1, 1)));
+        assert_eq!(hash_slot(4062 + 4096 + 1), Some((2, 1)));
+    }
+}
@@ -1,23 +1,51 @@
-use crate::models::{DatabaseHeader, DatabaseInfo, PageInfo, PageType};
+use crate::models::{DatabaseHeader, DatabaseInfo, DatabaseView, PageInfo, PageType, WalInfo};
+use crate::parser::cell::{parse_cells, parse_freeblocks};
+use crate::parser::freelist::walk_freelist;
+use crate::parser::integrity::{check_child_pointers, check_page_layout};
+use crate::parser::journal::read_journal_file;
+use crate::parser::mapped::MappedDatabase;
+use crate::parser::overflow::follow_overflow_chains;
+use crate::parser::ptrmap::{decode_pointer_map, lock_byte_page, ptrmap_pages};
+use crate::parser::reachability::analyze_reachability;
+use crate::parser::btree::build_btree;
+use crate::parser::schema::{assign_owners, read_schema, schema_roots};
+use crate::parser::shm::read_wal_index_file;
+use crate::parser::wal::{latest_frames, open_view, read_wal_file};
 use anyhow::Result;
 use byteorder::{BigEndian, ReadBytesExt};
-use std::collections::BTreeMap;
-use std::fs::File;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 
 /// Progress callback function type for reporting parsing progress
 pub type ProgressCallback = Box<dyn Fn(f32) + Send + Sync>;
 
+/// Returned when a parse stops because its cancel flag was set
+#[derive(Debug, thiserror::Error)]
+#[error("Parsing cancelled by user")]
+pub struct ParseCancelledError;
+
+/// How the pages are read for the main parse loop
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PageReadStrategy {
+    /// Seek to and read every page through one file handle on one thread
+    Sequential,
+    /// Memory-map the file, unless it is in use, and parse batches of pages on every core
+    #[default]
+    Parallel,
+}
+
 /// Configuration for batch parsing operations
 #[derive(Debug, Clone)]
 pub struct BatchParseConfig {
     pub batch_size: usize,
     pub progress_update_interval: Duration,
     pub enable_cancellation: bool,
+    pub strategy: PageReadStrategy,
 }
 
 impl Default for BatchParseConfig {
@@ -26,6 +54,7 @@ impl Default for BatchParseConfig {
             batch_size: 100,
             progress_update_interval: Duration::from_millis(100),
             enable_cancellation: true,
+            strategy: PageReadStrategy::default(),
         }
     }
 }
@@ -40,9 +69,115 @@ pub fn parse_database_file_with_progress(
     cancel_flag: Option<Arc<AtomicBool>>,
     config: Option<BatchParseConfig>,
 ) -> Result<Arc<DatabaseInfo>> {
-    let mut file = File::open(path)?;
+    parse_database_view(
+        path,
+        DatabaseView::default(),
+        progress_callback,
+        cancel_flag,
+        config,
+    )
+}
+
+/// Parse the database as `view` sees it. Unless `view` is
+/// `DatabaseView::MainFile`, every page with a valid committed frame in the
+/// `-wal` file up to the selected transaction is read from there.
+pub fn parse_database_view(
+    path: &Path,
+    view: DatabaseView,
+    progress_callback: Option<ProgressCallback>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    config: Option<BatchParseConfig>,
+) -> Result<Arc<DatabaseInfo>> {
+    parse_database(path, view, None, progress_callback, cancel_flag, config)
+}
+
+/// Parse the database again after it changed on disk, reusing the b-tree
+/// pages of `previous` whose bytes are unchanged. Returns `previous` itself
+/// when the header and WAL show nothing was written, along with the pages
+/// that were added, removed, rewritten or retyped.
+pub fn reparse_database_view(
+    path: &Path,
+    view: DatabaseView,
+    previous: &Arc<DatabaseInfo>,
+    config: Option<BatchParseConfig>,
+) -> Result<(Arc<DatabaseInfo>, BTreeSet<u32>)> {
+    let database_info = parse_database(path, view, Some(previous), None, None, config)?;
+    let changed_pages = if Arc::ptr_eq(&database_info, previous) {
+        BTreeSet::new()
+    } else {
+        changed_pages(&previous.pages, &database_info.pages)
+    };
+    Ok((database_info, changed_pages))
+}
+
+/// Pages that differ between two parses of the same database
+pub fn changed_pages(
+    previous: &BTreeMap<u32, PageInfo>,
+    current: &BTreeMap<u32, PageInfo>,
+) -> BTreeSet<u32> {
+    let removed = previous
+        .keys()
+        .filter(|page_number| !current.contains_key(page_number));
+    let added_or_changed = current.values().filter_map(|page| {
+        match previous.get(&page.page_number) {
+            Some(before)
+                if before.content_hash == page.content_hash
+                    && before.page_type == page.page_type =>
+            {
+                None
+            }
+            _ => Some(&page.page_number),
+        }
+    });
+    removed.chain(added_or_changed).copied().collect()
+}
+
+/// A write bumps the header's change counter, except in WAL mode where
+/// commits only append frames. A checkpoint changes the main file without
+/// either, so the main file and time-travel views always reparse then.
+fn is_unchanged(
+    previous: &DatabaseInfo,
+    view: DatabaseView,
+    header: &DatabaseHeader,
+    file_size: u64,
+    wal: Option<&WalInfo>,
+) -> bool {
+    let wal_unchanged = match (previous.wal.as_ref(), wal) {
+        (None, None) => true,
+        (Some(before), Some(after)) => {
+            view == DatabaseView::WithWal
+                && before.frames.len() == after.frames.len()
+                && before.header.salt1 == after.header.salt1
+                && before.header.salt2 == after.header.salt2
+        }
+        _ => false,
+    };
+
+    previous.view == view
+        && previous.header.file_change_counter == header.file_change_counter
+        && previous.total_file_size == file_size
+        && wal_unchanged
+}
+
+fn parse_database(
+    path: &Path,
+    view: DatabaseView,
+    previous: Option<&Arc<DatabaseInfo>>,
+    progress_callback: Option<ProgressCallback>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    config: Option<BatchParseConfig>,
+) -> Result<Arc<DatabaseInfo>> {
     let config = config.unwrap_or_default();
 
+    let wal = match read_wal_file(path) {
+        Ok(wal) => wal,
+        Err(e) => {
+            eprintln!("Warning: Failed to read write-ahead log: {}", e);
+            None
+        }
+    };
+    let mut file = open_view(path, view, wal.as_ref())?;
+
     // Parse header first
     let header = parse_header(&mut file)?;
 
@@ -52,51 +187,194 @@ pub fn parse_database_file_with_progress(
     }
 
     let page_size = header.actual_page_size();
+    let usable_size = page_size - header.reserved_space as usize;
 
     // Get file size to determine number of pages
-    let file_metadata = file.metadata()?;
-    let file_size = file_metadata.len();
+    let file_size = file.len();
     let total_pages = (file_size as usize) / page_size;
 
-    let mut pages = BTreeMap::new();
-    let mut last_progress_update = Instant::now();
+    if let Some(previous) = previous
+        && is_unchanged(previous, view, &header, file_size, wal.as_ref())
+    {
+        return Ok(previous.clone());
+    }
 
-    // Parse pages in batches to avoid UI blocking
-    for batch_start in (1..=total_pages).step_by(config.batch_size) {
-        // Check for cancellation
-        if let Some(ref cancel) = cancel_flag {
-            if cancel.load(Ordering::Relaxed) {
-                return Err(anyhow::anyhow!("Parsing cancelled by user"));
-            }
+    // Walk the freelist up front so its pages are not misread as b-tree pages
+    let freelist = walk_freelist(&mut file, &header, page_size, total_pages as u32);
+    for error in &freelist.errors {
+        eprintln!("Warning: {}", error);
+    }
+    if freelist.has_mismatch() {
+        eprintln!(
+            "Warning: Header reports {} freelist pages but the trunk chain holds {}",
+            freelist.expected_total,
+            freelist.total_pages()
+        );
+    }
+    // Pages whose role is known from the header alone are not parsed as b-tree pages
+    let mut known_pages: HashMap<u32, PageType> = freelist.page_types().collect();
+    if header.has_pointer_map() {
+        for map_page in ptrmap_pages(total_pages as u32, page_size, usable_size) {
+            known_pages.insert(map_page, PageType::PointerMap);
         }
+    }
+    let lock_page = lock_byte_page(page_size);
+    if lock_page as usize <= total_pages {
+        known_pages.insert(lock_page, PageType::LockByte);
+    }
 
-        let batch_end = (batch_start + config.batch_size - 1).min(total_pages);
-        
-        // Parse batch of pages
-        for page_num in batch_start..=batch_end {
-            match parse_page(&mut file, page_num as u32, page_size, &header) {
-                Ok(page_info) => {
-                    let _ = pages.insert(page_num as u32, page_info);
-                }
+    let page_loop = PageLoop {
+        header: &header,
+        page_size,
+        usable_size,
+        total_pages,
+        known_pages: &known_pages,
+        previous: previous
+            .filter(|previous| previous.header.actual_page_size() == page_size)
+            .map(|previous| previous.pages.as_ref()),
+        config: &config,
+        progress_callback: progress_callback.as_ref(),
+        cancel_flag: cancel_flag.as_deref(),
+        pages_done: AtomicUsize::new(0),
+        last_progress_update: Mutex::new(Instant::now()),
+    };
+    let mut pages = match config.strategy {
+        PageReadStrategy::Sequential => page_loop.sequential(&mut file)?,
+        PageReadStrategy::Parallel => {
+            match MappedDatabase::open(path, view, wal.as_ref(), page_size) {
+                Ok(database) => page_loop.parallel(&database)?,
                 Err(e) => {
-                    // Log error but continue parsing other pages
-                    eprintln!("Warning: Failed to parse page {}: {}", page_num, e);
+                    eprintln!("Warning: Failed to map database, reading pages sequentially: {}", e);
+                    page_loop.sequential(&mut file)?
                 }
             }
         }
+    };
+
+    // Record the first page pointing at each child, in page order
+    let mut parents = HashMap::new();
+    for (&page_number, page_info) in &pages {
+        for child in page_info.child_pages() {
+            parents.entry(child).or_insert(page_number);
+        }
+    }
 
-        // Update progress if callback provided and enough time has passed
-        if let Some(ref callback) = progress_callback {
-            let now = Instant::now();
-            if now.duration_since(last_progress_update) >= config.progress_update_interval {
-                let progress = batch_end as f32 / total_pages as f32;
-                callback(progress);
-                last_progress_update = now;
+    check_child_pointers(&mut pages, total_pages as u32);
+    let finding_count: usize = pages.values().map(|page| page.findings.len()).sum();
+    if finding_count > 0 {
+        eprintln!(
+            "Warning: Integrity check found {} problems on {} pages",
+            finding_count,
+            pages.values().filter(|page| !page.findings.is_empty()).count()
+        );
+    }
+
+    // Retag the pages reached through overflow chains now that every cell is known
+    let overflow_errors = follow_overflow_chains(
+        &mut file,
+        &mut pages,
+        page_size,
+        usable_size,
+        total_pages as u32,
+    );
+    for error in &overflow_errors {
+        eprintln!("Warning: {}", error);
+    }
+
+    let (schema, schema_errors) = read_schema(&mut file, &pages, &header, page_size, usable_size);
+    for error in &schema_errors {
+        eprintln!("Warning: {}", error);
+    }
+    let btrees: Vec<_> = schema_roots(&schema)
+        .into_iter()
+        .map(|(root_page, owner)| build_btree(&pages, root_page, owner))
+        .collect();
+    assign_owners(&mut pages, &btrees);
+
+    let reachability = analyze_reachability(&pages, &btrees, &freelist);
+    if !reachability.is_clean() {
+        eprintln!(
+            "Warning: {} pages are never referenced and {} are referenced more than once",
+            reachability.lost.len(),
+            reachability.shared.len()
+        );
+    }
+
+    let pointer_map = if header.has_pointer_map() {
+        match decode_pointer_map(
+            &mut file,
+            &mut pages,
+            &parents,
+            page_size,
+            usable_size,
+            total_pages as u32,
+        ) {
+            Ok(pointer_map) => {
+                if pointer_map.mismatch_count > 0 {
+                    eprintln!(
+                        "Warning: {} pointer-map entries disagree with the parsed page layout",
+                        pointer_map.mismatch_count
+                    );
+                }
+                Some(pointer_map)
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to decode pointer map: {}", e);
+                None
             }
         }
+    } else {
+        None
+    };
+
+    if let Some(wal) = wal.as_ref() {
+        if wal.header.page_size as usize != page_size {
+            eprintln!(
+                "Warning: WAL page size {} does not match the database page size {}",
+                wal.header.page_size, page_size
+            );
+        }
 
-        // Yield control to prevent UI blocking (simulate async behavior)
-        std::thread::sleep(Duration::from_millis(1));
+        // Mark pages whose latest committed version lives in the WAL. The main
+        // file view marks every page the WAL would replace.
+        let frame_limit = match view {
+            DatabaseView::MainFile => wal.valid_frame_count(),
+            view => wal.frame_limit(view),
+        };
+        for (page_number, frame) in latest_frames(wal, frame_limit) {
+            if let Some(page) = pages.get_mut(&page_number) {
+                page.wal_frame = Some(frame.frame_number);
+            }
+        }
+    }
+
+    let wal_index = match read_wal_index_file(path, wal.as_ref()) {
+        Ok(wal_index) => wal_index,
+        Err(e) => {
+            eprintln!("Warning: Failed to read wal-index: {}", e);
+            None
+        }
+    };
+    for issue in wal_index.iter().flat_map(|index| &index.issues) {
+        eprintln!("Warning: {}", issue);
+    }
+
+    let journal = match read_journal_file(path) {
+        Ok(journal) => journal,
+        Err(e) => {
+            eprintln!("Warning: Failed to read rollback journal: {}", e);
+            None
+        }
+    };
+    if let Some(journal) = journal.as_ref() {
+        for error in &journal.errors {
+            eprintln!("Warning: {}", error);
+        }
+        for record in &journal.pages {
+            if let Some(page) = pages.get_mut(&record.page_number) {
+                page.journaled = true;
+            }
+        }
     }
 
     // Final progress update
@@ -104,14 +382,188 @@ pub fn parse_database_file_with_progress(
         callback(1.0);
     }
 
-    Ok(Arc::new(DatabaseInfo::new(
-        header,
-        Arc::new(pages),
-        file_size,
-    )))
+    Ok(Arc::new(
+        DatabaseInfo::new(header, Arc::new(pages), file_size)
+            .with_freelist(freelist)
+            .with_pointer_map(pointer_map)
+            .with_schema(schema)
+            .with_btrees(btrees)
+            .with_wal(wal)
+            .with_wal_index(wal_index)
+            .with_journal(journal)
+            .with_reachability(reachability)
+            .with_view(view)
+            .with_source_path(path.to_path_buf()),
+    ))
 }
 
-fn parse_header(file: &mut File) -> Result<DatabaseHeader> {
+/// State shared by the strategies of the main page loop, which parses every
+/// page not already identified by the freelist, pointer map or lock byte
+struct PageLoop<'a> {
+    header: &'a DatabaseHeader,
+    page_size: usize,
+    usable_size: usize,
+    total_pages: usize,
+    known_pages: &'a HashMap<u32, PageType>,
+    /// Pages of the previous parse, reused where their bytes are unchanged
+    previous: Option<&'a BTreeMap<u32, PageInfo>>,
+    config: &'a BatchParseConfig,
+    progress_callback: Option<&'a ProgressCallback>,
+    cancel_flag: Option<&'a AtomicBool>,
+    pages_done: AtomicUsize,
+    last_progress_update: Mutex<Instant>,
+}
+
+impl PageLoop<'_> {
+    fn batch_size(&self) -> usize {
+        self.config.batch_size.max(1)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.config.enable_cancellation
+            && self
+                .cancel_flag
+                .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Parse one page, reading its bytes only when its role is not known yet
+    fn parse_one<P: AsRef<[u8]>>(
+        &self,
+        page_number: u32,
+        read: impl FnOnce() -> Result<P>,
+    ) -> Option<PageInfo> {
+        let page = read();
+        let content_hash = page.as_ref().map_or(0, |page| page_hash(page.as_ref()));
+
+        if let Some(page_type) = self.known_pages.get(&page_number) {
+            return Some(
+                PageInfo::new(page_number, page_type.clone(), 0, 0, 0, None)
+                    .with_content_hash(content_hash),
+            );
+        }
+
+        // A b-tree page whose bytes are unchanged since the previous parse
+        // decodes to the same header and cells
+        if page.is_ok()
+            && let Some(previous) = self.previous.and_then(|pages| pages.get(&page_number))
+            && previous.page_type.is_btree()
+            && previous.content_hash == content_hash
+        {
+            return Some(previous.parsed_only());
+        }
+
+        let parsed = page.and_then(|page| {
+            parse_page(
+                page.as_ref(),
+                page_number,
+                self.page_size,
+                self.usable_size,
+                self.header,
+            )
+            .map(|page_info| page_info.with_content_hash(content_hash))
+        });
+        match parsed {
+            Ok(page_info) => Some(page_info),
+            Err(e) => {
+                // Log error but continue parsing other pages
+                eprintln!("Warning: Failed to parse page {}: {}", page_number, e);
+                None
+            }
+        }
+    }
+
+    /// Count a finished batch and report progress if enough time has passed.
+    /// Workers skip the report while another one is making it.
+    fn finish_batch(&self, page_count: usize) {
+        self.pages_done.fetch_add(page_count, Ordering::Relaxed);
+
+        if let Some(callback) = self.progress_callback
+            && let Ok(mut last_progress_update) = self.last_progress_update.try_lock()
+            && last_progress_update.elapsed() >= self.config.progress_update_interval
+        {
+            let pages_done = self.pages_done.load(Ordering::Relaxed);
+            callback(pages_done as f32 / self.total_pages as f32);
+            *last_progress_update = Instant::now();
+        }
+    }
+
+    fn sequential<R: Read + Seek>(&self, file: &mut R) -> Result<BTreeMap<u32, PageInfo>> {
+        let mut pages = BTreeMap::new();
+
+        for batch_start in (1..=self.total_pages).step_by(self.batch_size()) {
+            if self.is_cancelled() {
+                return Err(ParseCancelledError.into());
+            }
+
+            let batch_end = (batch_start + self.batch_size() - 1).min(self.total_pages);
+            for page_number in batch_start..=batch_end {
+                let page_number = page_number as u32;
+                if let Some(page_info) = self.parse_one(page_number, || {
+                    read_page(file, page_number, self.page_size)
+                }) {
+                    pages.insert(page_number, page_info);
+                }
+            }
+            self.finish_batch(batch_end - batch_start + 1);
+        }
+
+        Ok(pages)
+    }
+
+    /// Workers claim batches from a shared counter until none are left, so
+    /// cores that finish early pick up the remaining work
+    fn parallel(&self, database: &MappedDatabase) -> Result<BTreeMap<u32, PageInfo>> {
+        let batch_count = self.total_pages.div_ceil(self.batch_size());
+        let next_batch = AtomicUsize::new(0);
+        let worker_count = thread::available_parallelism()
+            .map_or(1, |count| count.get())
+            .min(batch_count)
+            .max(1);
+
+        let parsed: Vec<Vec<PageInfo>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..worker_count)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut pages = Vec::new();
+                        while !self.is_cancelled() {
+                            let batch = next_batch.fetch_add(1, Ordering::Relaxed);
+                            if batch >= batch_count {
+                                break;
+                            }
+
+                            let batch_start = batch * self.batch_size() + 1;
+                            let batch_end =
+                                (batch_start + self.batch_size() - 1).min(self.total_pages);
+                            pages.extend((batch_start..=batch_end).filter_map(|page_number| {
+                                let page_number = page_number as u32;
+                                self.parse_one(page_number, || Ok(database.page(page_number)?))
+                            }));
+                            self.finish_batch(batch_end - batch_start + 1);
+                        }
+                        pages
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                .collect()
+        });
+
+        if self.is_cancelled() {
+            return Err(ParseCancelledError.into());
+        }
+
+        Ok(parsed
+            .into_iter()
+            .flatten()
+            .map(|page_info| (page_info.page_number, page_info))
+            .collect())
+    }
+}
+
+pub fn parse_header<R: Read + Seek>(file: &mut R) -> Result<DatabaseHeader> {
     file.seek(SeekFrom::Start(0))?;
 
     // Read SQLite header (first 100 bytes)
@@ -170,41 +622,59 @@ fn parse_header(file: &mut File) -> Result<DatabaseHeader> {
     })
 }
 
-fn parse_page(
-    file: &mut File,
+/// Fast non-cryptographic hash of a page image. Every step is a bijection
+/// of the running state, so a page that differs in a single word always
+/// hashes differently.
+pub fn page_hash(page: &[u8]) -> u64 {
+    const MULTIPLIER: u64 = 0x517c_c1b7_2722_0a95;
+
+    let mix = |hash: u64, word: u64| (hash.rotate_left(5) ^ word).wrapping_mul(MULTIPLIER);
+    let mut words = page.chunks_exact(8);
+    let hash = (&mut words).fold(0, |hash, word| {
+        // chunks_exact only yields 8-byte words, so the default never applies
+        mix(hash, u64::from_le_bytes(word.try_into().unwrap_or_default()))
+    });
+    words
+        .remainder()
+        .iter()
+        .fold(hash, |hash, &byte| mix(hash, byte as u64))
+}
+
+/// Read the raw bytes of a single page
+pub fn read_page<R: Read + Seek>(reader: &mut R, page_number: u32, page_size: usize) -> Result<Vec<u8>> {
+    let page_offset = ((page_number - 1) as u64) * (page_size as u64);
+    reader.seek(SeekFrom::Start(page_offset))?;
+
+    let mut page = vec![0u8; page_size];
+    reader.read_exact(&mut page)?;
+    Ok(page)
+}
+
+/// Decode the header and cells of a page image that was already read
+pub fn parse_page(
+    page: &[u8],
     page_number: u32,
     page_size: usize,
+    usable_size: usize,
     header: &DatabaseHeader,
 ) -> Result<PageInfo> {
-    let page_offset = ((page_number - 1) as u64) * (page_size as u64);
-    file.seek(SeekFrom::Start(page_offset))?;
-
     // Skip database header on page 1
     let header_offset = if page_number == 1 { 100 } else { 0 };
-    if header_offset > 0 {
-        file.seek(SeekFrom::Current(header_offset))?;
-    }
+    let mut reader = &page[header_offset..];
 
     // Read page header
-    let page_type_byte = file.read_u8()?;
+    let page_type_byte = reader.read_u8()?;
 
-    // Determine page type - freelist trunk pages are special
-    let page_type = if page_number == header.first_freelist_trunk_page
-        && header.first_freelist_trunk_page != 0
-    {
-        PageType::FreelistTrunk
-    } else {
-        PageType::from_byte(page_type_byte)
-    };
+    let page_type = PageType::from_byte(page_type_byte);
 
-    let _first_freeblock = file.read_u16::<BigEndian>()?;
-    let cell_count = file.read_u16::<BigEndian>()?;
-    let cell_content_start = file.read_u16::<BigEndian>()?;
-    let fragmented_bytes = file.read_u8()?;
+    let first_freeblock = reader.read_u16::<BigEndian>()?;
+    let cell_count = reader.read_u16::<BigEndian>()?;
+    let cell_content_start = reader.read_u16::<BigEndian>()?;
+    let fragmented_bytes = reader.read_u8()?;
 
     // Read rightmost pointer for interior pages
     let rightmost_pointer = if page_type.has_rightmost_pointer() {
-        Some(file.read_u32::<BigEndian>()?)
+        Some(reader.read_u32::<BigEndian>()?)
     } else {
         None
     };
@@ -215,24 +685,296 @@ fn parse_page(
     } else {
         8usize
     };
-    let total_header_size = page_header_size + header_offset as usize;
+    let total_header_size = page_header_size + header_offset;
     let cell_pointer_array_size = cell_count as usize * 2;
     let used_header_space = total_header_size + cell_pointer_array_size;
 
+    // A zero content start stands for 65536, the end of the largest page
     let content_start = if cell_content_start == 0 {
-        page_size as u16
+        65536
     } else {
-        cell_content_start
+        cell_content_start as usize
     };
 
-    let free_space = content_start.saturating_sub(used_header_space as u16);
+    let free_space = content_start
+        .min(page_size)
+        .saturating_sub(used_header_space) as u32;
+
+    let cells = parse_cells(
+        page,
+        &page_type,
+        total_header_size,
+        cell_count,
+        usable_size,
+        header,
+    );
+    let (freeblocks, freeblock_problem) = if page_type.is_btree() {
+        parse_freeblocks(page, first_freeblock, content_start, usable_size)
+    } else {
+        (Vec::new(), None)
+    };
 
-    Ok(PageInfo::new(
+    let page_info = PageInfo::new(
         page_number,
         page_type,
         cell_count,
         free_space,
         fragmented_bytes,
         rightmost_pointer,
-    ))
+    )
+    .with_cells(cells)
+    .with_freeblocks(freeblocks);
+    let findings = check_page_layout(
+        page,
+        &page_info,
+        cell_content_start,
+        freeblock_problem,
+        usable_size,
+    );
+    Ok(page_info.with_findings(findings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PageChange, PageRegion, PageRegionKind, ReloadChanges};
+    use crate::parser::journal::journal_path;
+    use crate::parser::test_support::database_image;
+    use std::path::PathBuf;
+
+    const PAGE_SIZE: usize = 1024;
+
+    /// Database whose first page holds an empty schema and every other page
+    /// is a table leaf with a few one-column rows
+    fn write_database(name: &str, page_count: usize) -> PathBuf {
+        let data = database_image(PAGE_SIZE, page_count, 0, |page_number| page_number % 7 + 1);
+        let path = std::env::temp_dir().join(format!(
+            "sqlite-browser-{}-{}.db",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    fn parse_with(path: &Path, strategy: PageReadStrategy) -> Arc<DatabaseInfo> {
+        let config = BatchParseConfig {
+            batch_size: 16,
+            strategy,
+            ..BatchParseConfig::default()
+        };
+        parse_database_view(path, DatabaseView::WithWal, None, None, Some(config)).unwrap()
+    }
+
+    #[test]
+    fn test_page_read_strategies_agree() {
+        let path = write_database("strategies", 100);
+
+        let sequential = parse_with(&path, PageReadStrategy::Sequential);
+        let parallel = parse_with(&path, PageReadStrategy::Parallel);
+        // A file with a journal beside it is read without mapping it
+        let journal = journal_path(&path);
+        std::fs::write(&journal, b"").unwrap();
+        let unmapped = parse_with(&path, PageReadStrategy::Parallel);
+        std::fs::remove_file(&journal).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(parallel.pages.len(), 100);
+        assert_eq!(parallel.pages[&9].cell_count, 3);
+        assert_eq!(
+            format!("{:?}", sequential.pages),
+            format!("{:?}", parallel.pages)
+        );
+        assert_eq!(
+            format!("{:?}", sequential.pages),
+            format!("{:?}", unmapped.pages)
+        );
+    }
+
+    #[test]
+    fn test_parallel_parse_cancels() {
+        let path = write_database("cancel", 10);
+        let config = BatchParseConfig {
+            strategy: PageReadStrategy::Parallel,
+            ..BatchParseConfig::default()
+        };
+
+        let result = parse_database_view(
+            &path,
+            DatabaseView::WithWal,
+            None,
+            Some(Arc::new(AtomicBool::new(true))),
+            Some(config),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.unwrap_err().is::<ParseCancelledError>());
+    }
+
+    #[test]
+    fn test_reparse_reports_changed_pages() {
+        let path = write_database("reparse", 20);
+        let previous = parse_with(&path, PageReadStrategy::Parallel);
+
+        let (unchanged, changed_pages) =
+            reparse_database_view(&path, DatabaseView::WithWal, &previous, None).unwrap();
+        assert!(Arc::ptr_eq(&unchanged, &previous));
+        assert!(changed_pages.is_empty());
+
+        // Rewrite the value of the last row on page 5 and bump the change counter
+        let mut data = std::fs::read(&path).unwrap();
+        data[5 * PAGE_SIZE - 1] = 99;
+        data[24..28].copy_from_slice(&1u32.to_be_bytes());
+        std::fs::write(&path, &data).unwrap();
+
+        let (reloaded, changed_pages) =
+            reparse_database_view(&path, DatabaseView::WithWal, &previous, None).unwrap();
+        let fresh = parse_with(&path, PageReadStrategy::Sequential);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(changed_pages, BTreeSet::from([1, 5]));
+        assert_eq!(format!("{:?}", reloaded.pages), format!("{:?}", fresh.pages));
+    }
+
+    #[test]
+    fn test_reload_changes_classify_pages() {
+        let path = write_database("reload-changes", 20);
+        let previous = parse_with(&path, PageReadStrategy::Parallel);
+
+        // Change a row on page 5 and truncate the last two pages away
+        let mut data = std::fs::read(&path).unwrap();
+        data[5 * PAGE_SIZE - 1] = 99;
+        data[24..28].copy_from_slice(&1u32.to_be_bytes());
+        data[28..32].copy_from_slice(&18u32.to_be_bytes());
+        data.truncate(18 * PAGE_SIZE);
+        std::fs::write(&path, &data).unwrap();
+
+        let (reloaded, changed_pages) =
+            reparse_database_view(&path, DatabaseView::WithWal, &previous, None).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let changes = ReloadChanges::new(&previous, &reloaded, &changed_pages);
+
+        assert_eq!(
+            changes.pages,
+            BTreeMap::from([
+                (1, PageChange::Modified),
+                (5, PageChange::Modified),
+                (19, PageChange::Freed),
+                (20, PageChange::Freed),
+            ])
+        );
+        assert_eq!(changes.count(PageChange::Freed), 2);
+        assert_eq!(
+            changes.by_page_type,
+            BTreeMap::from([(PageType::TableBTreeLeaf.name(), 4)])
+        );
+        assert_eq!(
+            changes.by_owner,
+            BTreeMap::from([
+                ("Unowned".to_string(), 3),
+                ("table sqlite_schema".to_string(), 1),
+            ])
+        );
+    }
+
+    /// Written by SQLite with `PRAGMA page_size = 65536`: page 2 holds table
+    /// `t` with every fifth row deleted and page 3 the emptied table `empty`
+    fn page_size_65536_database() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/page_size_65536.db")
+    }
+
+    #[test]
+    fn test_parse_65536_byte_pages() {
+        for strategy in [PageReadStrategy::Sequential, PageReadStrategy::Parallel] {
+            let info = parse_with(&page_size_65536_database(), strategy);
+            let page_size = info.header.actual_page_size();
+
+            assert_eq!(info.header.page_size, 1);
+            assert_eq!(page_size, 65536);
+            assert_eq!(info.pages.len(), 3);
+            assert_eq!(info.schema.len(), 2);
+            assert!(info.reachability.is_clean());
+            assert!(info.pages.values().all(|page| page.findings.is_empty()));
+
+            let rows = &info.pages[&2];
+            assert_eq!(rows.cells.len(), 160);
+            assert_eq!(rows.freeblocks.len(), 39);
+            assert_eq!(rows.total_free_space(), 52150);
+
+            // A content start of 0 stands for 65536 rather than the start of the page
+            let empty = &info.pages[&3];
+            assert_eq!(empty.free_space, 65528);
+            assert_eq!(empty.total_free_space(), 65528);
+            assert!(empty.utilization_percent(page_size) < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_page_layout_covers_page() {
+        let info = parse_with(&page_size_65536_database(), PageReadStrategy::Sequential);
+        let page_size = info.header.actual_page_size();
+        for page in info.pages.values() {
+            let regions = page.layout(page_size, page_size);
+            assert_eq!(regions[0].offset, 0);
+            assert_eq!(regions.last().map(PageRegion::end), Some(page_size as u32));
+            assert!(regions.windows(2).all(|pair| pair[0].end() == pair[1].offset));
+        }
+
+        let rows = &info.pages[&2];
+        let regions = rows.layout(page_size, page_size);
+        let bytes = |kind: PageRegionKind| -> u32 {
+            regions
+                .iter()
+                .filter(|region| region.kind == kind)
+                .map(|region| region.length)
+                .sum()
+        };
+        let cells = regions
+            .iter()
+            .filter(|region| matches!(region.kind, PageRegionKind::Cell(_)))
+            .count();
+        assert_eq!(cells, 160);
+        assert_eq!(bytes(PageRegionKind::Freeblock), rows.freeblock_space());
+        assert_eq!(bytes(PageRegionKind::Unallocated), rows.free_space);
+        assert_eq!(bytes(PageRegionKind::Fragment), rows.fragmented_bytes as u32);
+
+        let header = info.pages[&1].layout(page_size, page_size);
+        assert_eq!(header[0].kind, PageRegionKind::DatabaseHeader);
+        assert_eq!(header[0].length, 100);
+    }
+
+    /// Times both strategies on `SQLITE_BROWSER_BENCH_DB`, or on a generated
+    /// 100 MB database. Sequential reads go through the buffered `open_view`
+    /// reader, so the gap between the two is what extra cores buy, and on a
+    /// single core they take about as long. Run with
+    /// `cargo test --release bench_page_read_strategies -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_page_read_strategies() {
+        let (path, generated) = match std::env::var_os("SQLITE_BROWSER_BENCH_DB") {
+            Some(path) => (PathBuf::from(path), false),
+            None => (write_database("bench", 100_000), true),
+        };
+
+        for strategy in [PageReadStrategy::Sequential, PageReadStrategy::Parallel] {
+            let config = BatchParseConfig {
+                batch_size: 1000,
+                strategy,
+                ..BatchParseConfig::default()
+            };
+            let started = Instant::now();
+            let info = parse_database_view(&path, DatabaseView::WithWal, None, None, Some(config))
+                .unwrap();
+            println!(
+                "{:?}: {} pages in {:?}",
+                strategy,
+                info.pages.len(),
+                started.elapsed()
+            );
+        }
+
+        if generated {
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
 }
@@ -0,0 +1,543 @@
+use crate::models::{DatabaseInfo, DatabaseView, WalFrame, WalHeader, WalInfo, WalTransaction};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Magic number of a WAL whose checksums use little-endian words; big-endian adds 1
+const WAL_MAGIC: u32 = 0x377f_0682;
+
+const WAL_HEADER_SIZE: usize = 32;
+
+const WAL_FRAME_HEADER_SIZE: usize = 24;
+
+/// Path of the write-ahead log SQLite keeps next to `db_path`
+pub fn wal_path(db_path: &Path) -> PathBuf {
+    let mut path = OsString::from(db_path.as_os_str());
+    path.push("-wal");
+    PathBuf::from(path)
+}
+
+/// Parse the write-ahead log next to `db_path`.
+///
+/// Returns `None` when there is no WAL or it is empty, which is the normal
+/// state of a database that was checkpointed and closed cleanly.
+pub fn read_wal_file(db_path: &Path) -> Result<Option<WalInfo>> {
+    let path = wal_path(db_path);
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+
+    parse_wal(&mut BufReader::new(file), path).map(Some)
+}
+
+/// Read the WAL header and every complete frame, verifying salts and the
+/// cumulative checksums, and group the frames into transactions.
+///
+/// A partial frame at the end of the log is ignored, as SQLite does.
+pub fn parse_wal<R: Read>(reader: &mut R, path: PathBuf) -> Result<WalInfo> {
+    let mut raw_header = [0u8; WAL_HEADER_SIZE];
+    reader.read_exact(&mut raw_header)?;
+
+    let header = parse_wal_header(&raw_header)?;
+    let big_endian = header.big_endian_checksums();
+    let page_size = header.page_size as usize;
+    if !(512..=65536).contains(&page_size) || !page_size.is_power_of_two() {
+        return Err(anyhow::anyhow!(
+            "Invalid WAL page size {}",
+            header.page_size
+        ));
+    }
+
+    let header_checksum = wal_checksum(&raw_header[..24], big_endian, (0, 0));
+    let header_checksum_valid = header_checksum == (header.checksum1, header.checksum2);
+
+    let mut frames = Vec::new();
+    let mut checksum = (header.checksum1, header.checksum2);
+    let mut frame = vec![0u8; WAL_FRAME_HEADER_SIZE + page_size];
+
+    loop {
+        match reader.read_exact(&mut frame) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut frame_header = &frame[..WAL_FRAME_HEADER_SIZE];
+        let page_number = frame_header.read_u32::<BigEndian>()?;
+        let commit_size = frame_header.read_u32::<BigEndian>()?;
+        let salt1 = frame_header.read_u32::<BigEndian>()?;
+        let salt2 = frame_header.read_u32::<BigEndian>()?;
+        let checksum1 = frame_header.read_u32::<BigEndian>()?;
+        let checksum2 = frame_header.read_u32::<BigEndian>()?;
+
+        checksum = wal_checksum(&frame[..8], big_endian, checksum);
+        checksum = wal_checksum(&frame[WAL_FRAME_HEADER_SIZE..], big_endian, checksum);
+
+        let frame_number = frames.len() as u32 + 1;
+        frames.push(WalFrame {
+            frame_number,
+            offset: WAL_HEADER_SIZE as u64
+                + (frame_number as u64 - 1) * (WAL_FRAME_HEADER_SIZE + page_size) as u64,
+            page_number,
+            commit_size,
+            salt1,
+            salt2,
+            checksum1,
+            checksum2,
+            salt_valid: salt1 == header.salt1 && salt2 == header.salt2,
+            checksum_valid: checksum == (checksum1, checksum2),
+        });
+    }
+
+    let transactions = group_transactions(&frames);
+
+    Ok(WalInfo {
+        path,
+        header,
+        header_checksum_valid,
+        frames,
+        transactions,
+    })
+}
+
+fn parse_wal_header(mut raw: &[u8]) -> Result<WalHeader> {
+    let header = WalHeader {
+        magic: raw.read_u32::<BigEndian>()?,
+        format_version: raw.read_u32::<BigEndian>()?,
+        page_size: raw.read_u32::<BigEndian>()?,
+        checkpoint_sequence: raw.read_u32::<BigEndian>()?,
+        salt1: raw.read_u32::<BigEndian>()?,
+        salt2: raw.read_u32::<BigEndian>()?,
+        checksum1: raw.read_u32::<BigEndian>()?,
+        checksum2: raw.read_u32::<BigEndian>()?,
+    };
+
+    if header.magic & !1 != WAL_MAGIC {
+        return Err(anyhow::anyhow!(
+            "Not a WAL file (magic {:#010x})",
+            header.magic
+        ));
+    }
+
+    Ok(header)
+}
+
+/// The last frame of each page among the first `frame_limit` frames, which
+/// holds the version of the page a reader at that point would see.
+pub fn latest_frames(wal: &WalInfo, frame_limit: usize) -> HashMap<u32, &WalFrame> {
+    wal.frames
+        .iter()
+        .take(frame_limit)
+        .map(|frame| (frame.page_number, frame))
+        .collect()
+}
+
+/// Pages a reader sees in the WAL after the first `frame_limit` frames, as
+/// the offset of each page image within the log, and the database size in
+/// pages recorded by the last commit among them. Frames past that commit
+/// are ignored, and `None` means no commit was reached.
+pub fn overlay_pages(wal: &WalInfo, frame_limit: usize) -> Option<(HashMap<u32, u64>, u32)> {
+    let frames = &wal.frames[..frame_limit.min(wal.frames.len())];
+    let commit_index = frames.iter().rposition(WalFrame::is_commit)?;
+
+    let page_offsets = latest_frames(wal, commit_index + 1)
+        .into_iter()
+        .map(|(page_number, frame)| (page_number, frame.offset + WAL_FRAME_HEADER_SIZE as u64))
+        .collect();
+    Some((page_offsets, frames[commit_index].commit_size))
+}
+
+/// Reader over the database image as a connection would see it: pages with
+/// a committed WAL frame are read from the log, all others from the main file.
+///
+/// Reads past the end of the main file return zeros, since a database that
+/// was never checkpointed may have pages that only exist in the WAL.
+pub struct WalOverlay<R> {
+    main: R,
+    wal: Option<R>,
+    /// Offset of the page image within the WAL for every overlaid page
+    page_offsets: HashMap<u32, u64>,
+    page_size: u64,
+    len: u64,
+    position: u64,
+    /// Where the main reader was left, so sequential reads need no seek and
+    /// keep whatever the reader buffered
+    main_position: Option<u64>,
+}
+
+impl<R: Read + Seek> WalOverlay<R> {
+    /// Reader over the main file alone, passing reads straight through
+    pub fn main_only(mut main: R) -> io::Result<Self> {
+        let len = main.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            main,
+            wal: None,
+            page_offsets: HashMap::new(),
+            page_size: len.max(1),
+            len,
+            position: 0,
+            main_position: Some(len),
+        })
+    }
+
+    /// Reader applying the first `frame_limit` frames of `wal_info` on top of
+    /// the main file. Frames past the last commit in that range are ignored.
+    pub fn new(mut main: R, wal: R, wal_info: &WalInfo, frame_limit: usize) -> io::Result<Self> {
+        let main_len = main.seek(SeekFrom::End(0))?;
+        let page_size = wal_info.header.page_size as u64;
+
+        let Some((page_offsets, size)) = overlay_pages(wal_info, frame_limit) else {
+            return Self::main_only(main);
+        };
+
+        Ok(Self {
+            main,
+            wal: Some(wal),
+            page_offsets,
+            page_size,
+            len: if size == 0 { main_len } else { size as u64 * page_size },
+            position: 0,
+            main_position: Some(main_len),
+        })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<R: Read + Seek> Read for WalOverlay<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let page_number = (self.position / self.page_size) as u32 + 1;
+        let page_offset = self.position % self.page_size;
+        let mut count = (buf.len() as u64).min(self.len - self.position);
+        if self.wal.is_some() {
+            count = count.min(self.page_size - page_offset);
+        }
+        let count = count as usize;
+        let buf = &mut buf[..count];
+
+        let read = match (self.page_offsets.get(&page_number), self.wal.as_mut()) {
+            (Some(&frame_offset), Some(wal)) => {
+                wal.seek(SeekFrom::Start(frame_offset + page_offset))?;
+                wal.read(buf)?
+            }
+            _ => {
+                if self.main_position != Some(self.position) {
+                    self.main.seek(SeekFrom::Start(self.position))?;
+                }
+                self.main_position = None;
+                let read = match self.main.read(buf)? {
+                    0 => {
+                        buf.fill(0);
+                        return self.advance(count);
+                    }
+                    read => read,
+                };
+                self.main_position = Some(self.position + read as u64);
+                read
+            }
+        };
+
+        self.advance(read)
+    }
+}
+
+impl<R> WalOverlay<R> {
+    fn advance(&mut self, read: usize) -> io::Result<usize> {
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for WalOverlay<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        let Some(position) = position else {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            ));
+        };
+        self.position = position;
+        Ok(position)
+    }
+}
+
+/// Reader over a database file as a `DatabaseView` sees it
+pub type ViewReader = WalOverlay<BufReader<File>>;
+
+/// Open `path` the way `view` sees it, overlaying the valid committed WAL frames it includes
+pub fn open_view(path: &Path, view: DatabaseView, wal: Option<&WalInfo>) -> Result<ViewReader> {
+    let main = BufReader::new(File::open(path)?);
+
+    match wal {
+        Some(wal) if wal.frame_limit(view) > 0 => {
+            let wal_file = BufReader::new(File::open(&wal.path)?);
+            Ok(WalOverlay::new(main, wal_file, wal, wal.frame_limit(view))?)
+        }
+        _ => Ok(WalOverlay::main_only(main)?),
+    }
+}
+
+/// Open the database a `DatabaseInfo` was parsed from, in the same view
+pub fn open_database(database_info: &DatabaseInfo) -> Result<ViewReader> {
+    let path = database_info
+        .source_path
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Database has no source file"))?;
+    open_view(path, database_info.view, database_info.wal.as_ref())
+}
+
+/// Split frames into transactions, each ending at a commit frame. Frames
+/// after the last commit form a final uncommitted transaction.
+fn group_transactions(frames: &[WalFrame]) -> Vec<WalTransaction> {
+    let mut transactions = Vec::new();
+    let mut start = 0;
+
+    for (index, frame) in frames.iter().enumerate() {
+        if frame.is_commit() {
+            transactions.push(WalTransaction {
+                frames: start..index + 1,
+                commit_size: Some(frame.commit_size),
+                valid: frames[start..=index].iter().all(WalFrame::is_valid),
+            });
+            start = index + 1;
+        }
+    }
+
+    if start < frames.len() {
+        transactions.push(WalTransaction {
+            frames: start..frames.len(),
+            commit_size: None,
+            valid: frames[start..].iter().all(WalFrame::is_valid),
+        });
+    }
+
+    // A reader stops at the first bad frame, so later transactions are unusable too
+    if let Some(first_invalid) = transactions.iter().position(|transaction| !transaction.valid) {
+        for transaction in &mut transactions[first_invalid..] {
+            transaction.valid = false;
+        }
+    }
+
+    transactions
+}
+
+/// SQLite's WAL checksum: Fletcher-style sums over pairs of 32-bit words,
+/// continued from `seed`. `data` must be a multiple of 8 bytes long.
+pub fn wal_checksum(data: &[u8], big_endian: bool, seed: (u32, u32)) -> (u32, u32) {
+    let (mut s0, mut s1) = seed;
+
+    for words in data.chunks_exact(8) {
+        let (x0, x1) = if big_endian {
+            (
+                u32::from_be_bytes([words[0], words[1], words[2], words[3]]),
+                u32::from_be_bytes([words[4], words[5], words[6], words[7]]),
+            )
+        } else {
+            (
+                u32::from_le_bytes([words[0], words[1], words[2], words[3]]),
+                u32::from_le_bytes([words[4], words[5], words[6], words[7]]),
+            )
+        };
+        s0 = s0.wrapping_add(x0).wrapping_add(s1);
+        s1 = s1.wrapping_add(x1).wrapping_add(s0);
+    }
+
+    (s0, s1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const PAGE_SIZE: usize = 512;
+    const SALTS: (u32, u32) = (0x1111_1111, 0x2222_2222);
+
+    /// Build a WAL with big-endian checksums holding `frames` of (page, commit size)
+    fn build_wal(frames: &[(u32, u32)]) -> Vec<u8> {
+        let mut wal = Vec::new();
+        wal.extend_from_slice(&(WAL_MAGIC + 1).to_be_bytes());
+        wal.extend_from_slice(&3_007_000u32.to_be_bytes());
+        wal.extend_from_slice(&(PAGE_SIZE as u32).to_be_bytes());
+        wal.extend_from_slice(&0u32.to_be_bytes());
+        wal.extend_from_slice(&SALTS.0.to_be_bytes());
+        wal.extend_from_slice(&SALTS.1.to_be_bytes());
+        let mut checksum = wal_checksum(&wal, true, (0, 0));
+        wal.extend_from_slice(&checksum.0.to_be_bytes());
+        wal.extend_from_slice(&checksum.1.to_be_bytes());
+
+        for &(page_number, commit_size) in frames {
+            let mut frame = Vec::new();
+            frame.extend_from_slice(&page_number.to_be_bytes());
+            frame.extend_from_slice(&commit_size.to_be_bytes());
+            let page = vec![page_number as u8; PAGE_SIZE];
+            checksum = wal_checksum(&frame, true, checksum);
+            checksum = wal_checksum(&page, true, checksum);
+            frame.extend_from_slice(&SALTS.0.to_be_bytes());
+            frame.extend_from_slice(&SALTS.1.to_be_bytes());
+            frame.extend_from_slice(&checksum.0.to_be_bytes());
+            frame.extend_from_slice(&checksum.1.to_be_bytes());
+            frame.extend_from_slice(&page);
+            wal.extend_from_slice(&frame);
+        }
+
+        wal
+    }
+
+    #[test]
+    fn test_parse_wal_groups_transactions() {
+        let wal = build_wal(&[(2, 0), (3, 3), (2, 0), (4, 0)]);
+
+        let info = parse_wal(&mut Cursor::new(wal), PathBuf::from("test.db-wal")).unwrap();
+
+        assert!(info.header_checksum_valid);
+        assert_eq!(info.frames.len(), 4);
+        assert!(info.frames.iter().all(WalFrame::is_valid));
+        assert_eq!(info.frames[2].offset, 32 + 2 * (24 + PAGE_SIZE as u64));
+        assert_eq!(info.transactions.len(), 2);
+        assert_eq!(info.transactions[0].frames, 0..2);
+        assert_eq!(info.transactions[0].commit_size, Some(3));
+        assert_eq!(info.transactions[1].frames, 2..4);
+        assert!(!info.transactions[1].is_committed());
+        assert_eq!(info.valid_frame_count(), 2);
+    }
+
+    #[test]
+    fn test_frame_limit_per_view() {
+        let wal = build_wal(&[(2, 0), (3, 3), (2, 3), (4, 4), (5, 0)]);
+
+        let info = parse_wal(&mut Cursor::new(wal), PathBuf::from("test.db-wal")).unwrap();
+
+        assert_eq!(info.valid_transaction_count(), 3);
+        assert_eq!(info.frame_limit(DatabaseView::MainFile), 0);
+        assert_eq!(info.frame_limit(DatabaseView::WithWal), 4);
+        assert_eq!(info.frame_limit(DatabaseView::AtTransaction(0)), 2);
+        assert_eq!(info.frame_limit(DatabaseView::AtTransaction(1)), 3);
+        // The uncommitted tail is never applied
+        assert_eq!(info.frame_limit(DatabaseView::AtTransaction(3)), 4);
+        assert_eq!(info.transaction_pages(0).into_iter().collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_parse_wal_detects_corrupt_frame() {
+        let mut wal = build_wal(&[(2, 2), (3, 3), (4, 4)]);
+        // Flip a byte in the page image of the second frame
+        wal[32 + (24 + PAGE_SIZE) + 24 + 10] ^= 0xff;
+        // Drop half of a trailing frame, which should be ignored
+        wal.extend_from_slice(&[0u8; 100]);
+
+        let info = parse_wal(&mut Cursor::new(wal), PathBuf::from("test.db-wal")).unwrap();
+
+        assert_eq!(info.frames.len(), 3);
+        assert!(info.frames[0].checksum_valid);
+        assert!(!info.frames[1].checksum_valid);
+        assert!(info.transactions[0].valid);
+        assert!(!info.transactions[1].valid);
+        assert!(!info.transactions[2].valid);
+        assert_eq!(info.valid_frame_count(), 1);
+    }
+
+    #[test]
+    fn test_overlay_serves_latest_committed_frames() {
+        // Page 2 is written twice, page 3 only in an uncommitted frame
+        let wal = build_wal(&[(2, 0), (1, 2), (2, 2), (3, 0)]);
+        let info = parse_wal(&mut Cursor::new(wal.clone()), PathBuf::from("test.db-wal")).unwrap();
+        let main = Cursor::new(vec![0xaau8; PAGE_SIZE]);
+
+        let mut overlay =
+            WalOverlay::new(main, Cursor::new(wal), &info, info.valid_frame_count()).unwrap();
+        let mut image = Vec::new();
+        overlay.read_to_end(&mut image).unwrap();
+
+        assert_eq!(overlay.len(), 2 * PAGE_SIZE as u64);
+        assert_eq!(image.len(), 2 * PAGE_SIZE);
+        assert!(image[..PAGE_SIZE].iter().all(|&byte| byte == 1));
+        assert!(image[PAGE_SIZE..].iter().all(|&byte| byte == 2));
+
+        // Only the first transaction applied
+        let main = Cursor::new(vec![0xaau8; PAGE_SIZE]);
+        let wal = build_wal(&[(2, 0), (1, 2), (2, 2), (3, 0)]);
+        let mut overlay = WalOverlay::new(main, Cursor::new(wal), &info, 2).unwrap();
+        let mut page = vec![0u8; PAGE_SIZE];
+        overlay.seek(SeekFrom::Start(PAGE_SIZE as u64)).unwrap();
+        overlay.read_exact(&mut page).unwrap();
+        assert!(page.iter().all(|&byte| byte == 2));
+        assert_eq!(latest_frames(&info, 2)[&2].frame_number, 1);
+    }
+
+    /// Cursor counting the reads and seeks that reach it
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        reads: usize,
+        seeks: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.seeks += 1;
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_main_only_reads_pass_through() {
+        let main = CountingReader {
+            inner: Cursor::new((0..4 * PAGE_SIZE).map(|i| i as u8).collect()),
+            reads: 0,
+            seeks: 0,
+        };
+        let mut overlay = WalOverlay::main_only(main).unwrap();
+
+        let mut image = vec![0u8; 2 * PAGE_SIZE];
+        overlay.seek(SeekFrom::Start(PAGE_SIZE as u64)).unwrap();
+        overlay.read_exact(&mut image).unwrap();
+        let mut next = [0u8; 4];
+        overlay.read_exact(&mut next).unwrap();
+
+        assert_eq!(image[0], PAGE_SIZE as u8);
+        assert_eq!(next[0], 0);
+        // One seek to open, one to move to page 2, none to continue after it
+        assert_eq!((overlay.main.reads, overlay.main.seeks), (2, 2));
+    }
+
+    #[test]
+    fn test_wal_path() {
+        assert_eq!(
+            wal_path(Path::new("/data/app.db")),
+            PathBuf::from("/data/app.db-wal")
+        );
+    }
+}
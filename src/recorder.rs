@@ -0,0 +1,475 @@
+use crate::models::{DatabaseInfo, RecordedSession, RecordedSnapshot};
+use crate::parser::sqlite_parser::{changed_pages, read_page};
+use crate::parser::wal::open_database;
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// File extension of recorded sessions
+pub const SESSION_EXTENSION: &str = "sqlrec";
+
+/// Every session file starts with these eight bytes, the last being the
+/// format version
+const SESSION_MAGIC: [u8; 8] = *b"SQLBREC\x01";
+
+/// Elapsed milliseconds, page size and page count ahead of the database header
+const SNAPSHOT_PREFIX_SIZE: u64 = 16;
+
+const DATABASE_HEADER_SIZE: usize = 100;
+
+/// Default place to record `db_path` to: next to it, named after the time
+/// the recording started
+pub fn session_path(db_path: &Path) -> PathBuf {
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let stem = db_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("database");
+    db_path.with_file_name(format!("{}-{}.{}", stem, started, SESSION_EXTENSION))
+}
+
+/// Appends every database state the watcher observes to a session file.
+///
+/// After the magic, the file is a sequence of snapshots: the milliseconds
+/// since recording started, page size and page count as big-endian u64, u32
+/// and u32, the 100-byte database header, the number of page images that
+/// follow as a u32, then each image prefixed by its page number. The first
+/// snapshot holds every page and later ones only the pages that changed.
+pub struct SessionRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    started: Instant,
+    snapshot_count: usize,
+    /// State the next snapshot is a delta against
+    last_recorded: Arc<DatabaseInfo>,
+}
+
+impl SessionRecorder {
+    /// Create the session file and record the current state of the database
+    pub fn create(path: PathBuf, database_info: &Arc<DatabaseInfo>) -> Result<Self> {
+        let mut writer = BufWriter::new(File::create(&path)?);
+        writer.write_all(&SESSION_MAGIC)?;
+
+        let mut recorder = Self {
+            path,
+            writer,
+            started: Instant::now(),
+            snapshot_count: 0,
+            last_recorded: database_info.clone(),
+        };
+        let all_pages = database_info.pages.keys().copied().collect();
+        recorder.write_snapshot(database_info, &all_pages)?;
+        Ok(recorder)
+    }
+
+    /// Record a reloaded state, storing only the pages that differ from the
+    /// last recorded one. Returns false when nothing changed.
+    pub fn record(&mut self, database_info: &Arc<DatabaseInfo>) -> Result<bool> {
+        let previous = &self.last_recorded;
+        let pages = if previous.header.actual_page_size() == database_info.header.actual_page_size() {
+            changed_pages(&previous.pages, &database_info.pages)
+        } else {
+            database_info.pages.keys().copied().collect()
+        };
+        if pages.is_empty() {
+            return Ok(false);
+        }
+
+        self.write_snapshot(database_info, &pages)?;
+        self.last_recorded = database_info.clone();
+        Ok(true)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn snapshot_count(&self) -> usize {
+        self.snapshot_count
+    }
+
+    fn write_snapshot(&mut self, database_info: &DatabaseInfo, pages: &BTreeSet<u32>) -> Result<()> {
+        let page_size = database_info.header.actual_page_size();
+        let page_count = database_info.pages.len() as u32;
+        let mut file = open_database(database_info)?;
+        let first_page = read_page(&mut file, 1, page_size)?;
+
+        // Pages truncated away since the last state only show in the page count
+        let stored = pages
+            .iter()
+            .copied()
+            .filter(|&page_number| page_number <= page_count)
+            .collect::<Vec<_>>();
+
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        self.writer.write_u64::<BigEndian>(elapsed)?;
+        self.writer.write_u32::<BigEndian>(page_size as u32)?;
+        self.writer.write_u32::<BigEndian>(page_count)?;
+        self.writer.write_all(&first_page[..DATABASE_HEADER_SIZE])?;
+        self.writer.write_u32::<BigEndian>(stored.len() as u32)?;
+        for page_number in stored {
+            let page = match page_number {
+                1 => first_page.clone(),
+                _ => read_page(&mut file, page_number, page_size)?,
+            };
+            self.writer.write_u32::<BigEndian>(page_number)?;
+            self.writer.write_all(&page)?;
+        }
+
+        // Keep the file readable up to the last state if the app is killed
+        self.writer.flush()?;
+        self.snapshot_count += 1;
+        Ok(())
+    }
+}
+
+/// Index every snapshot of a session file without reading the page images
+pub fn read_session(path: &Path) -> Result<RecordedSession> {
+    let file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if magic != SESSION_MAGIC {
+        return Err(anyhow::anyhow!("Not a recorded session (bad magic)"));
+    }
+
+    let mut snapshots = Vec::new();
+    let mut errors = Vec::new();
+    let mut offset = SESSION_MAGIC.len() as u64;
+    // Every page a database grows to is stored when it appears, so no
+    // snapshot can hold more pages than the session has images for
+    let mut highest_stored = 0;
+
+    while offset < file_len {
+        let snapshot_start = offset;
+        let fixed_size = SNAPSHOT_PREFIX_SIZE + DATABASE_HEADER_SIZE as u64 + 4;
+        if offset + fixed_size > file_len {
+            errors.push(format!(
+                "Session ends inside the header of snapshot {}",
+                snapshots.len()
+            ));
+            break;
+        }
+
+        reader.seek(SeekFrom::Start(offset))?;
+        let elapsed = Duration::from_millis(reader.read_u64::<BigEndian>()?);
+        let page_size = reader.read_u32::<BigEndian>()?;
+        let page_count = reader.read_u32::<BigEndian>()?;
+        let mut header = vec![0u8; DATABASE_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        let stored_count = reader.read_u32::<BigEndian>()? as u64;
+        offset += fixed_size;
+
+        if !(512..=65536).contains(&page_size) || !page_size.is_power_of_two() {
+            errors.push(format!(
+                "Snapshot at byte {} has invalid page size {}",
+                snapshot_start, page_size
+            ));
+            break;
+        }
+
+        let record_size = 4 + page_size as u64;
+        if offset + stored_count * record_size > file_len {
+            errors.push(format!(
+                "Session ends inside snapshot {}",
+                snapshots.len()
+            ));
+            break;
+        }
+
+        let mut pages = Vec::with_capacity(stored_count as usize);
+        for _ in 0..stored_count {
+            reader.seek(SeekFrom::Start(offset))?;
+            let page_number = reader.read_u32::<BigEndian>()?;
+            pages.push((page_number, offset + 4));
+            offset += record_size;
+        }
+
+        if let Some(&(page_number, _)) = pages
+            .iter()
+            .find(|&&(page_number, _)| page_number == 0 || page_number > page_count)
+        {
+            errors.push(format!(
+                "Snapshot {} stores page {} of a {}-page database",
+                snapshots.len(),
+                page_number,
+                page_count
+            ));
+            break;
+        }
+        highest_stored = pages
+            .iter()
+            .map(|&(page_number, _)| page_number)
+            .fold(highest_stored, u32::max);
+        if page_count > highest_stored {
+            errors.push(format!(
+                "Snapshot {} has {} pages but only {} were ever recorded",
+                snapshots.len(),
+                page_count,
+                highest_stored
+            ));
+            break;
+        }
+
+        snapshots.push(RecordedSnapshot {
+            elapsed,
+            page_size,
+            page_count,
+            header,
+            pages,
+        });
+    }
+
+    Ok(RecordedSession {
+        path: path.to_path_buf(),
+        snapshots,
+        errors,
+    })
+}
+
+/// Rebuilds the database as of any recorded snapshot into a scratch file,
+/// which the parser then opens like any other database
+pub struct SessionPlayer {
+    session: RecordedSession,
+    file: File,
+    /// Database image as of `position`
+    image: Vec<u8>,
+    page_size: u32,
+    position: Option<usize>,
+    replay_path: PathBuf,
+}
+
+impl SessionPlayer {
+    pub fn open(path: &Path) -> Result<Self> {
+        let session = read_session(path)?;
+        if session.snapshots.is_empty() {
+            return Err(anyhow::anyhow!("Session holds no recorded states"));
+        }
+
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("session");
+        let replay_path = std::env::temp_dir().join(format!(
+            "sqlite-browser-replay-{}-{}.db",
+            std::process::id(),
+            stem
+        ));
+
+        Ok(Self {
+            file: File::open(path)?,
+            session,
+            image: Vec::new(),
+            page_size: 0,
+            position: None,
+            replay_path,
+        })
+    }
+
+    pub fn session(&self) -> &RecordedSession {
+        &self.session
+    }
+
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// Write the database as of snapshot `position` to the replay file and
+    /// return its path. Stepping forward applies only the newer snapshots,
+    /// stepping back replays from the first one.
+    pub fn seek(&mut self, position: usize) -> Result<&Path> {
+        let position = position.min(self.session.snapshots.len() - 1);
+        let start = match self.position {
+            Some(current) if current <= position => current + 1,
+            _ => {
+                self.image.clear();
+                0
+            }
+        };
+
+        for index in start..=position {
+            self.apply(index)?;
+        }
+        std::fs::write(&self.replay_path, &self.image)?;
+        self.position = Some(position);
+        Ok(&self.replay_path)
+    }
+
+    fn apply(&mut self, index: usize) -> Result<()> {
+        let snapshot = &self.session.snapshots[index];
+        let page_size = snapshot.page_size as usize;
+
+        // A VACUUM can change the page size, and then every page was stored
+        if snapshot.page_size != self.page_size {
+            self.image.clear();
+            self.page_size = snapshot.page_size;
+        }
+        let image_len = (snapshot.page_count as usize)
+            .checked_mul(page_size)
+            .ok_or_else(|| anyhow::anyhow!("Snapshot {} is too large to replay", index))?;
+        self.image.resize(image_len, 0);
+
+        for &(page_number, offset) in &snapshot.pages {
+            let start = (page_number as usize).saturating_sub(1) * page_size;
+            let Some(page) = self.image.get_mut(start..start + page_size) else {
+                continue;
+            };
+            self.file.seek(SeekFrom::Start(offset))?;
+            self.file.read_exact(page)?;
+        }
+
+        if let Some(header) = self.image.get_mut(..DATABASE_HEADER_SIZE) {
+            header.copy_from_slice(&snapshot.header);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SessionPlayer {
+    fn drop(&mut self) {
+        // The replay file only exists once the player has seeked
+        if let Err(e) = std::fs::remove_file(&self.replay_path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            eprintln!(
+                "Warning: Failed to remove replay file {}: {}",
+                self.replay_path.display(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::test_support::database_image;
+    use crate::parser::{DatabaseParser, create_sqlite_parser};
+
+    const PAGE_SIZE: usize = 512;
+
+    /// Database of `page_count` empty table leaves, the first holding the
+    /// schema, with the given change counter
+    fn database_bytes(page_count: usize, change_counter: u32) -> Vec<u8> {
+        database_image(PAGE_SIZE, page_count, change_counter, |_| 0)
+    }
+
+    #[test]
+    fn test_record_and_replay_session() {
+        let temp_dir = std::env::temp_dir();
+        let db_path = temp_dir.join(format!("sqlite-browser-record-{}.db", std::process::id()));
+        let session_file = db_path.with_extension(SESSION_EXTENSION);
+        let parser = create_sqlite_parser();
+
+        let first = database_bytes(4, 1);
+        std::fs::write(&db_path, &first).unwrap();
+        let info = parser.parse_file(&db_path).unwrap();
+        let mut recorder = SessionRecorder::create(session_file.clone(), &info).unwrap();
+
+        // Grow the database by a page and touch page 3
+        let mut second = database_bytes(5, 2);
+        second[3 * PAGE_SIZE - 1] = 0xaa;
+        std::fs::write(&db_path, &second).unwrap();
+        let (info, _) = parser
+            .reparse_file_view(&db_path, info.view, &info)
+            .unwrap();
+        assert!(recorder.record(&info).unwrap());
+
+        // Then truncate it back to two pages
+        let third = database_bytes(2, 3);
+        std::fs::write(&db_path, &third).unwrap();
+        let (info, _) = parser
+            .reparse_file_view(&db_path, info.view, &info)
+            .unwrap();
+        assert!(recorder.record(&info).unwrap());
+        assert_eq!(recorder.snapshot_count(), 3);
+        drop(recorder);
+
+        let mut player = SessionPlayer::open(&session_file).unwrap();
+        let snapshots = &player.session().snapshots;
+        assert_eq!(snapshots[0].pages.len(), 4);
+        assert_eq!(
+            snapshots[1].pages.iter().map(|&(page, _)| page).collect::<Vec<_>>(),
+            vec![1, 3, 5]
+        );
+        assert_eq!(snapshots[2].page_count, 2);
+        assert_eq!(snapshots[2].change_counter(), 3);
+
+        for (position, expected) in [(2, &third), (1, &second), (0, &first), (1, &second)] {
+            let replay_path = player.seek(position).unwrap().to_path_buf();
+            assert_eq!(&std::fs::read(&replay_path).unwrap(), expected);
+        }
+
+        std::fs::remove_file(&db_path).unwrap();
+        std::fs::remove_file(&session_file).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_session_keeps_complete_snapshots() {
+        let temp_dir = std::env::temp_dir();
+        let db_path = temp_dir.join(format!("sqlite-browser-truncated-{}.db", std::process::id()));
+        let session_file = db_path.with_extension(SESSION_EXTENSION);
+
+        std::fs::write(&db_path, database_bytes(3, 1)).unwrap();
+        let info = create_sqlite_parser().parse_file(&db_path).unwrap();
+        let mut recorder = SessionRecorder::create(session_file.clone(), &info).unwrap();
+        assert!(!recorder.record(&info).unwrap());
+
+        std::fs::write(&db_path, database_bytes(3, 2)).unwrap();
+        let info = create_sqlite_parser().parse_file(&db_path).unwrap();
+        assert!(recorder.record(&info).unwrap());
+        drop(recorder);
+
+        // Cut the second snapshot off inside its page image
+        let data = std::fs::read(&session_file).unwrap();
+        std::fs::write(&session_file, &data[..data.len() - 10]).unwrap();
+
+        let session = read_session(&session_file).unwrap();
+        std::fs::remove_file(&db_path).unwrap();
+        std::fs::remove_file(&session_file).unwrap();
+
+        assert_eq!(session.snapshots.len(), 1);
+        assert_eq!(session.errors, vec!["Session ends inside snapshot 1".to_string()]);
+    }
+
+    #[test]
+    fn test_session_with_corrupt_page_numbers() {
+        let temp_dir = std::env::temp_dir();
+        let db_path = temp_dir.join(format!("sqlite-browser-corrupt-{}.db", std::process::id()));
+        let session_file = db_path.with_extension(SESSION_EXTENSION);
+
+        std::fs::write(&db_path, database_bytes(3, 1)).unwrap();
+        let info = create_sqlite_parser().parse_file(&db_path).unwrap();
+        drop(SessionRecorder::create(session_file.clone(), &info).unwrap());
+        let data = std::fs::read(&session_file).unwrap();
+        std::fs::remove_file(&db_path).unwrap();
+
+        // A page count far past the stored pages, then a stored page 0
+        let page_count_offset = SESSION_MAGIC.len() + 12;
+        let first_page_offset = SESSION_MAGIC.len() + SNAPSHOT_PREFIX_SIZE as usize + 104;
+        let mut huge = data.clone();
+        huge[page_count_offset..page_count_offset + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let mut zero = data;
+        zero[first_page_offset..first_page_offset + 4].fill(0);
+
+        for (data, error) in [
+            (huge, "Snapshot 0 has 4294967295 pages but only 3 were ever recorded"),
+            (zero, "Snapshot 0 stores page 0 of a 3-page database"),
+        ] {
+            std::fs::write(&session_file, data).unwrap();
+            let session = read_session(&session_file).unwrap();
+            assert!(session.snapshots.is_empty());
+            assert_eq!(session.errors, vec![error.to_string()]);
+        }
+        std::fs::remove_file(&session_file).unwrap();
+    }
+}
@@ -1,8 +1,14 @@
 use crate::file_manager::{FileManager, FileManagerEvent};
-use crate::models::{DatabaseInfo, PageInfo};
+use crate::models::{DatabaseInfo, DatabaseView, PageInfo, RecordedSession, ReloadChanges};
+use crate::parser::diff::diff_databases;
+use crate::parser::sqlite_parser::changed_pages;
+use crate::parser::{DatabaseParser, ParseCancelledError, create_sqlite_parser};
+use crate::recorder::{SessionPlayer, session_path};
 
 use crate::ui::entities::{
-    FileDialogManager, FileOpenError, FileOpened, PageGrid, PageSelected, PageSidebar,
+    BTreeView, DatabaseViewSelected, DiffView, FileDialogManager, FileOpenError, FileOpened,
+    HexView, IssuesPanel, PageEdited, PageGrid, PageSelected, PageSidebar, SessionOpenRequested,
+    SessionPositionSelected, SessionTimeline, WalPanel, WalTimeline,
 };
 use crate::ui::status_manager::{StatusManager, StatusAction};
 use anyhow::Result;
@@ -11,9 +17,10 @@ use gpui::{
     Task, Window, actions, div, impl_actions, prelude::*, px,
 };
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 actions![sqlite_browser, [OpenFile, RefreshDatabase]];
 
@@ -34,6 +41,28 @@ pub struct SqliteBrowser {
     file_dialog: Entity<FileDialogManager>,
     page_grid: Entity<PageGrid>,
     page_sidebar: Entity<PageSidebar>,
+    btree_view: Entity<BTreeView>,
+    show_btree_panel: bool,
+    wal_panel: Entity<WalPanel>,
+    show_wal_panel: bool,
+    /// Problems found by the integrity check
+    issues_panel: Entity<IssuesPanel>,
+    show_issues_panel: bool,
+    /// Annotated hex dump of the selected page
+    hex_view: Entity<HexView>,
+    show_hex_view: bool,
+    /// Mark pages that are never referenced or referenced more than once
+    show_reachability: bool,
+    /// Page-by-page comparison of the open file with a second one
+    diff_view: Entity<DiffView>,
+    show_diff: bool,
+    wal_timeline: Entity<WalTimeline>,
+    /// Recorded session being replayed instead of a live file
+    session: Option<Arc<RecordedSession>>,
+    session_player: Option<Arc<Mutex<SessionPlayer>>>,
+    /// Latest state asked for, which a slow rebuild skips ahead to
+    session_target: Arc<AtomicUsize>,
+    session_timeline: Entity<SessionTimeline>,
 
     // Subscriptions
     _subscriptions: Vec<Subscription>,
@@ -47,6 +76,13 @@ impl SqliteBrowser {
         let file_dialog = cx.new(|_cx| FileDialogManager::new());
         let page_grid = cx.new(|_cx| PageGrid::new(Arc::new(BTreeMap::new())));
         let page_sidebar = cx.new(|_cx| PageSidebar::new());
+        let btree_view = cx.new(|_cx| BTreeView::new());
+        let wal_panel = cx.new(|_cx| WalPanel::new());
+        let issues_panel = cx.new(|_cx| IssuesPanel::new());
+        let hex_view = cx.new(HexView::new);
+        let wal_timeline = cx.new(|_cx| WalTimeline::new());
+        let diff_view = cx.new(|_cx| DiffView::new());
+        let session_timeline = cx.new(|_cx| SessionTimeline::new());
 
         let mut browser = Self {
             file_manager: FileManager::new(),
@@ -56,6 +92,22 @@ impl SqliteBrowser {
             file_dialog: file_dialog.clone(),
             page_grid: page_grid.clone(),
             page_sidebar: page_sidebar.clone(),
+            btree_view: btree_view.clone(),
+            show_btree_panel: false,
+            wal_panel: wal_panel.clone(),
+            show_wal_panel: false,
+            issues_panel: issues_panel.clone(),
+            show_issues_panel: false,
+            hex_view: hex_view.clone(),
+            show_hex_view: false,
+            show_reachability: false,
+            diff_view,
+            show_diff: false,
+            wal_timeline: wal_timeline.clone(),
+            session: None,
+            session_player: None,
+            session_target: Arc::new(AtomicUsize::new(0)),
+            session_timeline: session_timeline.clone(),
             _subscriptions: Vec::new(),
         };
 
@@ -68,6 +120,7 @@ impl SqliteBrowser {
 
         let file_error_subscription = cx.subscribe(&file_dialog, {
             move |this, _entity, event: &FileOpenError, cx| {
+                this.status_manager.dismiss_progress(cx);
                 this.status_manager.show_error(
                     format!("Failed to open {}: {}", event.path.display(), event.error),
                     cx,
@@ -82,17 +135,95 @@ impl SqliteBrowser {
             }
         });
 
+        // The dialog parses newly opened files with its own file manager, so
+        // its progress is shown the same way as reloads. Its errors arrive as
+        // FileOpenError instead.
+        let file_dialog_progress_subscription = cx.subscribe(&file_dialog, {
+            move |this, _entity, event: &FileManagerEvent, cx| {
+                if !matches!(event, FileManagerEvent::ParseError(..)) {
+                    this.handle_file_manager_event(event, cx);
+                }
+            }
+        });
+
         let page_selected_subscription = cx.subscribe(&page_grid, {
             move |this, _entity, event: &PageSelected, cx| {
                 this.handle_page_selected(event.page_number, cx);
             }
         });
 
+        // Selecting a page in the tree view selects it in the grid, which in
+        // turn notifies the sidebar through the grid's PageSelected event
+        let btree_page_selected_subscription = cx.subscribe(&btree_view, {
+            move |this, _entity, event: &PageSelected, cx| {
+                this.page_grid.update(cx, |grid, cx| {
+                    grid.select_page_programmatically(event.page_number, cx);
+                });
+            }
+        });
+
+        let wal_page_selected_subscription = cx.subscribe(&wal_panel, {
+            move |this, _entity, event: &PageSelected, cx| {
+                this.page_grid.update(cx, |grid, cx| {
+                    grid.select_page_programmatically(event.page_number, cx);
+                });
+            }
+        });
+
+        let issue_page_selected_subscription = cx.subscribe(&issues_panel, {
+            move |this, _entity, event: &PageSelected, cx| {
+                this.page_grid.update(cx, |grid, cx| {
+                    grid.select_page_programmatically(event.page_number, cx);
+                });
+            }
+        });
+
+        // Reload after an edited page was written back so every view shows it
+        let page_edited_subscription = cx.subscribe(&hex_view, {
+            move |this, _entity, event: &PageEdited, cx| {
+                this.status_manager.show_success(
+                    format!(
+                        "Saved page {}; the previous file was backed up to {}",
+                        event.page_number,
+                        event.backup_path.display()
+                    ),
+                    cx,
+                );
+                this.set_database_view(this.file_manager.view(), cx);
+            }
+        });
+
+        let timeline_subscription = cx.subscribe(&wal_timeline, {
+            move |this, _entity, event: &DatabaseViewSelected, cx| {
+                this.set_database_view(event.view, cx);
+            }
+        });
+
+        let session_open_subscription = cx.subscribe(&file_dialog, {
+            move |this, _entity, event: &SessionOpenRequested, cx| {
+                this.open_session(event.path.clone(), cx);
+            }
+        });
+
+        let session_position_subscription = cx.subscribe(&session_timeline, {
+            move |this, _entity, event: &SessionPositionSelected, cx| {
+                this.show_session_state(event.position, cx);
+            }
+        });
+
         browser._subscriptions.extend([
             file_opened_subscription,
             file_error_subscription,
             file_manager_subscription,
+            file_dialog_progress_subscription,
             page_selected_subscription,
+            btree_page_selected_subscription,
+            wal_page_selected_subscription,
+            issue_page_selected_subscription,
+            page_edited_subscription,
+            timeline_subscription,
+            session_open_subscription,
+            session_position_subscription,
         ]);
 
         browser
@@ -105,16 +236,40 @@ impl SqliteBrowser {
 
     pub fn close_current_file(&mut self, cx: &mut Context<Self>) {
         if let Some(path) = self.file_manager.current_file().map(|p| p.to_path_buf()) {
+            self.stop_recording(cx);
             self.file_manager.stop_watching(cx);
             self.file_manager.set_current_file(None);
+            self.file_manager.set_database_info(None);
             self.database_info = None;
 
             // Clear entities
             self.page_grid.update(cx, |grid, cx| {
                 grid.update_pages(Arc::new(BTreeMap::new()), cx);
+                grid.set_reachability_overlay(None, cx);
+                grid.show_recent_changes(BTreeMap::new(), cx);
             });
             self.page_sidebar.update(cx, |sidebar, cx| {
                 sidebar.update_data(None, None, cx);
+                sidebar.set_reload_changes(None, cx);
+            });
+            self.btree_view.update(cx, |view, cx| {
+                view.update_data(None, cx);
+                view.set_selected_page(None, cx);
+            });
+            self.wal_panel.update(cx, |panel, cx| {
+                panel.update_data(None, cx);
+                panel.set_selected_page(None, cx);
+            });
+            self.issues_panel.update(cx, |panel, cx| {
+                panel.update_data(None, cx);
+                panel.set_selected_page(None, cx);
+            });
+            self.hex_view.update(cx, |view, cx| {
+                view.update_data(None, cx);
+                view.set_selected_page(None, cx);
+            });
+            self.wal_timeline.update(cx, |timeline, cx| {
+                timeline.update_data(None, cx);
             });
 
             self.status_manager.clear_all(cx);
@@ -129,16 +284,49 @@ impl SqliteBrowser {
         database_info: Arc<DatabaseInfo>,
         cx: &mut Context<Self>,
     ) {
+        // A recording only ever follows the file it was started on
+        self.stop_recording(cx);
+        self.close_session(cx);
+        self.close_diff(cx);
+
         self.file_manager.set_current_file(Some(path.clone()));
+        self.file_manager.set_view(database_info.view);
+        self.file_manager.set_database_info(Some(database_info.clone()));
         self.database_info = Some(database_info.clone());
 
         // Update entities with new data
         self.page_grid.update(cx, |grid, cx| {
             grid.update_pages(database_info.pages.clone(), cx);
+            grid.set_highlighted_pages(BTreeSet::new(), cx);
+            grid.show_recent_changes(BTreeMap::new(), cx);
         });
+        self.update_reachability_overlay(cx);
 
         self.page_sidebar.update(cx, |sidebar, cx| {
             sidebar.update_data(None, Some(database_info.clone()), cx);
+            sidebar.set_reload_changes(None, cx);
+        });
+
+        self.btree_view.update(cx, |view, cx| {
+            view.update_data(Some(database_info.clone()), cx);
+            view.set_selected_page(None, cx);
+        });
+
+        self.wal_panel.update(cx, |panel, cx| {
+            panel.update_data(Some(database_info.clone()), cx);
+            panel.set_selected_page(None, cx);
+        });
+        self.issues_panel.update(cx, |panel, cx| {
+            panel.update_data(Some(database_info.clone()), cx);
+            panel.set_selected_page(None, cx);
+        });
+        self.hex_view.update(cx, |view, cx| {
+            view.update_data(Some(database_info.clone()), cx);
+            view.set_selected_page(None, cx);
+        });
+
+        self.wal_timeline.update(cx, |timeline, cx| {
+            timeline.update_data(Some(database_info.clone()), cx);
         });
 
         // Start watching the file
@@ -150,6 +338,87 @@ impl SqliteBrowser {
             );
         }
 
+        let finding_count = database_info.finding_count();
+        if finding_count > 0 {
+            self.status_manager.show_warning(
+                format!(
+                    "Integrity check found {} problems on {} pages",
+                    finding_count,
+                    database_info.pages_with_findings().count()
+                ),
+                cx,
+            );
+        }
+
+        let reachability = &database_info.reachability;
+        if !reachability.is_clean() {
+            self.status_manager.show_warning(
+                format!(
+                    "{} pages are never referenced and {} are referenced more than once",
+                    reachability.lost.len(),
+                    reachability.shared.len()
+                ),
+                cx,
+            );
+        }
+
+        if database_info.freelist.has_mismatch() {
+            self.status_manager.show_warning(
+                format!(
+                    "Header reports {} freelist pages but {} were found in the trunk chain",
+                    database_info.freelist.expected_total,
+                    database_info.freelist.total_pages()
+                ),
+                cx,
+            );
+        }
+
+        if let Some(pointer_map) = database_info
+            .pointer_map
+            .as_ref()
+            .filter(|pointer_map| pointer_map.mismatch_count > 0)
+        {
+            self.status_manager.show_warning(
+                format!(
+                    "{} pointer-map entries disagree with the parsed page layout",
+                    pointer_map.mismatch_count
+                ),
+                cx,
+            );
+        }
+
+        if let Some(wal) = database_info.wal.as_ref() {
+            let ignored_frames = wal.frames.len() - wal.valid_frame_count();
+            if ignored_frames > 0 {
+                self.status_manager.show_warning(
+                    format!(
+                        "{} of {} WAL frames are invalid or uncommitted and are ignored by readers",
+                        ignored_frames,
+                        wal.frames.len()
+                    ),
+                    cx,
+                );
+            }
+        }
+
+        if let Some(issue) = database_info
+            .wal_index
+            .as_ref()
+            .and_then(|index| index.issues.first())
+        {
+            self.status_manager.show_warning(format!("wal-index: {}", issue), cx);
+        }
+
+        if let Some(journal) = database_info.journal.as_ref().filter(|journal| journal.is_hot) {
+            self.status_manager.show_warning(
+                format!(
+                    "Hot journal with {} saved pages: the database may be mid-transaction and will be rolled back when next opened by SQLite",
+                    journal.pages.len()
+                ),
+                cx,
+            );
+        }
+
         self.status_manager.show_success(format!("Opened {}", path.display()), cx);
         cx.notify();
     }
@@ -159,9 +428,433 @@ impl SqliteBrowser {
         self.page_sidebar.update(cx, |sidebar, cx| {
             sidebar.set_selected_page(Some(page_number), cx);
         });
+        self.btree_view.update(cx, |view, cx| {
+            view.set_selected_page(Some(page_number), cx);
+        });
+        self.wal_panel.update(cx, |panel, cx| {
+            panel.set_selected_page(Some(page_number), cx);
+        });
+        self.issues_panel.update(cx, |panel, cx| {
+            panel.set_selected_page(Some(page_number), cx);
+        });
+        self.hex_view.update(cx, |view, cx| {
+            view.set_selected_page(Some(page_number), cx);
+        });
+    }
+
+    pub fn toggle_btree_panel(&mut self, cx: &mut Context<Self>) {
+        self.show_btree_panel = !self.show_btree_panel;
+        cx.notify();
+    }
+
+    pub fn toggle_wal_panel(&mut self, cx: &mut Context<Self>) {
+        self.show_wal_panel = !self.show_wal_panel;
+        cx.notify();
+    }
+
+    pub fn toggle_issues_panel(&mut self, cx: &mut Context<Self>) {
+        self.show_issues_panel = !self.show_issues_panel;
+        cx.notify();
+    }
+
+    pub fn toggle_hex_view(&mut self, cx: &mut Context<Self>) {
+        self.show_hex_view = !self.show_hex_view;
+        cx.notify();
+    }
+
+    pub fn toggle_reachability(&mut self, cx: &mut Context<Self>) {
+        self.show_reachability = !self.show_reachability;
+        self.update_reachability_overlay(cx);
+        cx.notify();
+    }
+
+    /// Show the current database's reachability in the grid while the overlay is on
+    fn update_reachability_overlay(&mut self, cx: &mut Context<Self>) {
+        let reachability = self
+            .database_info
+            .as_ref()
+            .filter(|_| self.show_reachability)
+            .map(|info| info.reachability.clone());
+        self.page_grid.update(cx, |grid, cx| {
+            grid.set_reachability_overlay(reachability, cx);
+        });
     }
 
+    /// Switch between the main file alone and the database with the WAL applied
+    pub fn toggle_wal_view(&mut self, cx: &mut Context<Self>) {
+        let view = match self.file_manager.view() {
+            DatabaseView::MainFile => DatabaseView::WithWal,
+            _ => DatabaseView::MainFile,
+        };
+        self.set_database_view(view, cx);
+    }
 
+    /// Reparse the current file as `view` sees it
+    pub fn set_database_view(&mut self, view: DatabaseView, cx: &mut Context<Self>) {
+        self.file_manager.set_view(view);
+
+        // Each parse cancels the one before it, so scrubbing the slider only
+        // finishes the parse for the step it stops on
+        let task = self.file_manager.refresh_current_file(cx);
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+            this.update(cx, |this, cx| match result {
+                // Keep only the latest view if parses still finish out of order
+                Ok(database_info) if database_info.view == this.file_manager.view() => {
+                    this.database_info = Some(database_info.clone());
+                    this.refresh_entities(database_info, cx);
+                    cx.notify();
+                }
+                Ok(_) => {}
+                Err(e) if e.is::<ParseCancelledError>() => {}
+                Err(e) => this
+                    .status_manager
+                    .show_error(format!("Failed to switch database view: {}", e), cx),
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Push reloaded data to the entities, keeping the current selection
+    fn refresh_entities(&mut self, database_info: Arc<DatabaseInfo>, cx: &mut Context<Self>) {
+        self.page_grid.update(cx, |grid, cx| {
+            grid.update_pages(database_info.pages.clone(), cx);
+        });
+        self.update_reachability_overlay(cx);
+        self.page_sidebar.update(cx, |sidebar, cx| {
+            sidebar.update_data(sidebar.selected_page, Some(database_info.clone()), cx);
+        });
+        self.btree_view.update(cx, |view, cx| {
+            view.update_data(Some(database_info.clone()), cx);
+        });
+        self.wal_panel.update(cx, |panel, cx| {
+            panel.update_data(Some(database_info.clone()), cx);
+        });
+        self.issues_panel.update(cx, |panel, cx| {
+            panel.update_data(Some(database_info.clone()), cx);
+        });
+        self.hex_view.update(cx, |view, cx| {
+            view.update_data(Some(database_info.clone()), cx);
+        });
+        self.wal_timeline.update(cx, |timeline, cx| {
+            timeline.update_data(Some(database_info.clone()), cx);
+        });
+
+        // Outline the pages written by the commit the timeline points at
+        let changed_pages = match (database_info.view, database_info.wal.as_ref()) {
+            (DatabaseView::AtTransaction(index), Some(wal)) => wal.transaction_pages(index),
+            _ => BTreeSet::new(),
+        };
+        self.page_grid.update(cx, |grid, cx| {
+            grid.set_highlighted_pages(changed_pages, cx);
+        });
+    }
+
+
+
+    /// Start recording every change to the open file into a session file
+    /// next to it, or finish the recording in progress
+    pub fn toggle_recording(&mut self, cx: &mut Context<Self>) {
+        if self.file_manager.is_recording() {
+            self.stop_recording(cx);
+            return;
+        }
+        let Some(session_file) = self.current_file_path().map(session_path) else {
+            return;
+        };
+
+        match self.file_manager.start_recording(session_file.clone()) {
+            Ok(()) if self.is_file_being_watched() => {
+                self.status_manager.show_info(
+                    format!("Recording changes to {}", session_file.display()),
+                    cx,
+                );
+            }
+            Ok(()) => {
+                self.status_manager.show_warning(
+                    "The file is not being watched, so only its current state was recorded"
+                        .to_string(),
+                    cx,
+                );
+            }
+            Err(e) => {
+                self.status_manager
+                    .show_error(format!("Failed to start recording: {}", e), cx);
+            }
+        }
+        cx.notify();
+    }
+
+    fn stop_recording(&mut self, cx: &mut Context<Self>) {
+        if let Some((session_file, state_count)) = self.file_manager.stop_recording() {
+            self.status_manager.show_success(
+                format!(
+                    "Recorded {} states to {}",
+                    state_count,
+                    session_file.display()
+                ),
+                cx,
+            );
+            cx.notify();
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.file_manager.is_recording()
+    }
+
+    /// Replay a recorded session in place of a live file, starting from its
+    /// first state
+    pub fn open_session(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let session_file = path.clone();
+        let task = cx.background_spawn(async move {
+            let mut player = SessionPlayer::open(&session_file)?;
+            let replay_path = player.seek(0)?.to_path_buf();
+            let database_info =
+                create_sqlite_parser().parse_file_view(&replay_path, DatabaseView::MainFile)?;
+            anyhow::Ok((player, database_info))
+        });
+
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+            this.update(cx, |this, cx| match result {
+                Ok((player, database_info)) => this.handle_session_opened(player, database_info, cx),
+                Err(e) => this.status_manager.show_error(
+                    format!("Failed to open session {}: {}", path.display(), e),
+                    cx,
+                ),
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn handle_session_opened(
+        &mut self,
+        player: SessionPlayer,
+        database_info: Arc<DatabaseInfo>,
+        cx: &mut Context<Self>,
+    ) {
+        // The session replaces whatever file was open, without watching anything
+        self.stop_recording(cx);
+        self.file_manager.stop_watching_silent();
+        self.file_manager.set_current_file(None);
+        self.file_manager.set_database_info(None);
+
+        let session = Arc::new(player.session().clone());
+        self.session = Some(session.clone());
+        self.session_player = Some(Arc::new(Mutex::new(player)));
+        self.session_target.store(0, Ordering::SeqCst);
+        self.session_timeline.update(cx, |timeline, cx| {
+            timeline.update_data(Some(session.clone()), cx);
+        });
+
+        self.database_info = Some(database_info.clone());
+        self.page_grid.update(cx, |grid, cx| {
+            grid.update_pages(database_info.pages.clone(), cx);
+            grid.set_highlighted_pages(BTreeSet::new(), cx);
+            grid.show_recent_changes(BTreeMap::new(), cx);
+        });
+        self.update_reachability_overlay(cx);
+        self.page_sidebar.update(cx, |sidebar, cx| {
+            sidebar.update_data(None, Some(database_info.clone()), cx);
+            sidebar.set_reload_changes(None, cx);
+        });
+        self.btree_view.update(cx, |view, cx| {
+            view.update_data(Some(database_info.clone()), cx);
+            view.set_selected_page(None, cx);
+        });
+        self.wal_panel.update(cx, |panel, cx| {
+            panel.update_data(Some(database_info.clone()), cx);
+            panel.set_selected_page(None, cx);
+        });
+        self.issues_panel.update(cx, |panel, cx| {
+            panel.update_data(Some(database_info.clone()), cx);
+            panel.set_selected_page(None, cx);
+        });
+        self.hex_view.update(cx, |view, cx| {
+            view.update_data(Some(database_info.clone()), cx);
+            view.set_selected_page(None, cx);
+        });
+        self.wal_timeline.update(cx, |timeline, cx| {
+            timeline.update_data(Some(database_info.clone()), cx);
+        });
+
+        for error in &session.errors {
+            self.status_manager
+                .show_warning(format!("Session: {}", error), cx);
+        }
+        self.status_manager.show_success(
+            format!(
+                "Opened session {} with {} recorded states",
+                session.path.display(),
+                session.snapshots.len()
+            ),
+            cx,
+        );
+        cx.notify();
+    }
+
+    fn close_session(&mut self, cx: &mut Context<Self>) {
+        if self.session.take().is_some() {
+            self.session_player = None;
+            self.session_timeline.update(cx, |timeline, cx| {
+                timeline.update_data(None, cx);
+            });
+        }
+    }
+
+    /// Rebuild the recorded database as of `position` and outline what
+    /// changed since the state shown before
+    fn show_session_state(&mut self, position: usize, cx: &mut Context<Self>) {
+        let Some(player) = self.session_player.clone() else {
+            return;
+        };
+        self.session_target.store(position, Ordering::SeqCst);
+
+        // Playback can request states faster than they are rebuilt. Whichever
+        // task gets the player next rebuilds the latest request, so the
+        // replay file always matches the state that ends up shown.
+        let target = self.session_target.clone();
+        let task = cx.background_spawn(async move {
+            // A panic mid-seek leaves the replay image half rebuilt
+            let mut player = player.lock().map_err(|_| {
+                anyhow::anyhow!("Session playback failed earlier; reopen the session")
+            })?;
+            let position = target.load(Ordering::SeqCst);
+            if player.position() == Some(position) {
+                return Ok(None);
+            }
+            let replay_path = player.seek(position)?.to_path_buf();
+            let database_info =
+                create_sqlite_parser().parse_file_view(&replay_path, DatabaseView::MainFile)?;
+            anyhow::Ok(Some((position, database_info)))
+        });
+
+        cx.spawn(async move |this, cx| {
+            let Some((position, database_info)) = task.await? else {
+                return Ok(());
+            };
+            this.update(cx, |this, cx| {
+                if this.session_target.load(Ordering::SeqCst) != position
+                    || this.session.is_none()
+                {
+                    return;
+                }
+
+                let changes = match this.database_info.replace(database_info.clone()) {
+                    Some(previous) => {
+                        let pages = changed_pages(&previous.pages, &database_info.pages);
+                        ReloadChanges::new(&previous, &database_info, &pages)
+                    }
+                    None => ReloadChanges::default(),
+                };
+                this.refresh_entities(database_info, cx);
+                this.page_grid.update(cx, |grid, cx| {
+                    grid.show_recent_changes(changes.pages.clone(), cx);
+                });
+                this.page_sidebar.update(cx, |sidebar, cx| {
+                    sidebar.set_reload_changes(Some(changes), cx);
+                });
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Pick a second database and compare the open one against it
+    pub fn compare_file_dialog(&mut self, cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| {
+            let picked = rfd::FileDialog::new()
+                .add_filter("SQLite Database", &["db", "sqlite", "sqlite3"])
+                .set_title("Compare With")
+                .pick_file();
+            if let Some(path) = picked {
+                this.update(cx, |this, cx| this.compare_with(path, cx))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Parse `path` in the current view and show it page by page against
+    /// the open database, which is treated as the "before" copy
+    pub fn compare_with(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let Some(left) = self.database_info.clone() else {
+            return;
+        };
+        let view = self.file_manager.view();
+        let right_path = path.clone();
+        let task = cx.background_spawn(async move {
+            let right = create_sqlite_parser().parse_file_view(&right_path, view)?;
+            anyhow::Ok(diff_databases(left, right))
+        });
+
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+            this.update(cx, |this, cx| match result {
+                Ok(diff) => {
+                    this.status_manager.show_success(
+                        format!(
+                            "Compared with {}: {} of {} pages differ",
+                            path.display(),
+                            diff.changed_count(),
+                            diff.pages.len()
+                        ),
+                        cx,
+                    );
+                    this.diff_view.update(cx, |view, cx| {
+                        view.update_data(Some(Arc::new(diff)), cx);
+                    });
+                    this.show_diff = true;
+                    cx.notify();
+                }
+                Err(e) => this.status_manager.show_error(
+                    format!("Failed to compare with {}: {}", path.display(), e),
+                    cx,
+                ),
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Open `before`, then compare it with `after`
+    pub fn open_and_compare(&mut self, before: PathBuf, after: PathBuf, cx: &mut Context<Self>) {
+        let open = self.open_file(before, cx);
+        cx.spawn(async move |this, cx| {
+            open.await?;
+            this.update(cx, |this, cx| this.compare_with(after, cx))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    pub fn close_diff(&mut self, cx: &mut Context<Self>) {
+        if self.show_diff {
+            self.show_diff = false;
+            self.diff_view.update(cx, |view, cx| view.update_data(None, cx));
+            cx.notify();
+        }
+    }
+
+    /// Problems the integrity check found in the open database
+    pub fn finding_count(&self) -> usize {
+        self.database_info
+            .as_ref()
+            .map_or(0, |info| info.finding_count())
+    }
+
+    pub fn has_wal(&self) -> bool {
+        self.database_info
+            .as_ref()
+            .is_some_and(|info| info.wal.is_some() || info.wal_index.is_some())
+    }
+
+    /// The timeline is only useful once the WAL holds a commit to step through
+    pub fn has_timeline(&self) -> bool {
+        self.database_info
+            .as_ref()
+            .and_then(|info| info.wal.as_ref())
+            .is_some_and(|wal| wal.valid_transaction_count() > 0)
+    }
 
     pub fn current_file_path(&self) -> Option<&std::path::Path> {
         self.file_manager.current_file()
@@ -199,42 +892,78 @@ impl SqliteBrowser {
                 self.database_info = Some(database_info.clone());
                 self.status_manager.show_success(format!("Opened {}", path.display()), cx);
             }
-            FileManagerEvent::FileModified(path, database_info) => {
-                self.database_info = Some(database_info.clone());
+            FileManagerEvent::FileModified(path, database_info, changed_pages) => {
+                let changes = self
+                    .database_info
+                    .replace(database_info.clone())
+                    .map(|previous| ReloadChanges::new(&previous, database_info, changed_pages))
+                    .unwrap_or_default();
                 
                 // Update last modification time
                 self.file_manager.update_last_modification(std::time::Instant::now());
 
                 // Update entities with new data
+                self.refresh_entities(database_info.clone(), cx);
+
+                // Outline what the reload changed until it fades
                 self.page_grid.update(cx, |grid, cx| {
-                    grid.update_pages(database_info.pages.clone(), cx);
+                    grid.show_recent_changes(changes.pages.clone(), cx);
                 });
                 self.page_sidebar.update(cx, |sidebar, cx| {
-                    sidebar.update_data(sidebar.selected_page, Some(database_info.clone()), cx);
+                    sidebar.set_reload_changes(Some(changes), cx);
                 });
 
                 self.status_manager.show_info(
-                    format!("File {} was modified and reloaded", path.display()),
+                    format!(
+                        "File {} was modified and reloaded ({} pages changed)",
+                        path.display(),
+                        changed_pages.len()
+                    ),
                     cx,
                 );
                 cx.notify();
             }
             FileManagerEvent::FileDeleted(path) => {
+                self.stop_recording(cx);
                 self.database_info = None;
                 self.file_manager.set_current_file(None);
+                self.file_manager.set_database_info(None);
 
                 // Clear entities
                 self.page_grid.update(cx, |grid, cx| {
                     grid.update_pages(Arc::new(BTreeMap::new()), cx);
+                    grid.set_reachability_overlay(None, cx);
+                    grid.show_recent_changes(BTreeMap::new(), cx);
                 });
                 self.page_sidebar.update(cx, |sidebar, cx| {
                     sidebar.update_data(None, None, cx);
+                    sidebar.set_reload_changes(None, cx);
+                });
+                self.btree_view.update(cx, |view, cx| {
+                    view.update_data(None, cx);
+                    view.set_selected_page(None, cx);
+                });
+                self.wal_panel.update(cx, |panel, cx| {
+                    panel.update_data(None, cx);
+                    panel.set_selected_page(None, cx);
+                });
+                self.issues_panel.update(cx, |panel, cx| {
+                    panel.update_data(None, cx);
+                    panel.set_selected_page(None, cx);
+                });
+                self.hex_view.update(cx, |view, cx| {
+                    view.update_data(None, cx);
+                    view.set_selected_page(None, cx);
+                });
+                self.wal_timeline.update(cx, |timeline, cx| {
+                    timeline.update_data(None, cx);
                 });
 
                 self.status_manager.show_error(format!("File {} was deleted", path.display()), cx);
                 cx.notify();
             }
             FileManagerEvent::ParseError(path, error) => {
+                self.status_manager.dismiss_progress(cx);
                 self.status_manager.show_error(
                     format!("Error parsing {}: {}", path.display(), error),
                     cx,
@@ -274,6 +1003,7 @@ impl SqliteBrowser {
                 self.status_manager.dismiss_message(cx);
             }
             FileManagerEvent::ParseCancelled(path) => {
+                self.status_manager.dismiss_progress(cx);
                 self.status_manager.show_info(
                     format!("Cancelled loading {}", path.file_name().and_then(|n| n.to_str()).unwrap_or("file")),
                     cx,
@@ -295,11 +1025,33 @@ impl Render for SqliteBrowser {
                 .when_some(self.status_manager.render(), |this, status_element| {
                     this.child(self.render_status_with_handlers(status_element, cx))
                 })
+                .when(self.has_timeline(), |this| {
+                    this.child(self.wal_timeline.clone())
+                })
+                .when(self.session.is_some(), |this| {
+                    this.child(self.session_timeline.clone())
+                })
                 .child(if self.database_info.is_some() {
                     div()
                         .flex()
                         .flex_1()
-                        .child(div().flex_1().child(self.page_grid.clone()))
+                        .when(self.show_btree_panel, |this| {
+                            this.child(self.btree_view.clone())
+                        })
+                        .child(if self.show_diff {
+                            div().flex().flex_1().child(self.diff_view.clone())
+                        } else {
+                            div().flex_1().child(self.page_grid.clone())
+                        })
+                        .when(self.show_wal_panel, |this| {
+                            this.child(self.wal_panel.clone())
+                        })
+                        .when(self.show_issues_panel, |this| {
+                            this.child(self.issues_panel.clone())
+                        })
+                        .when(self.show_hex_view, |this| {
+                            this.child(self.hex_view.clone())
+                        })
                         .child(self.page_sidebar.clone())
                         .into_any_element()
                 } else {
@@ -320,9 +1072,16 @@ impl SqliteBrowser {
             .on_mouse_down(
                 gpui::MouseButton::Left,
                 cx.listener(|this, _event, _window, cx| {
-                    // For now, just dismiss the message when clicked anywhere
-                    // In a real implementation, we'd need to check which element was clicked
-                    this.status_manager.dismiss_message(cx);
+                    // A progress message only offers Cancel, anything else is
+                    // dismissed when clicked
+                    let action = this
+                        .status_manager
+                        .current_message()
+                        .and_then(|message| message.action.clone());
+                    match action {
+                        Some(StatusAction::Cancel) => this.handle_status_action(StatusAction::Cancel, cx),
+                        _ => this.status_manager.dismiss_message(cx),
+                    }
                 }),
             )
     }
@@ -352,14 +1111,18 @@ impl SqliteBrowser {
     }
 
     pub fn cancel_current_operation(&mut self, cx: &mut Context<Self>) {
-        if self.file_manager.is_parsing() {
+        let dialog_loading = self.file_dialog.read(cx).is_loading();
+        if self.file_manager.is_parsing() || dialog_loading {
             self.file_manager.cancel_current_parse();
+            self.file_dialog
+                .update(cx, |dialog, _cx| dialog.cancel_loading());
+            self.status_manager.dismiss_progress(cx);
             self.status_manager.show_info("Cancelling operation...".to_string(), cx);
         }
     }
 
-    pub fn is_operation_in_progress(&self) -> bool {
-        self.file_manager.is_parsing()
+    pub fn is_operation_in_progress(&self, cx: &gpui::App) -> bool {
+        self.file_manager.is_parsing() || self.file_dialog.read(cx).is_loading()
     }
 
     fn render_header_with_handlers(&self, cx: &mut Context<Self>) -> impl IntoElement {
@@ -395,6 +1158,21 @@ impl SqliteBrowser {
                                         .unwrap_or("Unknown")
                                 )),
                         )
+                    })
+                    .when_some(self.session.as_ref(), |this, session| {
+                        this.child(
+                            div()
+                                .text_sm()
+                                .text_color(gpui::rgb(0xcccccc))
+                                .child(format!(
+                                    "- {} (recorded session)",
+                                    session
+                                        .path
+                                        .file_name()
+                                        .and_then(|n| n.to_str())
+                                        .unwrap_or("Unknown")
+                                )),
+                        )
                     }),
             )
             .child(
@@ -424,6 +1202,249 @@ impl SqliteBrowser {
                                     .child("Open File"),
                             ),
                     )
+                    .when(self.database_info.is_some(), |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(if self.show_btree_panel {
+                                    gpui::rgb(0x4b5563)
+                                } else {
+                                    gpui::rgb(0x374151)
+                                })
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        this.toggle_btree_panel(cx);
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .child("B-Trees"),
+                                ),
+                        )
+                    })
+                    .when(self.database_info.is_some(), |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(if self.show_hex_view {
+                                    gpui::rgb(0x4b5563)
+                                } else {
+                                    gpui::rgb(0x374151)
+                                })
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        this.toggle_hex_view(cx);
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .child("Hex"),
+                                ),
+                        )
+                    })
+                    .when(self.finding_count() > 0, |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(if self.show_issues_panel {
+                                    gpui::rgb(0x4b5563)
+                                } else {
+                                    gpui::rgb(0x374151)
+                                })
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        this.toggle_issues_panel(cx);
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xf59e0b))
+                                        .child(format!("⚠ Issues ({})", self.finding_count())),
+                                ),
+                        )
+                    })
+                    .when_some(self.database_info.as_ref(), |this, info| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(if self.show_reachability {
+                                    gpui::rgb(0x4b5563)
+                                } else {
+                                    gpui::rgb(0x374151)
+                                })
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        this.toggle_reachability(cx);
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .child(format!(
+                                            "Reachability ({} lost, {} shared)",
+                                            info.reachability.lost.len(),
+                                            info.reachability.shared.len()
+                                        )),
+                                ),
+                        )
+                    })
+                    .when(self.has_wal(), |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(if self.show_wal_panel {
+                                    gpui::rgb(0x4b5563)
+                                } else {
+                                    gpui::rgb(0x374151)
+                                })
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        this.toggle_wal_panel(cx);
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .child(format!(
+                                            "WAL ({} frames)",
+                                            self.database_info
+                                                .as_ref()
+                                                .and_then(|info| info.wal.as_ref())
+                                                .map_or(0, |wal| wal.frames.len())
+                                        )),
+                                ),
+                        )
+                        .child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(gpui::rgb(0x374151))
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        this.toggle_wal_view(cx);
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .child(self.file_manager.view().label()),
+                                ),
+                        )
+                    })
+                    .when(self.database_info.is_some() && self.session.is_none(), |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(if self.show_diff {
+                                    gpui::rgb(0x4b5563)
+                                } else {
+                                    gpui::rgb(0x374151)
+                                })
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        if this.show_diff {
+                                            this.close_diff(cx);
+                                        } else {
+                                            this.compare_file_dialog(cx);
+                                        }
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .child(if self.show_diff {
+                                            "Close Compare"
+                                        } else {
+                                            "Compare..."
+                                        }),
+                                ),
+                        )
+                    })
+                    .when(self.current_file_path().is_some(), |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .bg(if self.is_recording() {
+                                    gpui::rgb(0xb91c1c)
+                                } else {
+                                    gpui::rgb(0x374151)
+                                })
+                                .hover(|this| this.bg(gpui::rgb(0x4b5563)))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    gpui::MouseButton::Left,
+                                    cx.listener(|this, _event, _window, cx| {
+                                        this.toggle_recording(cx);
+                                    }),
+                                )
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .font_weight(gpui::FontWeight::MEDIUM)
+                                        .text_color(gpui::rgb(0xffffff))
+                                        .child(if self.is_recording() {
+                                            format!(
+                                                "● Stop Recording ({})",
+                                                self.file_manager.recorded_count()
+                                            )
+                                        } else {
+                                            "● Record".to_string()
+                                        }),
+                                ),
+                        )
+                    })
                     .child(
                         div()
                             .text_sm()
@@ -1,5 +1,32 @@
-use crate::models::{DatabaseHeader, DatabaseInfo, PageInfo};
-use gpui::{InteractiveElement, IntoElement, ParentElement, div, prelude::*, px, rgb};
+use crate::models::{
+    CellInfo, DatabaseHeader, DatabaseInfo, IntegrityFinding, PageChange, PageFieldDiff, PageInfo,
+    PageRegionKind, PageType, Reachability, Record, ReloadChanges,
+};
+use crate::parser::journal::JournalBeforeImage;
+use gpui::{
+    Context, InteractiveElement, IntoElement, ParentElement, Render, SharedString, Window, div,
+    prelude::*, px, relative, rgb,
+};
+
+/// Hover tooltip showing a line of text, such as a page summary
+pub struct TextTooltip {
+    pub text: SharedString,
+}
+
+impl Render for TextTooltip {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .px_2()
+            .py_1()
+            .bg(rgb(0x2d2d2d))
+            .border_1()
+            .border_color(rgb(0x555555))
+            .rounded_md()
+            .text_xs()
+            .text_color(rgb(0xffffff))
+            .child(self.text.clone())
+    }
+}
 
 /// Validates page data for consistency and safety
 fn validate_page_data(page: &PageInfo, page_size: Option<usize>) -> Result<(), String> {
@@ -8,17 +35,15 @@ fn validate_page_data(page: &PageInfo, page_size: Option<usize>) -> Result<(), S
             return Err("Page size cannot be zero".to_string());
         }
         
-        let size_u16 = size as u16;
-        
-        if page.free_space > size_u16 {
+        if page.free_space > size as u32 {
             return Err(format!(
                 "Free space ({} bytes) exceeds page size ({} bytes)", 
                 page.free_space, size
             ));
         }
         
-        let total_overhead = page.free_space.saturating_add(page.fragmented_bytes as u16);
-        if total_overhead > size_u16 {
+        let total_overhead = page.total_free_space();
+        if total_overhead > size as u32 {
             return Err(format!(
                 "Total overhead ({} bytes) exceeds page size ({} bytes)", 
                 total_overhead, size
@@ -132,9 +157,16 @@ fn render_page_details_error(page: &PageInfo, error_message: &str) -> impl IntoE
                     div()
                         .flex()
                         .justify_between()
-                        .child(div().text_xs().text_color(rgb(0xcccccc)).child("Free Space:"))
+                        .child(div().text_xs().text_color(rgb(0xcccccc)).child("Unallocated:"))
                         .child(div().text_xs().text_color(rgb(0xcccccc)).child(format!("{} bytes", page.free_space))),
                 )
+                .child(
+                    div()
+                        .flex()
+                        .justify_between()
+                        .child(div().text_xs().text_color(rgb(0xcccccc)).child("Freeblocks:"))
+                        .child(div().text_xs().text_color(rgb(0xcccccc)).child(format!("{} bytes", page.freeblock_space()))),
+                )
                 .child(
                     div()
                         .flex()
@@ -320,38 +352,24 @@ pub fn render_page_details(page: &PageInfo, page_size: Option<usize>) -> impl In
         return render_page_details_error(page, &validation_error).into_any_element();
     }
     
+    let total_free = page.total_free_space();
+
     // Safe calculation to prevent integer underflow
     let used_space = page_size
-        .map(|size| {
-            let size_u16 = size as u16;
-            if page.free_space <= size_u16 {
-                size_u16 - page.free_space
-            } else {
-                // Handle invalid data gracefully - free space cannot exceed page size
-                0
-            }
-        })
+        .map(|size| (size as u32).saturating_sub(total_free))
         .unwrap_or(0);
-    
-    let total_fragmented = page.fragmented_bytes as u16;
-    
-    // Safe efficiency calculation with bounds checking
+
+    // Share of the allocated part of the page, everything but the unallocated
+    // gap, still holding live data rather than freeblocks and fragments
     let efficiency = page_size
         .map(|size| {
-            if size == 0 {
+            let allocated = (size as u32).saturating_sub(page.free_space);
+            if allocated == 0 {
                 return 0.0;
             }
-            
-            let size_u16 = size as u16;
-            let total_overhead = page.free_space.saturating_add(total_fragmented);
-            
-            if total_overhead >= size_u16 {
-                // Invalid data - overhead cannot exceed page size
-                0.0
-            } else {
-                let usable_space = size_u16 - total_overhead;
-                (usable_space as f32 / size as f32) * 100.0
-            }
+
+            let wasted = page.freeblock_space() + page.fragmented_bytes as u32;
+            (allocated.saturating_sub(wasted) as f32 / allocated as f32) * 100.0
         })
         .unwrap_or(0.0);
 
@@ -402,6 +420,19 @@ pub fn render_page_details(page: &PageInfo, page_size: Option<usize>) -> impl In
                         ),
                 ),
         )
+        .when_some(page.owner.as_ref(), |this, owner| {
+            this.child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(
+                        div()
+                            .font_weight(gpui::FontWeight::BOLD)
+                            .child("Owner:"),
+                    )
+                    .child(div().child(owner.label())),
+            )
+        })
         .child(
             div()
                 .flex()
@@ -451,10 +482,10 @@ pub fn render_page_details(page: &PageInfo, page_size: Option<usize>) -> impl In
                         .flex()
                         .items_center()
                         .gap_2()
-                        .child(div().child(format!("{} bytes", page.free_space)))
+                        .child(div().child(format!("{} bytes", total_free)))
                         .when_some(page_size, |this, size| {
                             let percentage = if size > 0 {
-                                (page.free_space as f32 / size as f32) * 100.0
+                                (total_free as f32 / size as f32) * 100.0
                             } else {
                                 0.0
                             };
@@ -473,7 +504,36 @@ pub fn render_page_details(page: &PageInfo, page_size: Option<usize>) -> impl In
                 .justify_between()
                 .child(
                     div()
-                        .font_weight(gpui::FontWeight::BOLD)
+                        .text_sm()
+                        .text_color(rgb(0xaaaaaa))
+                        .child("Unallocated:"),
+                )
+                .child(div().text_sm().child(format!("{} bytes", page.free_space))),
+        )
+        .child(
+            div()
+                .flex()
+                .justify_between()
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0xaaaaaa))
+                        .child("Freeblocks:"),
+                )
+                .child(div().text_sm().child(format!(
+                    "{} bytes in {}",
+                    page.freeblock_space(),
+                    page.freeblocks.len()
+                ))),
+        )
+        .child(
+            div()
+                .flex()
+                .justify_between()
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0xaaaaaa))
                         .child("Fragmented:"),
                 )
                 .child(
@@ -481,6 +541,7 @@ pub fn render_page_details(page: &PageInfo, page_size: Option<usize>) -> impl In
                         .flex()
                         .items_center()
                         .gap_2()
+                        .text_sm()
                         .child(div().child(format!("{} bytes", page.fragmented_bytes)))
                         .when(page.fragmented_bytes > 0, |this| {
                             this.child(div().text_xs().text_color(rgb(0xff9800)).child("⚠"))
@@ -500,6 +561,78 @@ pub fn render_page_details(page: &PageInfo, page_size: Option<usize>) -> impl In
                     .child(div().child(format!("→ {}", ptr))),
             )
         })
+        .when_some(page.overflow_owner.as_ref(), |this, owner| {
+            this.child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(
+                        div()
+                            .font_weight(gpui::FontWeight::BOLD)
+                            .child("Overflow Of:"),
+                    )
+                    .child(div().child(format!(
+                        "Page {}, cell {}",
+                        owner.page_number, owner.cell_index
+                    ))),
+            )
+            .child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(
+                        div()
+                            .font_weight(gpui::FontWeight::BOLD)
+                            .child("Chain Position:"),
+                    )
+                    .child(div().child(format!(
+                        "{} of {}",
+                        owner.chain_position, owner.chain_length
+                    ))),
+            )
+        })
+        .when_some(page.wal_frame, |this, frame| {
+            this.child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(
+                        div()
+                            .font_weight(gpui::FontWeight::BOLD)
+                            .child("WAL Frame:"),
+                    )
+                    .child(div().text_color(rgb(0xfacc15)).child(format!("#{}", frame))),
+            )
+        })
+        .when_some(page.ptrmap_entry.as_ref(), |this, entry| {
+            this.child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(
+                        div()
+                            .font_weight(gpui::FontWeight::BOLD)
+                            .child("Pointer Map:"),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(div().child(if entry.parent_page != 0 {
+                                format!("{} ← {}", entry.entry_type.name(), entry.parent_page)
+                            } else {
+                                entry.entry_type.name().to_string()
+                            }))
+                            .when(entry.mismatch.is_some(), |this| {
+                                this.child(div().text_xs().text_color(rgb(0xff9800)).child("⚠"))
+                            }),
+                    ),
+            )
+            .when_some(entry.mismatch.clone(), |this, mismatch| {
+                this.child(div().text_xs().text_color(rgb(0xff9800)).child(mismatch))
+            })
+        })
         .when_some(page_size, |this, size| {
             this.child(
                 div()
@@ -562,6 +695,499 @@ pub fn render_page_details(page: &PageInfo, page_size: Option<usize>) -> impl In
         .into_any_element()
 }
 
+pub fn render_cell_table(page: &PageInfo) -> impl IntoElement {
+    let show_child = page.page_type.has_rightmost_pointer();
+    let show_rowid = matches!(
+        page.page_type,
+        PageType::TableBTreeLeaf | PageType::TableBTreeInterior
+    );
+    let show_payload = page.page_type != PageType::TableBTreeInterior;
+
+    let mut columns = vec!["#", "Offset", "Size"];
+    if show_child {
+        columns.push("Child");
+    }
+    if show_rowid {
+        columns.push("Rowid");
+    }
+    if show_payload {
+        columns.push("Payload");
+        columns.push("Overflow");
+    }
+
+    let header_row = columns.iter().fold(
+        div()
+            .flex()
+            .gap_1()
+            .pb_1()
+            .border_b_1()
+            .border_color(rgb(0x3e3e3e)),
+        |row, column| {
+            row.child(
+                div()
+                    .flex_1()
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(rgb(0xaaaaaa))
+                    .child(*column),
+            )
+        },
+    );
+
+    let rows = page.cells.iter().map(|cell| {
+        let mut values = vec![
+            format!("{}", cell.index),
+            format!("{}", cell.offset),
+            format!("{}", cell.size),
+        ];
+        if show_child {
+            values.push(cell.left_child.map_or("-".to_string(), |child| format!("→ {}", child)));
+        }
+        if show_rowid {
+            values.push(cell.rowid.map_or("-".to_string(), |rowid| format!("{}", rowid)));
+        }
+        if show_payload {
+            values.push(format_cell_payload(cell));
+            values.push(
+                cell.first_overflow_page
+                    .map_or("-".to_string(), |overflow| format!("→ {}", overflow)),
+            );
+        }
+
+        values.into_iter().fold(div().flex().gap_1(), |row, value| {
+            row.child(
+                div()
+                    .flex_1()
+                    .text_xs()
+                    .text_color(rgb(0xcccccc))
+                    .child(value),
+            )
+        })
+    });
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(format!("Cells ({})", page.cells.len())),
+        )
+        .child(header_row)
+        .children(rows)
+}
+
+pub fn render_record_list(records: &[Record]) -> impl IntoElement {
+    let rows = records.iter().map(|record| {
+        let label = match record.rowid {
+            Some(rowid) => format!("Row {}", rowid),
+            None => format!("Entry {}", record.cell_index),
+        };
+        let values = record
+            .values
+            .iter()
+            .map(|value| value.display(32))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        div()
+            .flex()
+            .flex_col()
+            .py_1()
+            .border_b_1()
+            .border_color(rgb(0x333333))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_xs()
+                            .font_weight(gpui::FontWeight::BOLD)
+                            .text_color(rgb(0xaaaaaa))
+                            .child(label),
+                    )
+                    .when(!record.complete, |this| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0xff9800))
+                                .child("⚠ truncated"),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0xcccccc))
+                    .child(format!("({})", values)),
+            )
+    });
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(format!("Rows ({})", records.len())),
+        )
+        .children(rows)
+}
+
+/// Page as it was before the open transaction, from the rollback journal
+pub fn render_before_image(before_image: &JournalBeforeImage, page_size: Option<usize>) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .p_2()
+        .rounded_md()
+        .border_1()
+        .border_color(rgb(0x7c3aed))
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child("Before Image (journal)"),
+        )
+        .child(
+            div()
+                .text_xs()
+                .text_color(rgb(0xaaaaaa))
+                .child(if before_image.changed_bytes == 0 {
+                    "Identical to the current page".to_string()
+                } else {
+                    format!(
+                        "{} bytes differ from the current page",
+                        before_image.changed_bytes
+                    )
+                }),
+        )
+        .when(!before_image.checksum_valid, |this| {
+            this.child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0xff9800))
+                    .child("⚠ Journal checksum mismatch, rollback would stop before this page"),
+            )
+        })
+        .child(render_page_details(&before_image.page, page_size))
+}
+
+/// The page's bytes drawn to scale, from the start of the page to the
+/// reserved region at its end. Hovering a segment shows its offset and length.
+pub fn render_page_anatomy(page: &PageInfo, page_size: usize, usable_size: usize) -> impl IntoElement {
+    let regions = page.layout(page_size, usable_size);
+
+    // Byte totals per kind of region, in the order they first appear
+    let mut totals: Vec<(&'static str, u32, u32)> = Vec::new();
+    for region in &regions {
+        let name = region.kind.name();
+        match totals.iter_mut().find(|(total_name, _, _)| *total_name == name) {
+            Some((_, _, bytes)) => *bytes += region.length,
+            None => totals.push((name, region_color(region.kind), region.length)),
+        }
+    }
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child("Page Layout"),
+        )
+        .child(
+            div()
+                .flex()
+                .w_full()
+                .h(px(20.0))
+                .rounded_sm()
+                .overflow_hidden()
+                .bg(rgb(0x1e1e1e))
+                .children(regions.into_iter().enumerate().map(|(index, region)| {
+                    let text = SharedString::from(format!(
+                        "{}: offset {}, {} bytes",
+                        region.label(),
+                        region.offset,
+                        region.length
+                    ));
+                    div()
+                        .id(("page-region", index))
+                        .flex_shrink_0()
+                        .h_full()
+                        .w(relative(region.length as f32 / page_size as f32))
+                        .bg(rgb(region_color(region.kind)))
+                        .hover(|this| this.opacity(0.7))
+                        .tooltip(move |_window, cx| {
+                            let text = text.clone();
+                            cx.new(|_| TextTooltip { text }).into()
+                        })
+                })),
+        )
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .gap_1()
+                .children(totals.into_iter().map(|(name, color, bytes)| {
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(div().size(px(10.0)).rounded_sm().bg(rgb(color)))
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_xs()
+                                .text_color(rgb(0xcccccc))
+                                .child(name),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0xaaaaaa))
+                                .child(format!("{} bytes", bytes)),
+                        )
+                })),
+        )
+}
+
+/// Fill color of a page region, in the layout bar and the hex view.
+/// Neighbouring cells alternate between two shades so their boundaries stay
+/// visible.
+pub fn region_color(kind: PageRegionKind) -> u32 {
+    match kind {
+        PageRegionKind::DatabaseHeader => 0x6366f1,
+        PageRegionKind::PageHeader => 0x8b5cf6,
+        PageRegionKind::CellPointers => 0x0ea5e9,
+        PageRegionKind::Unallocated => 0x374151,
+        PageRegionKind::Cell(index) if index % 2 == 0 => 0x22c55e,
+        PageRegionKind::Cell(_) => 0x15803d,
+        PageRegionKind::Freeblock => 0xf59e0b,
+        PageRegionKind::Fragment => 0xef4444,
+        PageRegionKind::Content => 0x64748b,
+        PageRegionKind::Reserved => 0x9ca3af,
+    }
+}
+
+/// Problems the integrity check found on one page
+pub fn render_findings(findings: &[IntegrityFinding]) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .p_2()
+        .rounded_md()
+        .border_1()
+        .border_color(rgb(0xf59e0b))
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(format!("Integrity Issues ({})", findings.len())),
+        )
+        .children(findings.iter().map(|finding| {
+            div()
+                .flex()
+                .flex_col()
+                .child(
+                    div()
+                        .text_xs()
+                        .font_weight(gpui::FontWeight::BOLD)
+                        .text_color(rgb(0xf59e0b))
+                        .child(format!("⚠ {}", finding.kind.name())),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(rgb(0xcccccc))
+                        .child(finding.message.clone()),
+                )
+        }))
+}
+
+/// Why the reachability analysis marked a page: nothing references it, or
+/// every place that does when there is more than one
+pub fn render_page_reachability(page_number: u32, reachability: &Reachability) -> impl IntoElement {
+    let references = reachability.shared.get(&page_number);
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .p_2()
+        .rounded_md()
+        .border_1()
+        .border_color(rgb(0xe879f9))
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(match references {
+                    Some(references) => format!("Referenced {} Times", references.len()),
+                    None => "Never Referenced".to_string(),
+                }),
+        )
+        .child(match references {
+            Some(references) => div()
+                .flex()
+                .flex_col()
+                .children(references.iter().map(|reference| {
+                    div()
+                        .text_xs()
+                        .text_color(rgb(0xcccccc))
+                        .child(reference.description())
+                }))
+                .into_any_element(),
+            None => div()
+                .text_xs()
+                .text_color(rgb(0xaaaaaa))
+                .child("No root, freelist or overflow chain reaches this page")
+                .into_any_element(),
+        })
+}
+
+/// Counts of the pages changed by the last reload or session step
+pub fn render_reload_changes(changes: &ReloadChanges) -> impl IntoElement {
+    let count_row = |label: String, count: usize| {
+        div()
+            .text_xs()
+            .text_color(rgb(0xcccccc))
+            .flex()
+            .justify_between()
+            .child(label)
+            .child(format!("{}", count))
+    };
+    let heading = |text: &'static str| {
+        div()
+            .pt_1()
+            .text_xs()
+            .font_weight(gpui::FontWeight::BOLD)
+            .text_color(rgb(0xaaaaaa))
+            .child(text)
+    };
+
+    let totals = [PageChange::Added, PageChange::Modified, PageChange::Freed]
+        .into_iter()
+        .map(|change| (change, changes.count(change)))
+        .filter(|&(_, count)| count > 0)
+        .map(|(change, count)| format!("{} {}", count, change.name().to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .p_2()
+        .rounded_md()
+        .border_1()
+        .border_color(rgb(0x38bdf8))
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(format!("Recently Changed ({})", changes.pages.len())),
+        )
+        .child(div().text_xs().text_color(rgb(0xaaaaaa)).child(totals))
+        .child(heading("By page type"))
+        .children(
+            changes
+                .by_page_type
+                .iter()
+                .map(|(&page_type, &count)| count_row(page_type.to_string(), count)),
+        )
+        .child(heading("By owner"))
+        .children(
+            changes
+                .by_owner
+                .iter()
+                .map(|(owner, &count)| count_row(owner.clone(), count)),
+        )
+}
+
+/// One page as it reads in each of two compared files, highlighting the
+/// fields that differ
+pub fn render_page_comparison(page_number: u32, fields: &[PageFieldDiff]) -> impl IntoElement {
+    let value_cell = |value: &Option<String>, differs: bool| {
+        div()
+            .flex_1()
+            .text_xs()
+            .text_color(if differs { rgb(0xfbbf24) } else { rgb(0xcccccc) })
+            .child(value.clone().unwrap_or_else(|| "(missing)".to_string()))
+    };
+
+    let rows = fields.iter().map(|field| {
+        let differs = field.differs();
+        div()
+            .flex()
+            .gap_2()
+            .px_1()
+            .py_1()
+            .rounded_sm()
+            .when(differs, |this| this.bg(rgb(0x3a2f1a)))
+            .child(
+                div()
+                    .w(px(90.0))
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(rgb(0xaaaaaa))
+                    .child(field.name),
+            )
+            .child(value_cell(&field.left, differs))
+            .child(value_cell(&field.right, differs))
+    });
+
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(gpui::FontWeight::BOLD)
+                .text_color(rgb(0xffffff))
+                .child(format!("Page {}", page_number)),
+        )
+        .child(
+            div()
+                .flex()
+                .gap_2()
+                .px_1()
+                .text_xs()
+                .text_color(rgb(0x888888))
+                .child(div().w(px(90.0)).child("Field"))
+                .child(div().flex_1().child("Before"))
+                .child(div().flex_1().child("After")),
+        )
+        .children(rows)
+}
+
+fn format_cell_payload(cell: &CellInfo) -> String {
+    match cell.payload_size {
+        Some(total) if cell.first_overflow_page.is_some() => {
+            format!("{}/{}", cell.local_payload_size, total)
+        }
+        Some(total) => format!("{}", total),
+        None => "-".to_string(),
+    }
+}
+
 pub fn render_database_info(header: &DatabaseHeader) -> impl IntoElement {
     div()
         .flex()
@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::models::{BTree, BTreeNode, DatabaseInfo};
+use crate::ui::entities::PageSelected;
+use gpui::{
+    Context, EventEmitter, IntoElement, MouseDownEvent, ParentElement, Render, Window, div,
+    prelude::*, px, rgb,
+};
+
+/// Panel listing every b-tree in the database as collapsible levels of pages
+pub struct BTreeView {
+    database_info: Option<Arc<DatabaseInfo>>,
+    selected_page: Option<u32>,
+    /// Root pages of the trees that are expanded
+    expanded_trees: HashSet<u32>,
+    /// (root page, depth) of the levels that are expanded
+    expanded_levels: HashSet<(u32, u32)>,
+}
+
+impl EventEmitter<PageSelected> for BTreeView {}
+
+impl BTreeView {
+    pub fn new() -> Self {
+        Self {
+            database_info: None,
+            selected_page: None,
+            expanded_trees: HashSet::new(),
+            expanded_levels: HashSet::new(),
+        }
+    }
+
+    pub fn update_data(&mut self, database_info: Option<Arc<DatabaseInfo>>, cx: &mut Context<Self>) {
+        self.database_info = database_info;
+
+        // Drop expansion state for trees that no longer exist
+        let roots: HashSet<u32> = self
+            .database_info
+            .iter()
+            .flat_map(|info| info.btrees.iter().map(|btree| btree.root_page))
+            .collect();
+        self.expanded_trees.retain(|root| roots.contains(root));
+        self.expanded_levels.retain(|(root, _)| roots.contains(root));
+
+        cx.notify();
+    }
+
+    pub fn set_selected_page(&mut self, page_number: Option<u32>, cx: &mut Context<Self>) {
+        self.selected_page = page_number;
+        cx.notify();
+    }
+
+    fn toggle_tree(&mut self, root_page: u32, cx: &mut Context<Self>) {
+        if !self.expanded_trees.remove(&root_page) {
+            self.expanded_trees.insert(root_page);
+        }
+        cx.notify();
+    }
+
+    fn toggle_level(&mut self, root_page: u32, depth: u32, cx: &mut Context<Self>) {
+        if !self.expanded_levels.remove(&(root_page, depth)) {
+            self.expanded_levels.insert((root_page, depth));
+        }
+        cx.notify();
+    }
+
+    fn select_page(&mut self, page_number: u32, cx: &mut Context<Self>) {
+        self.selected_page = Some(page_number);
+        cx.emit(PageSelected { page_number });
+        cx.notify();
+    }
+
+    fn render_tree(&self, btree: &BTree, cx: &mut Context<Self>) -> impl IntoElement {
+        let root_page = btree.root_page;
+        let is_expanded = self.expanded_trees.contains(&root_page);
+
+        div()
+            .flex()
+            .flex_col()
+            .child(
+                div()
+                    .id(("btree", root_page))
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(|this| this.bg(rgb(0x333333)))
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                            this.toggle_tree(root_page, cx);
+                        }),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xffffff))
+                            .child(format!(
+                                "{} {}",
+                                if is_expanded { "▾" } else { "▸" },
+                                btree.owner.label()
+                            )),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0xaaaaaa))
+                            .child(format!("h{} · {}p", btree.height(), btree.page_count())),
+                    ),
+            )
+            .when(is_expanded, |this| {
+                this.child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .pl_4()
+                        .children(
+                            btree
+                                .levels()
+                                .into_iter()
+                                .map(|level| self.render_level(root_page, level, cx)),
+                        ),
+                )
+            })
+    }
+
+    fn render_level(
+        &self,
+        root_page: u32,
+        level: &[BTreeNode],
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let depth = level.first().map_or(0, |node| node.depth);
+        let is_expanded = self.expanded_levels.contains(&(root_page, depth));
+        let interior: Vec<_> = level.iter().filter(|node| node.fan_out() > 0).collect();
+        let fan_out = if interior.is_empty() {
+            None
+        } else {
+            Some(
+                interior.iter().map(|node| node.fan_out()).sum::<usize>() as f32
+                    / interior.len() as f32,
+            )
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .child(
+                div()
+                    .id(("btree-level", root_page as u64 * 64 + depth as u64))
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(|this| this.bg(rgb(0x333333)))
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                            this.toggle_level(root_page, depth, cx);
+                        }),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0xcccccc))
+                            .child(format!(
+                                "{} Level {} — {} pages",
+                                if is_expanded { "▾" } else { "▸" },
+                                depth,
+                                level.len()
+                            )),
+                    )
+                    .when_some(fan_out, |this, fan_out| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0xaaaaaa))
+                                .child(format!("fan-out {:.1}", fan_out)),
+                        )
+                    }),
+            )
+            .when(is_expanded, |this| {
+                this.child(
+                    div()
+                        .flex()
+                        .flex_wrap()
+                        .gap_1()
+                        .pl_4()
+                        .py_1()
+                        .children(level.iter().map(|node| self.render_page_chip(node, cx))),
+                )
+            })
+    }
+
+    fn render_page_chip(&self, node: &BTreeNode, cx: &mut Context<Self>) -> impl IntoElement {
+        let page_number = node.page_number;
+        let is_selected = self.selected_page == Some(page_number);
+        let color = self
+            .database_info
+            .as_ref()
+            .and_then(|info| info.get_page_info(page_number))
+            .map(|page| page.page_type.color())
+            .unwrap_or(rgb(0x9E9E9E).into());
+
+        div()
+            .id(("btree-page", page_number))
+            .px_1()
+            .rounded_sm()
+            .bg(color)
+            .cursor_pointer()
+            .when(is_selected, |this| this.border_1().border_color(rgb(0xffffff)))
+            .hover(|this| this.opacity(0.7))
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                    this.select_page(page_number, cx);
+                }),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0xffffff))
+                    .child(format!("{}", page_number)),
+            )
+    }
+}
+
+impl Render for BTreeView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let btrees: &[BTree] = self
+            .database_info
+            .as_ref()
+            .map_or(&[], |info| &info.btrees);
+
+        div()
+            .id("btree-view")
+            .w(px(260.0))
+            .bg(rgb(0x252525))
+            .border_r_1()
+            .border_color(rgb(0x3e3e3e))
+            .flex()
+            .flex_col()
+            .child(
+                div().p_4().border_b_1().border_color(rgb(0x3e3e3e)).child(
+                    div()
+                        .text_lg()
+                        .font_weight(gpui::FontWeight::BOLD)
+                        .text_color(rgb(0xffffff))
+                        .child("B-Trees"),
+                ),
+            )
+            .child(
+                div()
+                    .id("btree-view-content")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .p_2()
+                    .flex()
+                    .flex_col()
+                    .gap_1()
+                    .when(btrees.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_2()
+                                .text_sm()
+                                .text_color(rgb(0xaaaaaa))
+                                .child("No b-trees found"),
+                        )
+                    })
+                    .children(btrees.iter().map(|btree| self.render_tree(btree, cx))),
+            )
+    }
+}
+
+impl Default for BTreeView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,273 @@
+use std::sync::Arc;
+
+use crate::models::{DatabaseDiff, DatabaseInfo, PageDiffKind};
+use crate::parser::diff::compare_page_fields;
+use crate::ui::components;
+use gpui::{
+    Context, IntoElement, MouseDownEvent, ParentElement, Render, Window, div, prelude::*, px, rgb,
+};
+
+/// Side-by-side comparison of two database files aligned by page number
+pub struct DiffView {
+    diff: Option<Arc<DatabaseDiff>>,
+    selected_page: Option<u32>,
+    /// Leave identical pages out of both grids
+    changed_only: bool,
+}
+
+impl DiffView {
+    pub fn new() -> Self {
+        Self {
+            diff: None,
+            selected_page: None,
+            changed_only: false,
+        }
+    }
+
+    pub fn update_data(&mut self, diff: Option<Arc<DatabaseDiff>>, cx: &mut Context<Self>) {
+        self.diff = diff;
+        self.selected_page = None;
+        cx.notify();
+    }
+
+    pub fn set_selected_page(&mut self, page_number: Option<u32>, cx: &mut Context<Self>) {
+        self.selected_page = page_number;
+        cx.notify();
+    }
+
+    pub fn toggle_changed_only(&mut self, cx: &mut Context<Self>) {
+        self.changed_only = !self.changed_only;
+        cx.notify();
+    }
+
+    fn visible_pages(&self, diff: &DatabaseDiff) -> Vec<(u32, PageDiffKind)> {
+        diff.pages
+            .iter()
+            .filter(|&(_, &kind)| !self.changed_only || kind != PageDiffKind::Identical)
+            .map(|(&page_number, &kind)| (page_number, kind))
+            .collect()
+    }
+
+    fn render_summary(&self, diff: &DatabaseDiff, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .items_center()
+            .gap_3()
+            .px_4()
+            .py_2()
+            .bg(rgb(0x252525))
+            .border_b_1()
+            .border_color(rgb(0x3e3e3e))
+            .child(
+                div()
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(rgb(0xaaaaaa))
+                    .child(format!(
+                        "{} changed of {} pages",
+                        diff.changed_count(),
+                        diff.pages.len()
+                    )),
+            )
+            .children(PageDiffKind::ALL.into_iter().map(|kind| {
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(div().size(px(10.0)).rounded_sm().bg(kind.color()))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0xcccccc))
+                            .child(format!("{} {}", diff.count(kind), kind.name())),
+                    )
+            }))
+            .child(div().flex_1())
+            .child(
+                div()
+                    .id("diff-changed-only")
+                    .px_2()
+                    .rounded_sm()
+                    .text_xs()
+                    .text_color(rgb(0xffffff))
+                    .bg(if self.changed_only {
+                        rgb(0x4b5563)
+                    } else {
+                        rgb(0x374151)
+                    })
+                    .cursor_pointer()
+                    .hover(|this| this.bg(rgb(0x4b5563)))
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                            this.toggle_changed_only(cx);
+                        }),
+                    )
+                    .child("Changed only"),
+            )
+    }
+
+    /// Grid of one file's pages. Both grids lay out the same page numbers so
+    /// their rows line up, with a placeholder where a file lacks the page.
+    fn render_grid(
+        &self,
+        id: &'static str,
+        database_info: &DatabaseInfo,
+        pages: &[(u32, PageDiffKind)],
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let pages_per_row = 6;
+        let title = database_info
+            .source_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
+
+        let rows = pages.chunks(pages_per_row).map(|row| {
+            div().flex().gap_2().children(row.iter().map(|&(page_number, kind)| {
+                let is_selected = self.selected_page == Some(page_number);
+                let square = div()
+                    .id((id, page_number as usize))
+                    .size(px(64.0))
+                    .rounded_md()
+                    .flex()
+                    .flex_col()
+                    .items_center()
+                    .justify_center()
+                    .cursor_pointer()
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                            this.set_selected_page(Some(page_number), cx);
+                        }),
+                    );
+
+                match database_info.pages.get(&page_number) {
+                    Some(page) => square
+                        .bg(page.page_type.color())
+                        .when(is_selected, |this| {
+                            this.border_2().border_color(rgb(0xffffff))
+                        })
+                        .when(!is_selected, |this| match kind {
+                            PageDiffKind::Identical => this.border_1().border_color(kind.color()),
+                            _ => this.border_2().border_color(kind.color()),
+                        })
+                        .child(
+                            div()
+                                .text_xs()
+                                .font_weight(gpui::FontWeight::BOLD)
+                                .text_color(rgb(0xffffff))
+                                .child(format!("{}", page_number)),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0xffffff))
+                                .opacity(0.8)
+                                .child(page.page_type.short_name()),
+                        ),
+                    None => square
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(if is_selected {
+                            rgb(0xffffff)
+                        } else {
+                            rgb(0x333333)
+                        })
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0x666666))
+                                .child(format!("{}", page_number)),
+                        ),
+                }
+            }))
+        });
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_1()
+            .min_h_0()
+            .child(
+                div()
+                    .px_4()
+                    .py_2()
+                    .text_sm()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(rgb(0xffffff))
+                    .child(title),
+            )
+            .child(
+                div()
+                    .id(id)
+                    .flex_1()
+                    .px_4()
+                    .pb_4()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .overflow_y_scroll()
+                    .children(rows),
+            )
+    }
+}
+
+impl Render for DiffView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(diff) = self.diff.clone() else {
+            return div()
+                .flex_1()
+                .p_4()
+                .text_color(rgb(0xaaaaaa))
+                .child("No comparison loaded")
+                .into_any_element();
+        };
+        let pages = self.visible_pages(&diff);
+        let comparison = self.selected_page.map(|page_number| {
+            let fields = compare_page_fields(
+                diff.left.get_page_info(page_number),
+                diff.right.get_page_info(page_number),
+            );
+            (page_number, fields)
+        });
+
+        div()
+            .flex()
+            .flex_col()
+            .flex_1()
+            .min_h_0()
+            .child(self.render_summary(&diff, cx))
+            .child(
+                div()
+                    .flex()
+                    .flex_1()
+                    .min_h_0()
+                    .child(self.render_grid("diff-left", &diff.left, &pages, cx))
+                    .child(div().w(px(1.0)).bg(rgb(0x3e3e3e)))
+                    .child(self.render_grid("diff-right", &diff.right, &pages, cx))
+                    .when_some(comparison, |this, (page_number, fields)| {
+                        this.child(
+                            div()
+                                .id("diff-comparison")
+                                .w(px(360.0))
+                                .p_4()
+                                .bg(rgb(0x252525))
+                                .border_l_1()
+                                .border_color(rgb(0x3e3e3e))
+                                .overflow_y_scroll()
+                                .child(components::render_page_comparison(page_number, &fields)),
+                        )
+                    }),
+            )
+            .into_any_element()
+    }
+}
+
+impl Default for DiffView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,7 @@
 use crate::file_manager::{FileManager, FileManagerEvent};
 use crate::models::DatabaseInfo;
+use crate::parser::ParseCancelledError;
+use crate::recorder::SESSION_EXTENSION;
 use anyhow::Result;
 use gpui::{
     Context, EventEmitter, IntoElement, ParentElement, Render, Task, Window, div, prelude::*, rgb,
@@ -18,6 +20,12 @@ pub struct FileOpened {
     pub database_info: Arc<DatabaseInfo>,
 }
 
+/// A recorded session was picked instead of a database
+#[derive(Clone, Debug)]
+pub struct SessionOpenRequested {
+    pub path: PathBuf,
+}
+
 #[derive(Clone, Debug)]
 pub struct FileOpenError {
     pub path: PathBuf,
@@ -40,6 +48,7 @@ pub struct FileDialogManager {
 impl EventEmitter<FileOpenRequested> for FileDialogManager {}
 impl EventEmitter<FileOpened> for FileDialogManager {}
 impl EventEmitter<FileOpenError> for FileDialogManager {}
+impl EventEmitter<SessionOpenRequested> for FileDialogManager {}
 impl EventEmitter<FileManagerEvent> for FileDialogManager {}
 
 impl FileDialogManager {
@@ -57,6 +66,7 @@ impl FileDialogManager {
         cx.spawn(async move |entity, cx| {
             let file_dialog = FileDialog::new()
                 .add_filter("SQLite Database", &["db", "sqlite", "sqlite3"])
+                .add_filter("Recorded Session", &[SESSION_EXTENSION])
                 .set_title("Open SQLite Database");
 
             if let Some(file_path) = file_dialog.pick_file() {
@@ -74,6 +84,13 @@ impl FileDialogManager {
     }
 
     pub fn open_file(&mut self, path: PathBuf, cx: &mut Context<Self>) -> Task<Result<()>> {
+        if path.extension().is_some_and(|extension| extension == SESSION_EXTENSION) {
+            self.state = FileDialogState::Idle;
+            cx.emit(SessionOpenRequested { path });
+            cx.notify();
+            return Task::ready(Ok(()));
+        }
+
         self.state = FileDialogState::Loading(path.clone());
         cx.notify();
 
@@ -95,6 +112,13 @@ impl FileDialogManager {
                 })?;
                 Ok(())
             }
+            Err(e) if e.is::<ParseCancelledError>() => {
+                entity.update(cx, |this, cx| {
+                    this.state = FileDialogState::Idle;
+                    cx.notify();
+                })?;
+                Ok(())
+            }
             Err(e) => {
                 entity.update(cx, |this, cx| {
                     this.state = FileDialogState::Error(e.to_string());
@@ -121,6 +145,11 @@ impl FileDialogManager {
         }
     }
 
+    /// Stop parsing the file being opened
+    pub fn cancel_loading(&mut self) {
+        self.file_manager.cancel_current_parse();
+    }
+
     pub fn state(&self) -> &FileDialogState {
         &self.state
     }
@@ -0,0 +1,951 @@
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::models::DatabaseInfo;
+use crate::page_editor::{EditValidation, PageEdit, save_page_edit, validate_page_edit};
+use crate::parser::annotate::{
+    AnnotatedPage, PageField, find_all, format_hex, read_annotated_page, search_bytes,
+};
+use crate::ui::components::region_color;
+use anyhow::Result;
+use gpui::{
+    App, ClipboardItem, Context, EventEmitter, FocusHandle, IntoElement, KeyDownEvent, Keystroke,
+    MouseButton, MouseDownEvent, MouseMoveEvent, ParentElement, Render, ScrollStrategy,
+    UniformListScrollHandle, Window, div, prelude::*, px, rgb, rgba, uniform_list,
+};
+
+/// Bytes shown on each row of the dump
+const BYTES_PER_ROW: usize = 16;
+
+/// An edited page was written back to the database file
+#[derive(Clone, Debug)]
+pub struct PageEdited {
+    pub page_number: u32,
+    pub backup_path: PathBuf,
+}
+
+/// Hex and ASCII dump of the selected page, colored by the region each byte
+/// belongs to. Clicking a byte explains its field, shift-clicking or dragging
+/// selects a range to copy, and typing searches the page.
+///
+/// The dump is read-only until edit mode is switched on. Typed hex digits
+/// then overwrite the byte under the cursor, and edits stay in memory until
+/// they are saved. Unsaved edits are dropped when another page is selected.
+pub struct HexView {
+    database_info: Option<Arc<DatabaseInfo>>,
+    selected_page: Option<u32>,
+    page: Option<AnnotatedPage>,
+    error: Option<String>,
+    /// A page is being read; the one on display, if any, stays until it arrives
+    loading: bool,
+    /// Bumped by every load so that only the latest one is shown
+    load_generation: usize,
+    /// Byte the selection started at and byte it extends to, in either order
+    selection: Option<(usize, usize)>,
+    query: String,
+    /// Read the query as hex digits rather than as text
+    hex_query: bool,
+    matches: Vec<usize>,
+    match_index: usize,
+    /// Edit mode, switched on explicitly
+    editing: bool,
+    edit: Option<PageEdit>,
+    /// First hex digit typed for the byte under the cursor
+    pending_nibble: Option<u8>,
+    /// The edited page reparsed, or why it could not be parsed
+    validation: Option<Result<EditValidation, String>>,
+    /// A save is copying the database to its backup and writing the page;
+    /// the edit is frozen until it finishes
+    saving: bool,
+    save_error: Option<String>,
+    focus_handle: FocusHandle,
+    search_focus: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl EventEmitter<PageEdited> for HexView {}
+
+impl HexView {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            database_info: None,
+            selected_page: None,
+            page: None,
+            error: None,
+            loading: false,
+            load_generation: 0,
+            selection: None,
+            query: String::new(),
+            hex_query: true,
+            matches: Vec::new(),
+            match_index: 0,
+            editing: false,
+            edit: None,
+            pending_nibble: None,
+            validation: None,
+            saving: false,
+            save_error: None,
+            focus_handle: cx.focus_handle(),
+            search_focus: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+        }
+    }
+
+    pub fn update_data(
+        &mut self,
+        database_info: Option<Arc<DatabaseInfo>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.database_info = database_info;
+        self.load_page(cx);
+    }
+
+    pub fn set_selected_page(&mut self, page_number: Option<u32>, cx: &mut Context<Self>) {
+        if self.selected_page != page_number {
+            self.selected_page = page_number;
+            self.selection = None;
+            self.page = None;
+            self.error = None;
+            self.edit = None;
+            self.load_page(cx);
+        }
+    }
+
+    /// Read and annotate the selected page in the background
+    fn load_page(&mut self, cx: &mut Context<Self>) {
+        self.load_generation += 1;
+        let (Some(database_info), Some(page_number)) =
+            (self.database_info.clone(), self.selected_page)
+        else {
+            self.loading = false;
+            self.show_page(None, cx);
+            return;
+        };
+
+        self.loading = true;
+        cx.notify();
+        let generation = self.load_generation;
+        let task =
+            cx.background_spawn(async move { read_annotated_page(&database_info, page_number) });
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+            this.update(cx, |view, cx| {
+                if view.load_generation == generation {
+                    view.loading = false;
+                    view.show_page(Some(result), cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Show a page that was read, keeping the selection when it still fits,
+    /// as it does after a reload
+    fn show_page(&mut self, result: Option<Result<AnnotatedPage>>, cx: &mut Context<Self>) {
+        (self.page, self.error) = match result {
+            Some(Ok(page)) => (Some(page), None),
+            Some(Err(e)) => (None, Some(format!("Failed to read page: {}", e))),
+            None => (None, None),
+        };
+
+        let page_len = self.page.as_ref().map_or(0, |page| page.bytes.len());
+        self.selection = self
+            .selection
+            .filter(|&(anchor, cursor)| anchor.max(cursor) < page_len);
+        self.sync_edit();
+        cx.notify();
+    }
+
+    /// Start an edit of the page on display in edit mode, or drop one that
+    /// no longer applies
+    fn sync_edit(&mut self) {
+        // An edit survives a reload of its own page; saving checks the page
+        // on disk still matches what the edit started from
+        let page_number = self.page.as_ref().map(|page| page.page_number);
+        self.edit = self
+            .edit
+            .take()
+            .filter(|edit| self.editing && Some(edit.page_number) == page_number);
+        if self.editing && self.edit.is_none() {
+            self.edit = self
+                .page
+                .as_ref()
+                .map(|page| PageEdit::new(page.page_number, page.bytes.clone()));
+            self.pending_nibble = None;
+        }
+        self.validate();
+        self.update_matches();
+    }
+
+    /// The bytes on display, edited or as read
+    fn bytes(&self) -> &[u8] {
+        match (&self.edit, &self.page) {
+            (Some(edit), _) => edit.bytes(),
+            (None, Some(page)) => &page.bytes,
+            (None, None) => &[],
+        }
+    }
+
+    fn selected_range(&self) -> Option<Range<usize>> {
+        self.selection
+            .map(|(anchor, cursor)| anchor.min(cursor)..anchor.max(cursor) + 1)
+    }
+
+    fn select_byte(&mut self, offset: usize, extend: bool, cx: &mut Context<Self>) {
+        let selection = match self.selection {
+            Some((anchor, _)) if extend => (anchor, offset),
+            _ => (offset, offset),
+        };
+        if self.selection != Some(selection) {
+            self.selection = Some(selection);
+            cx.notify();
+        }
+    }
+
+    fn copy_selection(&self, as_hex: bool, cx: &mut Context<Self>) {
+        let Some(bytes) = self
+            .selected_range()
+            .and_then(|range| self.bytes().get(range))
+        else {
+            return;
+        };
+        let text = if as_hex {
+            format_hex(bytes)
+        } else {
+            String::from_utf8_lossy(bytes).into_owned()
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    fn query_bytes(&self) -> Option<Vec<u8>> {
+        search_bytes(&self.query, self.hex_query)
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = self
+            .query_bytes()
+            .map(|needle| find_all(self.bytes(), &needle))
+            .unwrap_or_default();
+        self.match_index = 0;
+    }
+
+    /// Search again after the query changed and select the first match
+    fn search(&mut self, cx: &mut Context<Self>) {
+        self.update_matches();
+        self.show_match(cx);
+    }
+
+    fn step_match(&mut self, backwards: bool, cx: &mut Context<Self>) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let count = self.matches.len();
+        self.match_index = if backwards {
+            (self.match_index + count - 1) % count
+        } else {
+            (self.match_index + 1) % count
+        };
+        self.show_match(cx);
+    }
+
+    fn show_match(&mut self, cx: &mut Context<Self>) {
+        let needle_len = self.query_bytes().map_or(0, |needle| needle.len());
+        if let Some(&offset) = self.matches.get(self.match_index) {
+            self.selection = Some((offset, offset + needle_len - 1));
+            self.scroll_handle
+                .scroll_to_item(offset / BYTES_PER_ROW, ScrollStrategy::Center);
+        }
+        cx.notify();
+    }
+
+    fn toggle_query_mode(&mut self, cx: &mut Context<Self>) {
+        self.hex_query = !self.hex_query;
+        self.search(cx);
+    }
+
+    /// Switch edit mode on or off. Switching it off drops unsaved edits.
+    fn toggle_editing(&mut self, cx: &mut Context<Self>) {
+        if self.saving {
+            return;
+        }
+        self.editing = !self.editing;
+        self.edit = None;
+        self.save_error = None;
+        self.sync_edit();
+        cx.notify();
+    }
+
+    /// Reparse the edited page, or forget the last result outside edit mode
+    fn validate(&mut self) {
+        self.validation = match (&self.database_info, &self.edit) {
+            (Some(database_info), Some(edit)) => {
+                Some(validate_page_edit(database_info, edit).map_err(|e| e.to_string()))
+            }
+            _ => None,
+        };
+    }
+
+    /// Overwrite the byte under the cursor one hex digit at a time, moving
+    /// on to the next byte once both digits are typed
+    fn type_digit(&mut self, digit: u8, cx: &mut Context<Self>) {
+        if self.saving {
+            return;
+        }
+        let (Some(edit), Some((_, cursor))) = (self.edit.as_mut(), self.selection) else {
+            return;
+        };
+        match self.pending_nibble.take() {
+            None => self.pending_nibble = Some(digit),
+            Some(high) => {
+                edit.set_byte(cursor, high << 4 | digit);
+                let next = (cursor + 1).min(edit.bytes().len() - 1);
+                self.selection = Some((next, next));
+                self.after_edit();
+            }
+        }
+        cx.notify();
+    }
+
+    fn undo(&mut self, cx: &mut Context<Self>) {
+        if self.saving {
+            return;
+        }
+        if let Some(offset) = self.edit.as_mut().and_then(PageEdit::undo) {
+            self.selection = Some((offset, offset));
+            self.after_edit();
+            cx.notify();
+        }
+    }
+
+    fn redo(&mut self, cx: &mut Context<Self>) {
+        if self.saving {
+            return;
+        }
+        if let Some(offset) = self.edit.as_mut().and_then(PageEdit::redo) {
+            self.selection = Some((offset, offset));
+            self.after_edit();
+            cx.notify();
+        }
+    }
+
+    fn after_edit(&mut self) {
+        self.pending_nibble = None;
+        self.save_error = None;
+        self.validate();
+        self.update_matches();
+    }
+
+    /// Write the edited page back to the file in the background, since the
+    /// backup copies the whole database, then start over from the saved page
+    fn save(&mut self, cx: &mut Context<Self>) {
+        let (Some(database_info), Some(edit)) = (self.database_info.clone(), self.edit.clone())
+        else {
+            return;
+        };
+        if self.saving {
+            return;
+        }
+        self.saving = true;
+        self.save_error = None;
+        cx.notify();
+
+        let page_number = edit.page_number;
+        let task = cx.background_spawn(async move { save_page_edit(&database_info, &edit) });
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+            this.update(cx, |view, cx| {
+                view.saving = false;
+                match result {
+                    Ok(backup_path) => {
+                        if view.edit.as_ref().map(|edit| edit.page_number) == Some(page_number) {
+                            view.edit = None;
+                            view.load_page(cx);
+                        }
+                        cx.emit(PageEdited {
+                            page_number,
+                            backup_path,
+                        });
+                    }
+                    Err(e) => view.save_error = Some(format!("Not saved: {}", e)),
+                }
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn move_cursor(&mut self, delta: isize, cx: &mut Context<Self>) {
+        let Some((_, cursor)) = self.selection else {
+            return;
+        };
+        let last = self.bytes().len().saturating_sub(1);
+        let offset = cursor.saturating_add_signed(delta).min(last);
+        self.pending_nibble = None;
+        self.selection = Some((offset, offset));
+        self.scroll_handle
+            .scroll_to_item(offset / BYTES_PER_ROW, ScrollStrategy::Top);
+        cx.notify();
+    }
+
+    fn handle_key_down(&mut self, event: &KeyDownEvent, window: &Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.secondary() {
+            match keystroke.key.as_str() {
+                "c" => self.copy_selection(true, cx),
+                "z" if keystroke.modifiers.shift => self.redo(cx),
+                "z" => self.undo(cx),
+                _ => return,
+            }
+            cx.stop_propagation();
+            return;
+        }
+
+        // In edit mode typing goes to the bytes unless the search field has focus
+        let handled = if self.edit.is_some() && !self.search_focus.is_focused(window) {
+            self.handle_edit_key(keystroke, cx)
+        } else {
+            self.handle_search_key(keystroke, cx)
+        };
+        if handled {
+            cx.stop_propagation();
+        }
+    }
+
+    fn handle_edit_key(&mut self, keystroke: &Keystroke, cx: &mut Context<Self>) -> bool {
+        match keystroke.key.as_str() {
+            "left" => self.move_cursor(-1, cx),
+            "right" => self.move_cursor(1, cx),
+            "up" => self.move_cursor(-(BYTES_PER_ROW as isize), cx),
+            "down" => self.move_cursor(BYTES_PER_ROW as isize, cx),
+            "escape" => {
+                self.pending_nibble = None;
+                cx.notify();
+            }
+            key => {
+                let mut chars = key.chars();
+                let (Some(digit), None) = (chars.next().and_then(|c| c.to_digit(16)), chars.next())
+                else {
+                    return false;
+                };
+                self.type_digit(digit as u8, cx);
+            }
+        }
+        true
+    }
+
+    fn handle_search_key(&mut self, keystroke: &Keystroke, cx: &mut Context<Self>) -> bool {
+        match keystroke.key.as_str() {
+            "enter" => self.step_match(keystroke.modifiers.shift, cx),
+            "escape" if !self.query.is_empty() => {
+                self.query.clear();
+                self.search(cx);
+            }
+            "escape" => {
+                self.selection = None;
+                cx.notify();
+            }
+            "backspace" => {
+                self.query.pop();
+                self.search(cx);
+            }
+            _ => {
+                let Some(text) = keystroke
+                    .key_char
+                    .as_ref()
+                    .filter(|text| !text.chars().any(char::is_control))
+                else {
+                    return false;
+                };
+                self.query.push_str(text);
+                self.search(cx);
+            }
+        }
+        true
+    }
+
+    fn render_search(&self, window: &Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focused = self.search_focus.is_focused(window)
+            || (self.edit.is_none() && self.focus_handle.is_focused(window));
+        let match_label = match (self.query.is_empty(), self.matches.len()) {
+            (true, _) => String::new(),
+            (false, 0) if self.query_bytes().is_none() => "Invalid hex".to_string(),
+            (false, 0) => "No matches".to_string(),
+            (false, count) => format!("{} of {}", self.match_index + 1, count),
+        };
+
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .child(
+                div()
+                    .id("hex-search")
+                    .track_focus(&self.search_focus)
+                    .flex_1()
+                    .px_2()
+                    .py_1()
+                    .bg(rgb(0x1e1e1e))
+                    .border_1()
+                    .border_color(if focused {
+                        rgb(0x2563eb)
+                    } else {
+                        rgb(0x3e3e3e)
+                    })
+                    .rounded_sm()
+                    .text_xs()
+                    .cursor_text()
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, _event: &MouseDownEvent, window, _cx| {
+                            window.focus(&this.search_focus);
+                        }),
+                    )
+                    .child(if self.query.is_empty() {
+                        div().text_color(rgb(0x777777)).child(if self.hex_query {
+                            "Search hex, e.g. 0d 00"
+                        } else {
+                            "Search text"
+                        })
+                    } else {
+                        div().text_color(rgb(0xffffff)).child(self.query.clone())
+                    }),
+            )
+            .child(button(
+                "hex-query-mode",
+                if self.hex_query { "Hex" } else { "Text" },
+                cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                    this.toggle_query_mode(cx);
+                }),
+            ))
+            .child(
+                div()
+                    .w(px(64.0))
+                    .text_xs()
+                    .text_color(rgb(0xaaaaaa))
+                    .child(match_label),
+            )
+    }
+
+    /// Only pages of a database file can be edited, not those of a recorded
+    /// session
+    fn can_edit(&self) -> bool {
+        self.database_info
+            .as_ref()
+            .is_some_and(|info| info.source_path.is_some())
+    }
+
+    fn field_edited(&self, field: &PageField) -> bool {
+        self.edit.as_ref().is_some_and(|edit| {
+            (field.offset as usize..field.end() as usize).any(|offset| edit.is_changed(offset))
+        })
+    }
+
+    /// Where a field is and, for numbers, the value it held when read
+    fn describe_field(&self, field: &PageField) -> String {
+        let value = match &field.value {
+            None => String::new(),
+            Some(value) if self.field_edited(field) => {
+                format!(", value {} before editing", value)
+            }
+            Some(value) => format!(", value {}", value),
+        };
+        format!("Offset {}, {} bytes{}", field.offset, field.length, value)
+    }
+
+    /// Undo, redo and save, with how the parser reads the edited page
+    fn render_edit_bar(&self, edit: &PageEdit, cx: &mut Context<Self>) -> impl IntoElement {
+        let (status, status_color) = match &self.validation {
+            None => (String::new(), 0xaaaaaa),
+            Some(Err(error)) => (format!("Does not parse: {}", error), 0xef4444),
+            Some(Ok(validation)) => match validation.new_findings.first() {
+                Some(finding) => (
+                    format!("⚠ {}: {}", finding.kind.name(), finding.message),
+                    0xf59e0b,
+                ),
+                None => (
+                    format!(
+                        "Parses as {} with {} cells",
+                        validation.page.page_type.name(),
+                        validation.page.cell_count
+                    ),
+                    0x22c55e,
+                ),
+            },
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .rounded_sm()
+            .bg(rgb(0x2d2d2d))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_xs()
+                            .text_color(rgb(0xf97316))
+                            .child(format!("Editing: {} bytes changed", edit.changed_bytes())),
+                    )
+                    .when(self.saving, |this| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0xaaaaaa))
+                                .child("Backing up the database and saving..."),
+                        )
+                    })
+                    .when(!self.saving && edit.can_undo(), |this| {
+                        this.child(button(
+                            "hex-undo",
+                            "Undo",
+                            cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                                this.undo(cx);
+                            }),
+                        ))
+                    })
+                    .when(!self.saving && edit.can_redo(), |this| {
+                        this.child(button(
+                            "hex-redo",
+                            "Redo",
+                            cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                                this.redo(cx);
+                            }),
+                        ))
+                    })
+                    .when(!self.saving && edit.changed_bytes() > 0, |this| {
+                        this.child(button(
+                            "hex-save",
+                            "Save",
+                            cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                                this.save(cx);
+                            }),
+                        ))
+                    }),
+            )
+            .child(div().text_xs().text_color(rgb(status_color)).child(status))
+            .when_some(self.save_error.clone(), |this, error| {
+                this.child(div().text_xs().text_color(rgb(0xef4444)).child(error))
+            })
+    }
+
+    /// The field under the selection cursor, and the selected range when it
+    /// spans more than one byte
+    fn render_explanation(&self, page: &AnnotatedPage, cx: &mut Context<Self>) -> impl IntoElement {
+        let cursor = self.selection.map(|(_, cursor)| cursor);
+        let field = cursor.and_then(|offset| page.field_at(offset));
+        let range = self.selected_range().filter(|range| range.len() > 1);
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .min_h(px(48.0))
+            .child(match field {
+                None => div()
+                    .text_xs()
+                    .text_color(rgb(0xaaaaaa))
+                    .child("Click a byte to see the field it belongs to"),
+                Some(field) => div()
+                    .flex()
+                    .flex_col()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .size(px(10.0))
+                                    .rounded_sm()
+                                    .bg(rgb(region_color(field.region))),
+                            )
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .font_weight(gpui::FontWeight::MEDIUM)
+                                    .text_color(rgb(0xffffff))
+                                    .child(field.name.clone()),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0xaaaaaa))
+                            .child(self.describe_field(field)),
+                    ),
+            })
+            .when_some(range, |this, range| {
+                this.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_xs()
+                                .text_color(rgb(0xcccccc))
+                                .child(format!(
+                                    "Selected {} bytes at offset {}",
+                                    range.len(),
+                                    range.start
+                                )),
+                        )
+                        .child(button(
+                            "hex-copy-hex",
+                            "Copy Hex",
+                            cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                                this.copy_selection(true, cx);
+                            }),
+                        ))
+                        .child(button(
+                            "hex-copy-text",
+                            "Copy Text",
+                            cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                                this.copy_selection(false, cx);
+                            }),
+                        )),
+                )
+            })
+    }
+
+    fn render_rows(&self, rows: Range<usize>, cx: &mut Context<Self>) -> Vec<impl IntoElement> {
+        let Some(page) = self.page.as_ref() else {
+            return Vec::new();
+        };
+        let bytes = self.bytes();
+        let cursor = self.selection.map(|(_, cursor)| cursor);
+        let selected = self.selected_range().unwrap_or_default();
+        let field = self
+            .selection
+            .and_then(|(_, cursor)| page.field_at(cursor))
+            .map(|field| field.offset as usize..field.end() as usize)
+            .unwrap_or_default();
+        let needle_len = self.query_bytes().map_or(0, |needle| needle.len());
+        let is_match = |offset: usize| {
+            let index = self
+                .matches
+                .partition_point(|&start| start + needle_len <= offset);
+            self.matches
+                .get(index)
+                .is_some_and(|&start| start <= offset)
+        };
+
+        rows.map(|row| {
+            let start = row * BYTES_PER_ROW;
+            let end = (start + BYTES_PER_ROW).min(bytes.len());
+            let byte_cell = |offset: usize, text: String, width: f32, cx: &mut Context<Self>| {
+                let region = page
+                    .field_at(offset)
+                    .map_or(0x374151, |field| region_color(field.region));
+                let background = if selected.contains(&offset) {
+                    rgb(0x2563eb)
+                } else if is_match(offset) {
+                    rgba(0xfacc15 << 8 | 0x99)
+                } else if field.contains(&offset) {
+                    rgba(region << 8 | 0xaa)
+                } else {
+                    rgba(region << 8 | 0x40)
+                };
+
+                let changed = self
+                    .edit
+                    .as_ref()
+                    .is_some_and(|edit| edit.is_changed(offset));
+
+                div()
+                    .w(px(width))
+                    .flex()
+                    .justify_center()
+                    .bg(background)
+                    .when(changed, |this| {
+                        this.text_color(rgb(0xf97316))
+                            .font_weight(gpui::FontWeight::BOLD)
+                    })
+                    .cursor_pointer()
+                    .child(text)
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event: &MouseDownEvent, window, cx| {
+                            window.focus(&this.focus_handle);
+                            this.select_byte(offset, event.modifiers.shift, cx);
+                        }),
+                    )
+                    .on_mouse_move(
+                        cx.listener(move |this, event: &MouseMoveEvent, _window, cx| {
+                            if event.pressed_button == Some(MouseButton::Left) {
+                                this.select_byte(offset, true, cx);
+                            }
+                        }),
+                    )
+            };
+
+            let mut hex = div().flex().gap(px(2.0));
+            let mut ascii = div().flex();
+            for offset in start..end {
+                let byte = bytes[offset];
+                let character = if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                };
+                let hex_text = match self.pending_nibble.filter(|_| cursor == Some(offset)) {
+                    Some(nibble) => format!("{:x}_", nibble),
+                    None => format!("{:02x}", byte),
+                };
+                hex = hex.child(byte_cell(offset, hex_text, 18.0, cx));
+                ascii = ascii.child(byte_cell(offset, character.to_string(), 8.0, cx));
+            }
+
+            div()
+                .flex()
+                .gap_3()
+                .text_xs()
+                .text_color(rgb(0xe5e5e5))
+                .child(
+                    div()
+                        .w(px(48.0))
+                        .text_color(rgb(0x777777))
+                        .child(format!("{:06x}", start)),
+                )
+                .child(hex)
+                .child(ascii)
+        })
+        .collect()
+    }
+}
+
+impl Render for HexView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let row_count = self.bytes().len().div_ceil(BYTES_PER_ROW);
+
+        div()
+            .id("hex-view")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                this.handle_key_down(event, window, cx);
+            }))
+            .w(px(560.0))
+            .bg(rgb(0x252525))
+            .border_l_1()
+            .border_color(rgb(0x3e3e3e))
+            .flex()
+            .flex_col()
+            .child(
+                div()
+                    .p_4()
+                    .border_b_1()
+                    .border_color(rgb(0x3e3e3e))
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        div()
+                            .text_lg()
+                            .font_weight(gpui::FontWeight::BOLD)
+                            .text_color(rgb(0xffffff))
+                            .child("Hex View"),
+                    )
+                    .when_some(self.selected_page, |this, page_number| {
+                        this.child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .text_sm()
+                                        .text_color(rgb(0xaaaaaa))
+                                        .child(format!("Page {}", page_number)),
+                                )
+                                .when(self.can_edit() && !self.saving, |this| {
+                                    this.child(button(
+                                        "hex-edit-mode",
+                                        if self.editing { "Stop Editing" } else { "Edit" },
+                                        cx.listener(
+                                            |this, _event: &MouseDownEvent, _window, cx| {
+                                                this.toggle_editing(cx);
+                                            },
+                                        ),
+                                    ))
+                                }),
+                        )
+                    }),
+            )
+            .child(match (self.page.as_ref(), self.error.clone()) {
+                (_, Some(error)) => div()
+                    .p_4()
+                    .text_sm()
+                    .text_color(rgb(0xef4444))
+                    .child(error)
+                    .into_any_element(),
+                (None, None) => div()
+                    .p_4()
+                    .text_sm()
+                    .text_color(rgb(0xaaaaaa))
+                    .child(if self.loading {
+                        "Reading page..."
+                    } else {
+                        "Select a page to view its bytes"
+                    })
+                    .into_any_element(),
+                (Some(page), None) => div()
+                    .flex_1()
+                    .min_h_0()
+                    .flex()
+                    .flex_col()
+                    .child(
+                        div()
+                            .p_2()
+                            .flex()
+                            .flex_col()
+                            .gap_2()
+                            .border_b_1()
+                            .border_color(rgb(0x3e3e3e))
+                            .child(self.render_search(window, cx))
+                            .when_some(self.edit.as_ref(), |this, edit| {
+                                this.child(self.render_edit_bar(edit, cx))
+                            })
+                            .child(self.render_explanation(page, cx)),
+                    )
+                    .child(
+                        uniform_list(
+                            cx.entity().clone(),
+                            "hex-rows",
+                            row_count,
+                            |this, rows, _window, cx| this.render_rows(rows, cx),
+                        )
+                        .track_scroll(self.scroll_handle.clone())
+                        .flex_1()
+                        .p_2(),
+                    )
+                    .into_any_element(),
+            })
+    }
+}
+
+fn button(
+    id: &'static str,
+    label: &'static str,
+    on_click: impl Fn(&MouseDownEvent, &mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .id(id)
+        .px_2()
+        .py_1()
+        .bg(rgb(0x374151))
+        .hover(|this| this.bg(rgb(0x4b5563)))
+        .rounded_sm()
+        .cursor_pointer()
+        .text_xs()
+        .text_color(rgb(0xffffff))
+        .on_mouse_down(MouseButton::Left, on_click)
+        .child(label)
+}
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::models::{DatabaseInfo, FindingKind, PageInfo};
+use crate::ui::entities::PageSelected;
+use gpui::{
+    Context, EventEmitter, IntoElement, MouseDownEvent, ParentElement, Render, Window, div,
+    prelude::*, px, rgb,
+};
+
+/// Panel listing the structural problems the integrity check found, grouped
+/// by page. Clicking a page selects it in the grid.
+pub struct IssuesPanel {
+    database_info: Option<Arc<DatabaseInfo>>,
+    selected_page: Option<u32>,
+}
+
+impl EventEmitter<PageSelected> for IssuesPanel {}
+
+impl IssuesPanel {
+    pub fn new() -> Self {
+        Self {
+            database_info: None,
+            selected_page: None,
+        }
+    }
+
+    pub fn update_data(&mut self, database_info: Option<Arc<DatabaseInfo>>, cx: &mut Context<Self>) {
+        self.database_info = database_info;
+        cx.notify();
+    }
+
+    pub fn set_selected_page(&mut self, page_number: Option<u32>, cx: &mut Context<Self>) {
+        self.selected_page = page_number;
+        cx.notify();
+    }
+
+    fn select_page(&mut self, page_number: u32, cx: &mut Context<Self>) {
+        self.selected_page = Some(page_number);
+        cx.emit(PageSelected { page_number });
+        cx.notify();
+    }
+
+    fn render_summary(&self, database_info: &DatabaseInfo) -> impl IntoElement {
+        let mut counts = BTreeMap::<FindingKind, usize>::new();
+        for page in database_info.pages_with_findings() {
+            for finding in &page.findings {
+                *counts.entry(finding.kind).or_default() += 1;
+            }
+        }
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .border_b_1()
+            .border_color(rgb(0x3e3e3e))
+            .children(counts.into_iter().map(|(kind, count)| {
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(div().text_xs().text_color(rgb(0xaaaaaa)).child(kind.name()))
+                    .child(div().text_xs().text_color(rgb(0xffffff)).child(count.to_string()))
+            }))
+    }
+
+    fn render_page(&self, page: &PageInfo, cx: &mut Context<Self>) -> impl IntoElement {
+        let page_number = page.page_number;
+        let is_selected = self.selected_page == Some(page_number);
+
+        div()
+            .id(("issue-page", page_number as usize))
+            .flex()
+            .flex_col()
+            .gap_1()
+            .px_2()
+            .py_1()
+            .rounded_sm()
+            .cursor_pointer()
+            .when(is_selected, |this| this.bg(rgb(0x3b3b3b)))
+            .hover(|this| this.bg(rgb(0x333333)))
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                    this.select_page(page_number, cx);
+                }),
+            )
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(rgb(0xffffff))
+                    .child(page.summary()),
+            )
+            .children(page.findings.iter().map(|finding| {
+                div()
+                    .text_xs()
+                    .text_color(rgb(0xf59e0b))
+                    .child(format!("⚠ {}: {}", finding.kind.name(), finding.message))
+            }))
+    }
+}
+
+impl Render for IssuesPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let database_info = self.database_info.clone();
+        let database_info = database_info
+            .as_ref()
+            .filter(|info| info.finding_count() > 0);
+
+        div()
+            .id("issues-panel")
+            .w(px(280.0))
+            .bg(rgb(0x252525))
+            .border_l_1()
+            .border_color(rgb(0x3e3e3e))
+            .flex()
+            .flex_col()
+            .child(
+                div().p_4().border_b_1().border_color(rgb(0x3e3e3e)).child(
+                    div()
+                        .text_lg()
+                        .font_weight(gpui::FontWeight::BOLD)
+                        .text_color(rgb(0xffffff))
+                        .child("Integrity Issues"),
+                ),
+            )
+            .child(match database_info {
+                None => div()
+                    .p_4()
+                    .text_sm()
+                    .text_color(rgb(0xaaaaaa))
+                    .child("No structural problems found")
+                    .into_any_element(),
+                Some(database_info) => div()
+                    .id("issues-panel-content")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .flex()
+                    .flex_col()
+                    .child(self.render_summary(database_info))
+                    .child(
+                        div().p_2().flex().flex_col().gap_1().children(
+                            database_info
+                                .pages_with_findings()
+                                .map(|page| self.render_page(page, cx)),
+                        ),
+                    )
+                    .into_any_element(),
+            })
+    }
+}
+
+impl Default for IssuesPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,21 @@
+pub mod btree_view;
+pub mod diff_view;
 pub mod file_dialog;
+pub mod hex_view;
+pub mod issues_panel;
 pub mod page_grid;
 pub mod page_sidebar;
+pub mod session_timeline;
+pub mod wal_panel;
+pub mod wal_timeline;
 
+pub use btree_view::*;
+pub use diff_view::*;
 pub use file_dialog::*;
+pub use hex_view::*;
+pub use issues_panel::*;
 pub use page_grid::*;
-pub use page_sidebar::*;
\ No newline at end of file
+pub use page_sidebar::*;
+pub use session_timeline::*;
+pub use wal_panel::*;
+pub use wal_timeline::*;
\ No newline at end of file
@@ -1,11 +1,21 @@
-use std::{collections::BTreeMap, sync::Arc, time::Instant};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use crate::models::PageInfo;
+use crate::models::{PageChange, PageInfo, PageReach, Reachability};
+use crate::ui::components::TextTooltip;
 use gpui::{
-    Context, EventEmitter, IntoElement, ParentElement, Render, Window, div, prelude::*, px, rgb,
-    MouseDownEvent,
+    Context, EventEmitter, IntoElement, ParentElement, Render, SharedString, Task, Window, div,
+    prelude::*, px, rgb, rgba, MouseDownEvent,
 };
 
+/// How long pages changed by a reload stay outlined
+const RECENT_CHANGE_FADE: Duration = Duration::from_secs(8);
+/// Interval between repaints while the outline fades
+const RECENT_CHANGE_FRAME: Duration = Duration::from_millis(100);
+
 #[derive(Clone, Debug)]
 pub struct PageSelected {
     pub page_number: u32,
@@ -48,6 +58,14 @@ impl SelectionState {
 pub struct PageGrid {
     pages: Arc<BTreeMap<u32, PageInfo>>,
     selection_state: SelectionState,
+    /// Pages drawn with an accent outline, such as those a WAL commit wrote
+    highlighted_pages: BTreeSet<u32>,
+    /// Pages changed by the last reload, outlined until the outline fades out
+    recent_changes: BTreeMap<u32, PageChange>,
+    recent_changes_at: Instant,
+    fade_task: Option<Task<()>>,
+    /// When set, lost and shared pages are marked and every other page is dimmed
+    reachability_overlay: Option<Reachability>,
 }
 
 impl EventEmitter<PageSelected> for PageGrid {}
@@ -57,6 +75,11 @@ impl PageGrid {
         Self {
             pages,
             selection_state: SelectionState::new(),
+            highlighted_pages: BTreeSet::new(),
+            recent_changes: BTreeMap::new(),
+            recent_changes_at: Instant::now(),
+            fade_task: None,
+            reachability_overlay: None,
         }
     }
 
@@ -65,6 +88,53 @@ impl PageGrid {
         cx.notify();
     }
 
+    pub fn set_highlighted_pages(&mut self, pages: BTreeSet<u32>, cx: &mut Context<Self>) {
+        self.highlighted_pages = pages;
+        cx.notify();
+    }
+
+    pub fn set_reachability_overlay(
+        &mut self,
+        reachability: Option<Reachability>,
+        cx: &mut Context<Self>,
+    ) {
+        self.reachability_overlay = reachability;
+        cx.notify();
+    }
+
+    /// Flash an outline around pages changed by a reload, fading it out
+    /// over `RECENT_CHANGE_FADE`
+    pub fn show_recent_changes(&mut self, changes: BTreeMap<u32, PageChange>, cx: &mut Context<Self>) {
+        self.recent_changes = changes;
+        self.recent_changes_at = Instant::now();
+        self.fade_task = (!self.recent_changes.is_empty()).then(|| {
+            cx.spawn(async move |this, cx| {
+                loop {
+                    cx.background_executor().timer(RECENT_CHANGE_FRAME).await;
+                    let fading = this.update(cx, |grid, cx| {
+                        if grid.recent_change_strength().is_none() {
+                            grid.recent_changes.clear();
+                        }
+                        cx.notify();
+                        !grid.recent_changes.is_empty()
+                    });
+                    if !matches!(fading, Ok(true)) {
+                        break;
+                    }
+                }
+            })
+        });
+        cx.notify();
+    }
+
+    /// Opacity of the recent change outline, from 1.0 right after the reload
+    /// down to 0.0, or `None` once it has faded out
+    fn recent_change_strength(&self) -> Option<f32> {
+        let elapsed = self.recent_changes_at.elapsed();
+        (elapsed < RECENT_CHANGE_FADE)
+            .then(|| 1.0 - elapsed.as_secs_f32() / RECENT_CHANGE_FADE.as_secs_f32())
+    }
+
     pub fn select_page(&mut self, page_number: u32, cx: &mut Context<Self>) {
         // Only update if the selection actually changed to prevent flickering
         if !self.selection_state.is_selected(page_number) {
@@ -128,6 +198,11 @@ impl PageGrid {
             rows.push(current_row);
         }
 
+        let change_alpha = self
+            .recent_change_strength()
+            .map(|strength| (strength * 255.0) as u32)
+            .unwrap_or(0);
+
         let mut grid_container = div()
             .id("page-grid")
             .flex()
@@ -141,6 +216,17 @@ impl PageGrid {
             for page in row {
                 let page_number = page.page_number;
                 let is_selected = self.selection_state.is_selected(page_number);
+                let is_highlighted = self.highlighted_pages.contains(&page_number);
+                let recent_change = self
+                    .recent_changes
+                    .get(&page_number)
+                    .filter(|_| change_alpha > 0)
+                    .copied();
+                let reach = self
+                    .reachability_overlay
+                    .as_ref()
+                    .map(|reachability| reachability.reach(page_number));
+                let summary = SharedString::from(page.summary());
 
                 row_div = row_div.child(
                     div()
@@ -154,10 +240,23 @@ impl PageGrid {
                                 .shadow_lg()
                                 .opacity(1.0)
                         })
-                        .when(!is_selected, |this| {
-                            this.border_1()
+                        .when(!is_selected && is_highlighted, |this| {
+                            this.border_2().border_color(rgb(0xf97316))
+                        })
+                        .when(!is_selected && !is_highlighted, |this| match recent_change {
+                            Some(change) => this
+                                .border_2()
+                                .border_color(rgba(recent_change_color(change) << 8 | change_alpha)),
+                            None => this
+                                .border_1()
                                 .border_color(rgb(0x555555))
-                                .opacity(0.9)
+                                .opacity(0.9),
+                        })
+                        .when_some(reach, |this, reach| match reach {
+                            PageReach::Reached => this.opacity(0.35),
+                            reach => this.when(!is_selected, |this| {
+                                this.border_2().border_color(reach_color(reach))
+                            }),
                         })
                         .rounded_md()
                         .flex()
@@ -178,6 +277,10 @@ impl PageGrid {
                                 this.select_page(page_number, cx);
                             }),
                         )
+                        .tooltip(move |_window, cx| {
+                            let text = summary.clone();
+                            cx.new(|_| TextTooltip { text }).into()
+                        })
                         .child(
                             div()
                                 .text_xs()
@@ -191,7 +294,51 @@ impl PageGrid {
                                 .text_color(rgb(0xffffff))
                                 .opacity(0.8)
                                 .child(page.page_type.short_name()),
-                        ),
+                        )
+                        // Current version of the page lives in the write-ahead log
+                        .when(page.wal_frame.is_some(), |this| {
+                            this.child(
+                                div()
+                                    .text_xs()
+                                    .font_weight(gpui::FontWeight::BOLD)
+                                    .text_color(rgb(0xfacc15))
+                                    .child("WAL"),
+                            )
+                        })
+                        // Original version of the page is saved in the rollback journal
+                        .when(page.journaled, |this| {
+                            this.child(
+                                div()
+                                    .text_xs()
+                                    .font_weight(gpui::FontWeight::BOLD)
+                                    .text_color(rgb(0xa78bfa))
+                                    .child("JRN"),
+                            )
+                        })
+                        // The integrity check found problems with the page
+                        .when(!page.findings.is_empty(), |this| {
+                            this.child(
+                                div()
+                                    .text_xs()
+                                    .font_weight(gpui::FontWeight::BOLD)
+                                    .text_color(rgb(0xf59e0b))
+                                    .child(format!("⚠ {}", page.findings.len())),
+                            )
+                        })
+                        .when_some(reach, |this, reach| {
+                            let label = match reach {
+                                PageReach::Reached => return this,
+                                PageReach::Lost => "LOST".to_string(),
+                                PageReach::Shared(count) => format!("×{}", count),
+                            };
+                            this.child(
+                                div()
+                                    .text_xs()
+                                    .font_weight(gpui::FontWeight::BOLD)
+                                    .text_color(reach_color(reach))
+                                    .child(label),
+                            )
+                        }),
                 );
             }
             
@@ -201,3 +348,20 @@ impl PageGrid {
         grid_container
     }
 }
+
+/// Outline color for a page the reachability overlay marks
+fn reach_color(reach: PageReach) -> gpui::Rgba {
+    match reach {
+        PageReach::Lost => rgb(0xe879f9),
+        _ => rgb(0xf43f5e),
+    }
+}
+
+/// Outline color for a page changed by the last reload
+fn recent_change_color(change: PageChange) -> u32 {
+    match change {
+        PageChange::Added => 0x22c55e,
+        PageChange::Modified => 0x38bdf8,
+        PageChange::Freed => 0xef4444,
+    }
+}
@@ -1,7 +1,11 @@
 use std::sync::Arc;
 use std::time::Instant;
 
-use crate::models::{DatabaseInfo, PageInfo};
+use crate::models::{
+    DatabaseInfo, PageInfo, PageReach, PageType, Reachability, Record, ReloadChanges,
+};
+use crate::parser::journal::{JournalBeforeImage, read_before_image};
+use crate::parser::record::read_page_records;
 use crate::ui::components;
 use gpui::{Context, IntoElement, ParentElement, Render, Window, div, prelude::*, px, rgb};
 
@@ -17,6 +21,12 @@ pub struct PageSidebar {
     pub selected_page: Option<u32>,
     database_info: Option<Arc<DatabaseInfo>>,
     state: SidebarState,
+    records: Vec<Record>,
+    records_error: Option<String>,
+    before_image: Option<JournalBeforeImage>,
+    before_image_error: Option<String>,
+    /// Summary of the last reload or session step, shown above the page details
+    reload_changes: Option<ReloadChanges>,
     last_update: Instant,
 }
 
@@ -26,6 +36,11 @@ impl PageSidebar {
             selected_page: None,
             database_info: None,
             state: SidebarState::Empty,
+            records: Vec::new(),
+            records_error: None,
+            before_image: None,
+            before_image_error: None,
+            reload_changes: None,
             last_update: Instant::now(),
         }
     }
@@ -47,7 +62,9 @@ impl PageSidebar {
         } else {
             self.state = SidebarState::Error("Page not found".to_string());
         }
-        
+
+        self.load_records();
+        self.load_before_image();
         self.last_update = Instant::now();
         cx.notify();
     }
@@ -78,13 +95,83 @@ impl PageSidebar {
                 }
             }
         }
-        
+
+        self.load_records();
+        self.load_before_image();
         self.last_update = Instant::now();
         cx.notify();
     }
 
 
 
+    pub fn set_reload_changes(&mut self, changes: Option<ReloadChanges>, cx: &mut Context<Self>) {
+        self.reload_changes = changes;
+        cx.notify();
+    }
+
+    /// Decode the rows stored on the selected page when it is a leaf page
+    fn load_records(&mut self) {
+        self.records.clear();
+        self.records_error = None;
+
+        let SidebarState::Loaded(page_info) = &self.state else {
+            return;
+        };
+        if !matches!(
+            page_info.page_type,
+            PageType::TableBTreeLeaf | PageType::IndexBTreeLeaf
+        ) {
+            return;
+        }
+        let Some(database_info) = &self.database_info else {
+            return;
+        };
+
+        match read_page_records(database_info, page_info.page_number) {
+            Ok(records) => self.records = records,
+            Err(e) => self.records_error = Some(format!("Failed to decode rows: {}", e)),
+        }
+    }
+
+    /// Read the original image of the selected page from the rollback journal
+    fn load_before_image(&mut self) {
+        self.before_image = None;
+        self.before_image_error = None;
+
+        let SidebarState::Loaded(page_info) = &self.state else {
+            return;
+        };
+        if !page_info.journaled {
+            return;
+        }
+        let Some(database_info) = &self.database_info else {
+            return;
+        };
+
+        match read_before_image(database_info, page_info.page_number) {
+            Ok(before_image) => self.before_image = before_image,
+            Err(e) => {
+                self.before_image_error = Some(format!("Failed to read journaled page: {}", e))
+            }
+        }
+    }
+
+    /// The reachability analysis, when it marked the page as lost or shared
+    fn marked_reachability(&self, page_number: u32) -> Option<&Reachability> {
+        self.database_info
+            .as_ref()
+            .map(|info| &info.reachability)
+            .filter(|reachability| reachability.reach(page_number) != PageReach::Reached)
+    }
+
+    /// Page size and usable size, which excludes the reserved bytes at the
+    /// end of every page
+    fn page_geometry(&self) -> Option<(usize, usize)> {
+        let header = &self.database_info.as_ref()?.header;
+        let page_size = header.actual_page_size();
+        Some((page_size, page_size - header.reserved_space as usize))
+    }
+
     fn get_selected_page_info(&self) -> Option<&PageInfo> {
         self.database_info
             .as_ref()?
@@ -176,20 +263,78 @@ impl Render for PageSidebar {
                     .overflow_hidden()
                     .child(
                         div()
+                            .id("page-sidebar-content")
                             .h_full()
+                            .overflow_y_scroll()
+                            .when_some(self.reload_changes.as_ref(), |this, changes| {
+                                this.child(
+                                    div()
+                                        .px_4()
+                                        .pt_4()
+                                        .child(components::render_reload_changes(changes)),
+                                )
+                            })
                             .child(match &self.state {
                                 SidebarState::Empty => self.render_empty_state().into_any_element(),
                                 SidebarState::Loading(_page_num) => self.render_loading_indicator().into_any_element(),
                                 SidebarState::Loaded(page_info) => {
                                     div()
                                         .p_4()
-                                        .max_h_full()
+                                        .flex()
+                                        .flex_col()
+                                        .gap_4()
                                         .child(components::render_page_details(
                                             page_info,
                                             self.database_info
                                                 .as_ref()
                                                 .map(|info| info.header.actual_page_size()),
                                         ))
+                                        .when_some(self.page_geometry(), |this, (page_size, usable_size)| {
+                                            this.child(components::render_page_anatomy(
+                                                page_info,
+                                                page_size,
+                                                usable_size,
+                                            ))
+                                        })
+                                        .when(!page_info.findings.is_empty(), |this| {
+                                            this.child(components::render_findings(&page_info.findings))
+                                        })
+                                        .when_some(self.marked_reachability(page_info.page_number), |this, reachability| {
+                                            this.child(components::render_page_reachability(
+                                                page_info.page_number,
+                                                reachability,
+                                            ))
+                                        })
+                                        .when(!page_info.cells.is_empty(), |this| {
+                                            this.child(components::render_cell_table(page_info))
+                                        })
+                                        .when(!self.records.is_empty(), |this| {
+                                            this.child(components::render_record_list(&self.records))
+                                        })
+                                        .when_some(self.records_error.clone(), |this, error| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(rgb(0xef4444))
+                                                    .child(error),
+                                            )
+                                        })
+                                        .when_some(self.before_image.as_ref(), |this, before_image| {
+                                            this.child(components::render_before_image(
+                                                before_image,
+                                                self.database_info
+                                                    .as_ref()
+                                                    .map(|info| info.header.actual_page_size()),
+                                            ))
+                                        })
+                                        .when_some(self.before_image_error.clone(), |this, error| {
+                                            this.child(
+                                                div()
+                                                    .text_xs()
+                                                    .text_color(rgb(0xef4444))
+                                                    .child(error),
+                                            )
+                                        })
                                         .into_any_element()
                                 }
                                 SidebarState::Error(error) => self.render_error_state(error.clone()).into_any_element(),
@@ -0,0 +1,243 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::RecordedSession;
+use gpui::{
+    Context, EventEmitter, IntoElement, MouseDownEvent, ParentElement, Render, Task, Window, div,
+    prelude::*, px, rgb,
+};
+
+/// Delay between states while a session plays back
+const PLAYBACK_STEP: Duration = Duration::from_millis(600);
+
+#[derive(Clone, Debug)]
+pub struct SessionPositionSelected {
+    pub position: usize,
+}
+
+/// Timeline over the states of a recorded session, which can be stepped
+/// through or played back like an animation
+pub struct SessionTimeline {
+    session: Option<Arc<RecordedSession>>,
+    position: usize,
+    playback: Option<Task<()>>,
+}
+
+impl EventEmitter<SessionPositionSelected> for SessionTimeline {}
+
+impl SessionTimeline {
+    pub fn new() -> Self {
+        Self {
+            session: None,
+            position: 0,
+            playback: None,
+        }
+    }
+
+    pub fn update_data(&mut self, session: Option<Arc<RecordedSession>>, cx: &mut Context<Self>) {
+        self.session = session;
+        self.position = 0;
+        self.playback = None;
+        cx.notify();
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.session
+            .as_ref()
+            .map_or(0, |session| session.snapshots.len())
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: usize, cx: &mut Context<Self>) {
+        let position = position.min(self.state_count().saturating_sub(1));
+        if position == self.position {
+            return;
+        }
+
+        self.position = position;
+        cx.emit(SessionPositionSelected { position });
+        cx.notify();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Step through the remaining states, starting over when already at the end
+    pub fn play(&mut self, cx: &mut Context<Self>) {
+        if self.position + 1 >= self.state_count() {
+            self.set_position(0, cx);
+        }
+
+        self.playback = Some(cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(PLAYBACK_STEP).await;
+                let playing = this.update(cx, |timeline, cx| {
+                    timeline.set_position(timeline.position + 1, cx);
+                    let playing = timeline.position + 1 < timeline.state_count();
+                    if !playing {
+                        timeline.playback = None;
+                        cx.notify();
+                    }
+                    playing
+                });
+                if !matches!(playing, Ok(true)) {
+                    break;
+                }
+            }
+        }));
+        cx.notify();
+    }
+
+    pub fn pause(&mut self, cx: &mut Context<Self>) {
+        self.playback = None;
+        cx.notify();
+    }
+
+    fn render_step_button(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        enabled: bool,
+        target: usize,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        div()
+            .id(id)
+            .px_2()
+            .rounded_sm()
+            .text_xs()
+            .text_color(if enabled { rgb(0xffffff) } else { rgb(0x666666) })
+            .bg(rgb(0x374151))
+            .when(enabled, |this| {
+                this.cursor_pointer()
+                    .hover(|this| this.bg(rgb(0x4b5563)))
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                            this.pause(cx);
+                            this.set_position(target, cx);
+                        }),
+                    )
+            })
+            .child(label)
+    }
+}
+
+impl Render for SessionTimeline {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let state_count = self.state_count();
+        let position = self.position;
+        let last = state_count.saturating_sub(1);
+        let caption = match self
+            .session
+            .as_ref()
+            .and_then(|session| session.snapshots.get(position))
+        {
+            Some(snapshot) => format!(
+                "State {} of {} at {:.1}s — {} pages stored",
+                position + 1,
+                state_count,
+                snapshot.elapsed.as_secs_f32(),
+                snapshot.pages.len()
+            ),
+            None => "No recorded states".to_string(),
+        };
+
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_4()
+            .py_2()
+            .bg(rgb(0x252525))
+            .border_b_1()
+            .border_color(rgb(0x3e3e3e))
+            .child(
+                div()
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(rgb(0xaaaaaa))
+                    .child("Session"),
+            )
+            .child(
+                div()
+                    .id("session-play")
+                    .px_2()
+                    .rounded_sm()
+                    .text_xs()
+                    .text_color(rgb(0xffffff))
+                    .bg(rgb(0x374151))
+                    .cursor_pointer()
+                    .hover(|this| this.bg(rgb(0x4b5563)))
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(|this, _event: &MouseDownEvent, _window, cx| {
+                            if this.is_playing() {
+                                this.pause(cx);
+                            } else {
+                                this.play(cx);
+                            }
+                        }),
+                    )
+                    .child(if self.is_playing() { "⏸" } else { "⏵" }),
+            )
+            .child(self.render_step_button(
+                "session-previous",
+                "◀",
+                position > 0,
+                position.saturating_sub(1),
+                cx,
+            ))
+            .child(
+                div().flex().flex_1().gap(px(2.0)).h(px(16.0)).children(
+                    (0..state_count).map(|step| {
+                        div()
+                            .id(("session-step", step))
+                            .flex_1()
+                            .h_full()
+                            .rounded_sm()
+                            .cursor_pointer()
+                            .bg(if step == position {
+                                rgb(0x38bdf8)
+                            } else if step < position {
+                                rgb(0x4b5563)
+                            } else {
+                                rgb(0x333333)
+                            })
+                            .hover(|this| this.bg(rgb(0x6b7280)))
+                            .on_mouse_down(
+                                gpui::MouseButton::Left,
+                                cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                                    this.pause(cx);
+                                    this.set_position(step, cx);
+                                }),
+                            )
+                    }),
+                ),
+            )
+            .child(self.render_step_button(
+                "session-next",
+                "▶",
+                position < last,
+                position + 1,
+                cx,
+            ))
+            .child(
+                div()
+                    .w(px(260.0))
+                    .text_xs()
+                    .text_color(rgb(0xffffff))
+                    .child(caption),
+            )
+    }
+}
+
+impl Default for SessionTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::models::{
+    DatabaseInfo, WalCheckpointInfo, WalFrame, WalIndexInfo, WalInfo, WalTransaction,
+};
+use crate::ui::entities::PageSelected;
+use gpui::{
+    Context, EventEmitter, IntoElement, MouseDownEvent, ParentElement, Render, Window, div,
+    prelude::*, px, rgb,
+};
+
+/// Panel listing the write-ahead log's transactions and the frames inside them,
+/// followed by the state recorded in the `-shm` wal-index
+pub struct WalPanel {
+    database_info: Option<Arc<DatabaseInfo>>,
+    selected_page: Option<u32>,
+    /// Indices of the transactions whose frames are shown
+    expanded_transactions: HashSet<usize>,
+}
+
+impl EventEmitter<PageSelected> for WalPanel {}
+
+impl WalPanel {
+    pub fn new() -> Self {
+        Self {
+            database_info: None,
+            selected_page: None,
+            expanded_transactions: HashSet::new(),
+        }
+    }
+
+    pub fn update_data(&mut self, database_info: Option<Arc<DatabaseInfo>>, cx: &mut Context<Self>) {
+        let transaction_count = database_info
+            .as_ref()
+            .and_then(|info| info.wal.as_ref())
+            .map_or(0, |wal| wal.transactions.len());
+        self.expanded_transactions
+            .retain(|&index| index < transaction_count);
+        self.database_info = database_info;
+        cx.notify();
+    }
+
+    pub fn set_selected_page(&mut self, page_number: Option<u32>, cx: &mut Context<Self>) {
+        self.selected_page = page_number;
+        cx.notify();
+    }
+
+    fn toggle_transaction(&mut self, index: usize, cx: &mut Context<Self>) {
+        if !self.expanded_transactions.remove(&index) {
+            self.expanded_transactions.insert(index);
+        }
+        cx.notify();
+    }
+
+    fn select_page(&mut self, page_number: u32, cx: &mut Context<Self>) {
+        self.selected_page = Some(page_number);
+        cx.emit(PageSelected { page_number });
+        cx.notify();
+    }
+
+    fn render_summary(&self, wal: &WalInfo) -> impl IntoElement {
+        let row = |label: &'static str, value: String| {
+            div()
+                .flex()
+                .justify_between()
+                .child(div().text_xs().text_color(rgb(0xaaaaaa)).child(label))
+                .child(div().text_xs().text_color(rgb(0xffffff)).child(value))
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .border_b_1()
+            .border_color(rgb(0x3e3e3e))
+            .child(row("Frames", wal.frames.len().to_string()))
+            .child(row("Valid Frames", wal.valid_frame_count().to_string()))
+            .child(row("Transactions", wal.transactions.len().to_string()))
+            .child(row("Page Size", format!("{} bytes", wal.header.page_size)))
+            .child(row(
+                "Checkpoint Sequence",
+                wal.header.checkpoint_sequence.to_string(),
+            ))
+            .child(row(
+                "Salts",
+                format!("{:08x} {:08x}", wal.header.salt1, wal.header.salt2),
+            ))
+            .child(row(
+                "Header Checksum",
+                if wal.header_checksum_valid {
+                    "✓ Valid".to_string()
+                } else {
+                    "⚠ Mismatch".to_string()
+                },
+            ))
+    }
+
+    fn render_wal_index(&self, index: &WalIndexInfo) -> impl IntoElement {
+        let header = index.header();
+        let row = |label: String, value: String| {
+            div()
+                .flex()
+                .justify_between()
+                .child(div().text_xs().text_color(rgb(0xaaaaaa)).child(label))
+                .child(div().text_xs().text_color(rgb(0xffffff)).child(value))
+        };
+        let pinning = index.pinning_read_marks();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .border_t_1()
+            .border_color(rgb(0x3e3e3e))
+            .child(
+                div()
+                    .text_sm()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(rgb(0xffffff))
+                    .child("WAL Index (-shm)"),
+            )
+            .child(row("mxFrame".to_string(), header.max_frame.to_string()))
+            .child(row(
+                "nBackfill".to_string(),
+                format!(
+                    "{} (attempted {})",
+                    index.checkpoint.backfill, index.checkpoint.backfill_attempted
+                ),
+            ))
+            .child(row("Database Pages".to_string(), header.page_count.to_string()))
+            .child(row(
+                "Change Counter".to_string(),
+                header.change_counter.to_string(),
+            ))
+            .child(row(
+                "Salts".to_string(),
+                format!("{:08x} {:08x}", header.salt1, header.salt2),
+            ))
+            .child(row(
+                "Header Copies".to_string(),
+                if index.headers_match() {
+                    "✓ Identical".to_string()
+                } else {
+                    "⚠ Differ".to_string()
+                },
+            ))
+            .child(row(
+                "Header Checksum".to_string(),
+                if header.checksum_valid {
+                    "✓ Valid".to_string()
+                } else {
+                    "⚠ Mismatch".to_string()
+                },
+            ))
+            .children(
+                index
+                    .checkpoint
+                    .read_marks
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, &mark)| {
+                        row(
+                            format!("Read Mark {}", slot),
+                            if mark == WalCheckpointInfo::READ_MARK_UNUSED {
+                                "unused".to_string()
+                            } else {
+                                mark.to_string()
+                            },
+                        )
+                    }),
+            )
+            .children(pinning.into_iter().map(|(slot, mark)| {
+                div()
+                    .text_xs()
+                    .text_color(rgb(0xf59e0b))
+                    .child(format!(
+                        "⚠ A reader on slot {} may be holding checkpoints at frame {} of {}",
+                        slot, mark, header.max_frame
+                    ))
+            }))
+            .children(index.issues.iter().map(|issue| {
+                div()
+                    .text_xs()
+                    .text_color(rgb(0xf59e0b))
+                    .child(format!("⚠ {}", issue))
+            }))
+    }
+
+    fn render_transaction(
+        &self,
+        index: usize,
+        transaction: &WalTransaction,
+        frames: &[WalFrame],
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let is_expanded = self.expanded_transactions.contains(&index);
+        let first_frame = frames.first().map_or(0, |frame| frame.frame_number);
+        let last_frame = frames.last().map_or(0, |frame| frame.frame_number);
+        let status = match (transaction.valid, transaction.commit_size) {
+            (true, Some(size)) => format!("commit, {} pages", size),
+            (true, None) => "uncommitted".to_string(),
+            (false, _) => "⚠ invalid".to_string(),
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .child(
+                div()
+                    .id(("wal-transaction", index))
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .px_2()
+                    .py_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(|this| this.bg(rgb(0x333333)))
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                            this.toggle_transaction(index, cx);
+                        }),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(if transaction.valid {
+                                rgb(0xffffff)
+                            } else {
+                                rgb(0xf59e0b)
+                            })
+                            .child(format!(
+                                "{} Transaction {} — frames {}–{}",
+                                if is_expanded { "▾" } else { "▸" },
+                                index + 1,
+                                first_frame,
+                                last_frame
+                            )),
+                    )
+                    .child(div().text_xs().text_color(rgb(0xaaaaaa)).child(status)),
+            )
+            .when(is_expanded, |this| {
+                this.child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .pl_4()
+                        .children(frames.iter().map(|frame| self.render_frame(frame, cx))),
+                )
+            })
+    }
+
+    fn render_frame(&self, frame: &WalFrame, cx: &mut Context<Self>) -> impl IntoElement {
+        let page_number = frame.page_number;
+        let is_selected = self.selected_page == Some(page_number);
+        let problem = if !frame.salt_valid {
+            Some("salt mismatch")
+        } else if !frame.checksum_valid {
+            Some("checksum mismatch")
+        } else {
+            None
+        };
+
+        div()
+            .id(("wal-frame", frame.frame_number))
+            .flex()
+            .justify_between()
+            .px_2()
+            .rounded_sm()
+            .cursor_pointer()
+            .when(is_selected, |this| this.bg(rgb(0x3b3b3b)))
+            .hover(|this| this.bg(rgb(0x333333)))
+            .on_mouse_down(
+                gpui::MouseButton::Left,
+                cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                    this.select_page(page_number, cx);
+                }),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0xcccccc))
+                    .child(format!("#{} page {}", frame.frame_number, page_number)),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(if problem.is_some() {
+                        rgb(0xf59e0b)
+                    } else {
+                        rgb(0xaaaaaa)
+                    })
+                    .child(match problem {
+                        Some(problem) => format!("⚠ {}", problem),
+                        None if frame.is_commit() => "commit".to_string(),
+                        None => String::new(),
+                    }),
+            )
+    }
+}
+
+impl Render for WalPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let database_info = self.database_info.clone();
+        let wal = database_info.as_ref().and_then(|info| info.wal.as_ref());
+        let wal_index = database_info
+            .as_ref()
+            .and_then(|info| info.wal_index.as_ref());
+
+        div()
+            .id("wal-panel")
+            .w(px(280.0))
+            .bg(rgb(0x252525))
+            .border_l_1()
+            .border_color(rgb(0x3e3e3e))
+            .flex()
+            .flex_col()
+            .child(
+                div().p_4().border_b_1().border_color(rgb(0x3e3e3e)).child(
+                    div()
+                        .text_lg()
+                        .font_weight(gpui::FontWeight::BOLD)
+                        .text_color(rgb(0xffffff))
+                        .child("Write-Ahead Log"),
+                ),
+            )
+            .child(match (wal, wal_index) {
+                (None, None) => div()
+                    .p_4()
+                    .text_sm()
+                    .text_color(rgb(0xaaaaaa))
+                    .child("No write-ahead log")
+                    .into_any_element(),
+                (wal, wal_index) => div()
+                    .id("wal-panel-content")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .flex()
+                    .flex_col()
+                    .when_some(wal, |this, wal| {
+                        this.child(self.render_summary(wal)).child(
+                            div().p_2().flex().flex_col().gap_1().children(
+                                wal.transactions.iter().enumerate().map(
+                                    |(index, transaction)| {
+                                        self.render_transaction(
+                                            index,
+                                            transaction,
+                                            wal.frames_of(transaction),
+                                            cx,
+                                        )
+                                    },
+                                ),
+                            ),
+                        )
+                    })
+                    .when_some(wal_index, |this, index| {
+                        this.child(self.render_wal_index(index))
+                    })
+                    .into_any_element(),
+            })
+    }
+}
+
+impl Default for WalPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use crate::models::{DatabaseInfo, DatabaseView};
+use gpui::{
+    Context, EventEmitter, IntoElement, MouseDownEvent, ParentElement, Render, Window, div,
+    prelude::*, px, rgb,
+};
+
+#[derive(Clone, Debug)]
+pub struct DatabaseViewSelected {
+    pub view: DatabaseView,
+}
+
+/// Timeline over the committed WAL transactions. Position 0 is the main file
+/// alone and position `n` is the database as of the `n`th commit.
+pub struct WalTimeline {
+    database_info: Option<Arc<DatabaseInfo>>,
+    position: usize,
+}
+
+impl EventEmitter<DatabaseViewSelected> for WalTimeline {}
+
+impl WalTimeline {
+    pub fn new() -> Self {
+        Self {
+            database_info: None,
+            position: 0,
+        }
+    }
+
+    pub fn update_data(&mut self, database_info: Option<Arc<DatabaseInfo>>, cx: &mut Context<Self>) {
+        self.database_info = database_info;
+        self.position = match self.database_info.as_ref().map(|info| info.view) {
+            Some(DatabaseView::WithWal) => self.commit_count(),
+            Some(DatabaseView::AtTransaction(index)) => (index + 1).min(self.commit_count()),
+            Some(DatabaseView::MainFile) | None => 0,
+        };
+        cx.notify();
+    }
+
+    /// Number of valid commits in the WAL, which is the last timeline position
+    pub fn commit_count(&self) -> usize {
+        self.database_info
+            .as_ref()
+            .and_then(|info| info.wal.as_ref())
+            .map_or(0, |wal| wal.valid_transaction_count())
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: usize, cx: &mut Context<Self>) {
+        let position = position.min(self.commit_count());
+        if position == self.position {
+            return;
+        }
+
+        self.position = position;
+        let view = match position {
+            0 => DatabaseView::MainFile,
+            position => DatabaseView::AtTransaction(position - 1),
+        };
+        cx.emit(DatabaseViewSelected { view });
+        cx.notify();
+    }
+
+    fn render_step_button(
+        &self,
+        id: &'static str,
+        label: &'static str,
+        enabled: bool,
+        target: usize,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        div()
+            .id(id)
+            .px_2()
+            .rounded_sm()
+            .text_xs()
+            .text_color(if enabled { rgb(0xffffff) } else { rgb(0x666666) })
+            .bg(rgb(0x374151))
+            .when(enabled, |this| {
+                this.cursor_pointer()
+                    .hover(|this| this.bg(rgb(0x4b5563)))
+                    .on_mouse_down(
+                        gpui::MouseButton::Left,
+                        cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                            this.set_position(target, cx);
+                        }),
+                    )
+            })
+            .child(label)
+    }
+}
+
+impl Render for WalTimeline {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let commit_count = self.commit_count();
+        let position = self.position;
+        let wal = self.database_info.as_ref().and_then(|info| info.wal.as_ref());
+        let caption = match wal {
+            Some(wal) if position > 0 => format!(
+                "Commit {} of {} — {} pages written",
+                position,
+                commit_count,
+                wal.transaction_pages(position - 1).len()
+            ),
+            _ => "Main file only".to_string(),
+        };
+
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .px_4()
+            .py_2()
+            .bg(rgb(0x252525))
+            .border_b_1()
+            .border_color(rgb(0x3e3e3e))
+            .child(
+                div()
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .text_color(rgb(0xaaaaaa))
+                    .child("Timeline"),
+            )
+            .child(self.render_step_button(
+                "timeline-previous",
+                "◀",
+                position > 0,
+                position.saturating_sub(1),
+                cx,
+            ))
+            .child(
+                div().flex().flex_1().gap(px(2.0)).h(px(16.0)).children(
+                    (0..=commit_count).map(|step| {
+                        div()
+                            .id(("timeline-step", step))
+                            .flex_1()
+                            .h_full()
+                            .rounded_sm()
+                            .cursor_pointer()
+                            .bg(if step == position {
+                                rgb(0xf97316)
+                            } else if step < position {
+                                rgb(0x4b5563)
+                            } else {
+                                rgb(0x333333)
+                            })
+                            .hover(|this| this.bg(rgb(0x6b7280)))
+                            .on_mouse_down(
+                                gpui::MouseButton::Left,
+                                cx.listener(move |this, _event: &MouseDownEvent, _window, cx| {
+                                    this.set_position(step, cx);
+                                }),
+                            )
+                    }),
+                ),
+            )
+            .child(self.render_step_button(
+                "timeline-next",
+                "▶",
+                position < commit_count,
+                position + 1,
+                cx,
+            ))
+            .child(
+                div()
+                    .w(px(220.0))
+                    .text_xs()
+                    .text_color(rgb(0xffffff))
+                    .child(caption),
+            )
+    }
+}
+
+impl Default for WalTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -183,6 +183,18 @@ impl StatusManager {
         }
     }
 
+    /// Dismiss the current message if it is a progress message, which only
+    /// goes away when the operation it reports on ends
+    pub fn dismiss_progress<T: 'static>(&mut self, cx: &mut Context<T>) {
+        let is_progress = matches!(
+            self.current_message.as_ref().map(|message| &message.message_type),
+            Some(StatusType::Progress(_))
+        );
+        if is_progress {
+            self.dismiss_message(cx);
+        }
+    }
+
     /// Dismiss a specific message by ID
     pub fn dismiss_message_by_id<T: 'static>(&mut self, id: StatusId, cx: &mut Context<T>) {
         // Check if it's the current message